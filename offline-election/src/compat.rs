@@ -0,0 +1,100 @@
+//! Compatibility of the runtime of the chain with this build.
+//!
+//! The storage layout of the pallets is hardcoded in this crate. A runtime newer than what this
+//! build is tested against might have changed it, and produce subtly wrong numbers rather than
+//! failing. This is checked once, when connecting.
+
+use crate::{
+	error::{self, Error, Result},
+	primitives::Hash,
+	storage, Client, LOG_TARGET,
+};
+
+/// The newest tested spec version of each known runtime.
+const TESTED_SPECS: &[(&str, u32)] = &[("polkadot", 29), ("kusama", 2029), ("westend", 49)];
+
+/// The pallets whose storage is read by this build, with the newest tested major version.
+const TESTED_PALLETS: &[(&str, u16)] = &[("Staking", 3), ("PhragmenElection", 3), ("Balances", 3)];
+
+/// The version of a pallet, as stored under `:__PALLET_VERSION__:` by the tested runtimes.
+#[derive(codec::Decode, Debug, Clone, Copy)]
+struct PalletVersion {
+	major: u16,
+	minor: u8,
+	patch: u8,
+}
+
+/// The storage key of `item` of `pallet`.
+fn version_key(pallet: &str, item: &[u8]) -> storage::StorageKey {
+	storage::StorageKey(storage::module_prefix_raw(pallet.as_bytes(), item))
+}
+
+/// Check the runtime at `at` against what this build is tested against.
+///
+/// Any incompatibility is a warning, or an error if `strict`.
+pub(crate) async fn check(
+	spec_name: &str,
+	spec_version: u32,
+	client: &Client,
+	at: Hash,
+	strict: bool,
+) -> Result<()> {
+	let mut issues = vec![];
+	match TESTED_SPECS.iter().find(|(name, _)| *name == spec_name) {
+		Some((_, tested)) if spec_version > *tested => issues.push(format!(
+			"{} runtime version {} is newer than the tested version {}",
+			spec_name, spec_version, tested
+		)),
+		Some(_) => {}
+		None => issues.push(format!("runtime {} is not tested", spec_name)),
+	}
+
+	for (pallet, tested) in TESTED_PALLETS {
+		let key = version_key(pallet, b":__PALLET_VERSION__:");
+		match error::try_read::<PalletVersion>(key, client, at).await {
+			Ok(Some(version)) if version.major > *tested => issues.push(format!(
+				"pallet {} version {}.{}.{} is newer than the tested major version {}",
+				pallet, version.major, version.minor, version.patch, tested
+			)),
+			Ok(Some(version)) => {
+				log::debug!(target: LOG_TARGET, "pallet {} version {:?}", pallet, version)
+			}
+			// older runtimes have no pallet versions, newer ones have a storage version instead.
+			Ok(None) => {}
+			// not knowing the version is no evidence of an incompatibility, hence not an issue.
+			Err(e) => log::warn!(
+				target: LOG_TARGET,
+				"failed to read the version of pallet {}: {}",
+				pallet,
+				e
+			),
+		}
+
+		// the storage version replaced the pallet version after all of the tested runtimes.
+		let key = version_key(pallet, b":__STORAGE_VERSION__:");
+		match error::try_read::<u16>(key, client, at).await {
+			Ok(Some(version)) => issues.push(format!(
+				"pallet {} has storage version {}, which no tested runtime has",
+				pallet, version
+			)),
+			Ok(None) => {}
+			Err(e) => log::warn!(
+				target: LOG_TARGET,
+				"failed to read the storage version of pallet {}: {}",
+				pallet,
+				e
+			),
+		}
+	}
+
+	if issues.is_empty() {
+		return Ok(());
+	}
+	if strict {
+		return Err(Error::Incompatible(issues.join(", ")));
+	}
+	for issue in issues {
+		log::warn!(target: LOG_TARGET, "⚠️  {}, results might be wrong. See --strict.", issue);
+	}
+	Ok(())
+}
@@ -0,0 +1,79 @@
+//! The offline part of the staking election pipeline, working on a [`Snapshot`].
+
+use crate::{primitives::AccountId, snapshot::Snapshot};
+use sp_npos_elections::*;
+use std::{collections::BTreeMap, str::FromStr};
+
+/// The election algorithms that can be used.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Algorithm {
+	/// Sequential phragmen.
+	SeqPhragmen,
+	/// PhragMMS.
+	Phragmms,
+}
+
+impl FromStr for Algorithm {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"seq-phragmen" => Ok(Self::SeqPhragmen),
+			"phragmms" => Ok(Self::Phragmms),
+			_ => Err("Invalid algorithm. Can be seq-phragmen|phragmms."),
+		}
+	}
+}
+
+/// The outcome of an election.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+	/// The elected targets, in the order of election.
+	pub winners: Vec<AccountId>,
+	/// The assignments of the voters, in absolute stake.
+	pub assignments: Vec<StakedAssignment<AccountId>>,
+	/// The supports of the winners.
+	pub supports: SupportMap<AccountId>,
+	/// The score of the outcome.
+	pub score: ElectionScore,
+}
+
+/// Run the election on the given snapshot, electing `count` targets.
+///
+/// If `reduce` is set, the assignments are reduced after the election. This does not alter the
+/// supports.
+pub fn elect(
+	snapshot: &Snapshot,
+	count: usize,
+	algorithm: Algorithm,
+	iterations: usize,
+	reduce: bool,
+) -> Result<Outcome, String> {
+	let weights =
+		snapshot.voters.iter().map(|(v, w, _)| (v.clone(), *w)).collect::<BTreeMap<_, _>>();
+	let weight_of =
+		|who: &AccountId| -> VoteWeight { weights.get(who).cloned().unwrap_or_default() };
+
+	let targets = snapshot.targets.clone();
+	let voters = snapshot.voters.clone();
+	let balancing = if iterations > 0 { Some((iterations, 0)) } else { None };
+	let ElectionResult { winners, assignments } = match algorithm {
+		Algorithm::SeqPhragmen => seq_phragmen::<AccountId, pallet_staking::ChainAccuracy>(
+			count, targets, voters, balancing,
+		),
+		Algorithm::Phragmms => {
+			phragmms::<AccountId, pallet_staking::ChainAccuracy>(count, targets, voters, balancing)
+		}
+	}
+	.map_err(|e| format!("{:?}", e))?;
+
+	let winners = winners.into_iter().map(|(w, _)| w).collect::<Vec<_>>();
+	let mut assignments = assignment_ratio_to_staked(assignments, weight_of);
+	if reduce {
+		sp_npos_elections::reduce(&mut assignments);
+	}
+	let supports =
+		to_support_map::<AccountId>(&winners, &assignments).map_err(|e| format!("{:?}", e))?;
+	let score = supports.clone().evaluate();
+
+	Ok(Outcome { winners, assignments, supports, score })
+}
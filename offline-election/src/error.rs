@@ -0,0 +1,135 @@
+//! The errors of this crate.
+//!
+//! Errors carry enough context (what key, at which block, decoded as what type) to be reported
+//! without a backtrace. Connecting, setting up the network and the sub-commands, offline ones
+//! included, return these errors, and `main` reports them.
+
+use crate::{primitives::Hash, Client};
+use codec::Decode;
+use jsonrpsee_types::jsonrpc::{to_value as to_json_value, Params};
+use std::{
+	fmt,
+	path::{Path, PathBuf},
+};
+use sub_storage::{StorageData, StorageKey};
+
+/// An error of this crate.
+#[derive(Debug)]
+pub enum Error {
+	/// Could not connect to the node.
+	Connection { uri: String, reason: String },
+	/// The network is not known, hence neither its address format nor its token.
+	UnknownNetwork(String),
+	/// The block is not known to the node.
+	UnknownBlock(Hash),
+	/// The runtime of the chain is not supported by this build, see `--strict`.
+	Incompatible(String),
+	/// Something needs a connection to a node, which `--offline` forbids.
+	Offline(&'static str),
+	/// An rpc request failed.
+	Rpc { method: &'static str, reason: String },
+	/// A storage item could not be decoded.
+	Decode { key: StorageKey, at: Hash, ty: &'static str, reason: String },
+	/// A storage item, constant or call that is needed does not exist.
+	Missing(String),
+	/// The election could not be computed.
+	Election(String),
+	/// A file could not be read, written or parsed.
+	File { path: PathBuf, reason: String },
+	/// A combination of arguments is not valid.
+	Invalid(String),
+}
+
+impl Error {
+	/// An error of the file at `path`.
+	pub(crate) fn file(path: &Path, reason: impl ToString) -> Self {
+		Error::File { path: path.to_path_buf(), reason: reason.to_string() }
+	}
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Connection { uri, reason } => {
+				write!(f, "failed to connect to {}: {}", uri, reason)
+			}
+			Error::UnknownNetwork(network) => {
+				write!(f, "unknown network {}. Can be kusama|polkadot|substrate.", network)
+			}
+			Error::UnknownBlock(at) => write!(f, "block {:?} is not known to the node", at),
+			Error::Incompatible(reason) => write!(f, "incompatible runtime: {}", reason),
+			Error::Offline(what) => write!(f, "{} needs a connection to a node", what),
+			Error::Rpc { method, reason } => write!(f, "rpc request {} failed: {}", method, reason),
+			Error::Decode { key, at, ty, reason } => write!(
+				f,
+				"storage item 0x{} at block {:?} failed to decode as {}: {}",
+				hex::encode(&key.0),
+				at,
+				ty,
+				reason
+			),
+			Error::Missing(what) => write!(f, "{} does not exist", what),
+			Error::Election(reason) => write!(f, "election failed: {}", reason),
+			Error::File { path, reason } => write!(f, "file {:?}: {}", path, reason),
+			Error::Invalid(reason) => write!(f, "invalid arguments: {}", reason),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// The result type of this crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Make an rpc request, throttled as all the requests of `sub_storage`.
+pub(crate) async fn request<T: serde::de::DeserializeOwned>(
+	client: &Client,
+	method: &'static str,
+	params: Params,
+) -> Result<T> {
+	sub_storage::throttle::request(client, method, params)
+		.await
+		.map_err(|e| Error::Rpc { method, reason: e.to_string() })
+}
+
+/// Read and decode the storage item under `key`, if it exists.
+pub(crate) async fn try_read<T: Decode>(
+	key: StorageKey,
+	client: &Client,
+	at: Hash,
+) -> Result<Option<T>> {
+	let params = Params::Array(vec![
+		to_json_value(key.clone()).expect("StorageKey serialization infallible"),
+		to_json_value(at).expect("Block hash serialization infallible"),
+	]);
+	match request::<Option<StorageData>>(client, "state_getStorage", params).await? {
+		Some(data) => <T as Decode>::decode(&mut data.0.as_slice()).map(Some).map_err(|e| {
+			Error::Decode { key, at, ty: std::any::type_name::<T>(), reason: e.to_string() }
+		}),
+		None => Ok(None),
+	}
+}
+
+/// Get the constant `name` of `module`.
+pub(crate) async fn constant<T: Decode>(
+	client: &Client,
+	module: &'static str,
+	name: &'static str,
+	at: Hash,
+) -> Result<T> {
+	sub_storage::get_const::<T>(client, module, name, at)
+		.await
+		.ok_or_else(|| Error::Missing(format!("constant {}::{}", module, name)))
+}
+
+/// Get the pallet and call index of `call` of `module`.
+pub(crate) async fn call_index(
+	client: &Client,
+	module: &'static str,
+	call: &'static str,
+	at: Hash,
+) -> Result<(u8, u8)> {
+	sub_storage::get_call_index(client, module, call, at)
+		.await
+		.ok_or_else(|| Error::Missing(format!("call {}::{}", module, call)))
+}
@@ -0,0 +1,34 @@
+//! Handling of ctrl-c.
+//!
+//! While a long scrape is in progress, the first ctrl-c only raises a flag, so that the scrape can
+//! save what it fetched so far and be resumed later. Otherwise, and on a second ctrl-c, the process
+//! exits right away.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether a resumable scrape is in progress.
+static SCRAPING: AtomicBool = AtomicBool::new(false);
+
+/// Whether ctrl-c was hit during a resumable scrape.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Install the ctrl-c handler.
+pub fn install() {
+	ctrlc::set_handler(|| {
+		if !SCRAPING.load(Ordering::SeqCst) || INTERRUPTED.swap(true, Ordering::SeqCst) {
+			std::process::exit(130);
+		}
+		eprintln!("interrupted, saving the progress of the scrape. Hit ctrl-c again to abort.");
+	})
+	.expect("Failed to set the ctrl-c handler.");
+}
+
+/// Mark the start or the end of a resumable scrape.
+pub fn set_scraping(scraping: bool) {
+	SCRAPING.store(scraping, Ordering::SeqCst);
+}
+
+/// Whether the ongoing scrape should stop and save its progress.
+pub fn interrupted() -> bool {
+	INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -90,14 +90,48 @@
 //!
 //!
 //! SUBCOMMANDS:
+//!     accuracy               Store the prediction of the next election and track the accuracy of past ones
+//!     anomaly-scan           Scan the election snapshot for oddities
+//!     backtest               Replay the past eras and report the returns of a nomination strategy
+//!     bench                  Benchmark the staking election pipeline on a snapshot file
+//!     bounds                 Compare the size of the election snapshot with the bounds of the chain
+//!     churn                  Report the churn of the nominators between two eras
+//!     clusters               Group the previous, active and predicted sets by operator
 //!     command-center         Display the command center of the staking panel
+//!     commission-history     Report the commission of validators over the eras kept in storage
+//!     controllers            Report on the stash to controller mapping
+//!     convert                Upgrade a file produced by an older version of this tool to the current format
 //!     council                Run the council election
+//!     cross-check            Compare the storage of other nodes against the one of `--uri`, at the same block
 //!     current                Display the current validators
 //!     dangling-nominators    Show the nominators who are dangling:
+//!     emergency              Compute the emergency solution of the multi-phase election
+//!     export-rewards         Export the rewards of an account over a range of eras as csv
+//!     generate               Generate a synthetic snapshot file, to be used with `bench` or `staking --input`
 //!     help                   Prints this message or the help of the given subcommand(s)
+//!     inflation              Display the staking rate, the inflation and the era reward
+//!     ledger                 Dump the staking ledger of a stash
+//!     ledger-audit           Scan all staking ledgers for inconsistencies
+//!     mine                   Mine a solution for the unsigned phase of the staking election
+//!     multi-block            Scrape the paged snapshot of the multi-block election and mine a paged solution
 //!     next                   Display the next queued validators
 //!     nominator-check        The general checkup of a nominator
+//!     overlap                Report the pairs of validators that share many of their nominators
+//!     payees                 Report the distribution of reward destinations across all stakers
+//!     payout-check           List the eras in which the payout of a validator is not claimed
+//!     repl                   Run commands interactively on one connection and one scraped snapshot
+//!     reward-pot             Estimate the reward pot of the current era
+//!     selftest               Verify the results of this build against bundled fixtures
+//!     session-info           Display the active and current era, the session and epoch progress
+//!     simulate-slash         Simulate slashing a validator of the active era
+//!     snapshot               Work with snapshot files
+//!     stake-flow             Export how the backing moved between validators from one election to the next
 //!     staking                Run the staking election
+//!     stress                 Run the staking election on synthetic snapshots of increasing size
+//!     suggest                Suggest nomination targets, subject to the constraints of a json file
+//!     targets                List all the validator candidates, active and waiting
+//!     trace                  Export the full trace of sequential phragmen on a snapshot file as json
+//!     unbonding              Report how much stake becomes withdrawable in each era of the next bonding duration
 //!     validator-check        The general checkup of a validators
 //! ```
 //!
@@ -180,12 +214,19 @@ use std::path::PathBuf;
 use structopt::StructOpt;
 use sub_storage as storage;
 
+mod compat;
+mod election;
+mod error;
+mod interrupt;
 mod network;
+mod plan;
 mod primitives;
+mod snapshot;
 #[macro_use]
 mod timing;
 /// Sub commands.
 pub mod subcommands;
+mod weights;
 
 /// Default logging target.
 pub const LOG_TARGET: &'static str = "offline-election";
@@ -205,9 +246,26 @@ pub(crate) type Client = sub_storage::Client;
 pub struct Opt {
 	/// The block number at which the scrap should happen. Use only the hex value, no need for a
 	/// `0x` prefix.
+	///
+	/// A warning is emitted if the block is not part of the finalized chain.
+	///
+	/// A scrape of the snapshot that is interrupted with ctrl-c saves its progress, and is resumed
+	/// by the same command at the same block. Only the per-voter phase of the scrape can be
+	/// resumed: the bulk reads of the targets and of the nominations that precede it are repeated.
 	#[structopt(long)]
 	at: Option<primitives::Hash>,
 
+	/// Run the sub-command at each block of a range, as `<start>..<end>:<step>` block numbers, end
+	/// excluded. The step defaults to one. Meant for sampling a value over time.
+	#[structopt(long, conflicts_with = "at")]
+	at_range: Option<network::BlockRange>,
+
+	/// If `--at` is not given, use the best head of the chain, rather than the finalized one.
+	///
+	/// The best head might be reorged away.
+	#[structopt(long, parse(from_flag), conflicts_with = "at")]
+	best: bool,
+
 	/// The node to connect to.
 	#[structopt(long, default_value = "ws://localhost:9944")]
 	uri: String,
@@ -217,10 +275,48 @@ pub struct Opt {
 	/// This will also change the token display name.
 	///
 	/// If not provided, then the spec name of the runtime version at given at will be compared to
-	/// be `polkadot`, `kusama` or `substrate`.
+	/// be `polkadot`, `kusama` or `substrate`, and any other spec name falls back to `substrate`.
 	#[structopt(short, long)]
 	network: Option<String>,
 
+	/// The timeout of each rpc request, in seconds. A request that times out fails, rather than
+	/// hanging forever on a stalled endpoint.
+	#[structopt(long, default_value = "60")]
+	rpc_timeout: u64,
+
+	/// The maximum number of rpc requests per second, for rate limited public endpoints. Zero
+	/// means unlimited. Requests that are pushed back are retried with a jittered backoff.
+	#[structopt(long, default_value = "0")]
+	max_rps: u32,
+
+	/// The overall deadline of the command, in seconds. The process exits with an error once it is
+	/// reached.
+	#[structopt(long)]
+	deadline: Option<u64>,
+
+	/// More nodes, typically of other networks, to run the same sub-command against after
+	/// `--uri`, e.g. to report on both polkadot and kusama at once. The network of each node is
+	/// detected from its runtime. Can be given multiple times.
+	///
+	/// Any output file is written once per node, and the last one wins. `--network` is ignored,
+	/// and a summary of all the nodes is printed at the end.
+	#[structopt(long, conflicts_with = "at")]
+	also: Vec<String>,
+
+	/// Fail, rather than warn, if the runtime of the chain is newer than what this build is tested
+	/// against.
+	#[structopt(long, parse(from_flag))]
+	strict: bool,
+
+	/// Instead of running the sub-command, list the storage items that it would read at `--at`
+	/// with their number of keys, and estimate the number of requests and the duration. Only the
+	/// keys are fetched.
+	///
+	/// Only the sub-commands that read the staking snapshot, `council` and `dangling-nominators`
+	/// have a plan; it is an error for the others.
+	#[structopt(long, parse(from_flag))]
+	plan: bool,
+
 	/// Print more output.
 	#[structopt(short, parse(from_occurrences))]
 	verbosity: u64,
@@ -237,10 +333,58 @@ pub enum SubCommands {
 	Staking(StakingConfig),
 	/// Run the council election.
 	Council(CouncilConfig),
+	/// Benchmark the staking election pipeline on a snapshot file.
+	///
+	/// Times seq-phragmen, balancing and reduce separately over a number of runs. Does not need a
+	/// connection to a node.
+	Bench(BenchConfig),
+	/// Generate a synthetic snapshot file, to be used with `bench` or `staking --input`.
+	///
+	/// Does not need a connection to a node.
+	Generate(GenerateConfig),
+	/// Run the election on a set of small known-good snapshots bundled in the binary, and verify
+	/// that this build produces the exact expected results.
+	///
+	/// Does not need a connection to a node.
+	Selftest {},
+	/// Work with snapshot files.
+	///
+	/// Does not need a connection to a node.
+	Snapshot(SnapshotCommand),
+	/// Run the staking election on synthetic snapshots of increasing size, and report the time
+	/// and memory usage of each phase.
+	///
+	/// Does not need a connection to a node.
+	Stress(StressConfig),
+	/// Export the full trace of sequential phragmen on a snapshot file as json: the score of
+	/// each candidate in each round, and the edge weights after each balancing iteration.
+	///
+	/// Does not need a connection to a node.
+	Trace(TraceConfig),
+	/// Upgrade an election output or snapshot file produced by an older version of this tool to
+	/// the current format.
+	///
+	/// Does not need a connection to a node.
+	Convert {
+		/// The file to convert.
+		#[structopt(long, parse(from_os_str))]
+		input: PathBuf,
+
+		/// The file to write the converted content into.
+		#[structopt(long, parse(from_os_str))]
+		output: PathBuf,
+
+		/// The desired targets of the election, for old snapshots that did not record it.
+		#[structopt(long)]
+		desired_targets: Option<u32>,
+	},
 	/// Display the current validators.
 	///
 	/// Always maps to `session::validators()`.
 	Current {},
+	/// Display the active and current era, the session and epoch progress and the estimated time
+	/// to the next era.
+	SessionInfo {},
 	/// Display the next queued validators.
 	///
 	/// Always maps to `session::queued_keys()` and should only have sane values in the first
@@ -248,6 +392,18 @@ pub enum SubCommands {
 	Next {},
 	/// Display the command center of the staking panel.
 	CommandCenter {},
+	/// Compare the storage of other nodes against the one of `--uri`, at the same block.
+	///
+	/// Useful to detect lagging or misbehaving RPC providers before trusting their data.
+	CrossCheck {
+		/// The other nodes to compare against. Can be given multiple times.
+		#[structopt(long = "with", required = true)]
+		others: Vec<String>,
+
+		/// Also compare the entire staking maps. This uses unsafe RPCs on all nodes.
+		#[structopt(long, parse(from_flag))]
+		deep: bool,
+	},
 	/// Show the nominators who are dangling:
 	///
 	/// Those who have voted for a validator who has been slashed since the nomination was
@@ -258,12 +414,362 @@ pub enum SubCommands {
 		/// The nominator's address. Both hex and ss58 encoding are acceptable.
 		#[structopt(long)]
 		who: AccountId,
+
+		/// Also report the rewards earned in each of this many past eras, capped by the history
+		/// depth.
+		#[structopt(long, default_value = "0")]
+		eras: u32,
 	},
 	/// The general checkup of a validators.
 	ValidatorCheck {
 		/// The validator's address. Both hex and ss58 encoding are acceptable.
+		#[structopt(long, required_unless_one = &["file", "erratic"])]
+		who: Option<AccountId>,
+
+		/// A file of validator stashes, one per line, to check in one batch and report on in a
+		/// consolidated manner.
+		#[structopt(long, parse(from_os_str), conflicts_with = "who")]
+		file: Option<PathBuf>,
+
+		/// Also search for the additional stake that the validator needs to be elected in the
+		/// predicted election. This re-runs the election many times.
+		#[structopt(long, conflicts_with = "file")]
+		backing_needed: bool,
+
+		/// Also list the nominators of the validator, by intent and by active exposure, with
+		/// their totals, flagging the exposures that are clipped. Sorted by stake|account.
+		#[structopt(long, conflicts_with = "file")]
+		list_nominators: Option<subcommands::validator_check::NominatorOrder>,
+
+		/// Instead, flag the validators of the active set whose era points are erratic over the
+		/// recent eras.
+		#[structopt(long, conflicts_with_all = &["who", "file"])]
+		erratic: bool,
+	},
+	/// Dump the staking ledger of a stash: active and total bond, unlocking chunks and claimed
+	/// rewards.
+	Ledger {
+		/// The stash's address. Both hex and ss58 encoding are acceptable.
+		#[structopt(long)]
+		who: AccountId,
+	},
+	/// Scan all staking ledgers for inconsistencies.
+	///
+	/// Reports ledgers whose total is not the sum of active and unlocking, bonds exceeding the
+	/// free balance or the staking lock, duplicate unlocking chunks and orphaned controllers.
+	LedgerAudit {},
+	/// Report on the stash to controller mapping.
+	///
+	/// Highlights stashes with a distinct controller, controllers controlling nothing and the
+	/// accounts affected by a controller deprecation migration.
+	Controllers {},
+	/// Report the distribution of reward destinations (`Payee`) across all stakers.
+	Payees {},
+	/// Report the commission of validators over the eras kept in storage.
+	///
+	/// Flags the validators who raised their commission for a single era, only to lower it back
+	/// afterwards.
+	CommissionHistory {
+		/// The validator's address. Both hex and ss58 encoding are acceptable. If not provided, all
+		/// validators elected in the past eras are reported.
+		#[structopt(long)]
+		who: Option<AccountId>,
+	},
+	/// Report how much stake becomes withdrawable in each era of the next bonding duration.
+	Unbonding {
+		/// Only consider the validator and the nominators backing it. Both hex and ss58 encoding
+		/// are acceptable.
+		#[structopt(long)]
+		validator: Option<AccountId>,
+	},
+	/// Display the staking rate, the inflation and the era reward, per the reward curve of the
+	/// runtime.
+	Inflation {},
+	/// Estimate the reward pot of the current era, and compare the estimate with the actual
+	/// reward of the previous era.
+	RewardPot {},
+	/// List the eras within the history depth in which the payout of a validator is not claimed,
+	/// with the estimated amounts.
+	PayoutCheck {
+		/// The validator's address. Both hex and ss58 encoding are acceptable.
+		#[structopt(long)]
+		who: AccountId,
+
+		/// Also print the hex-encoded `utility.batch(staking.payout_stakers(..))` calls that claim
+		/// all the unclaimed eras.
+		///
+		/// Each call can be signed and submitted with the script of `staking-miner/js` and
+		/// `--submit`, including on a hardware wallet or parity signer with `--external-signer
+		/// <address>`.
+		#[structopt(long, parse(from_flag))]
+		call: bool,
+
+		/// The maximum number of eras claimed in each batch call. Batches are also split so that
+		/// none exceeds the weight limit of an extrinsic.
+		#[structopt(long, default_value = "8")]
+		chunk_size: usize,
+	},
+	/// Replay the past eras and report the returns that a nomination strategy would have
+	/// produced, compared to the network average.
+	///
+	/// The bond is assumed to be evenly distributed among the elected targets of each era.
+	///
+	/// With `--nominator`, the realized returns of an existing nominator are reconstructed instead.
+	Backtest {
+		/// A file of the targets to nominate, one per line.
+		#[structopt(long, parse(from_os_str), required_unless = "nominator")]
+		targets: Option<PathBuf>,
+
+		/// The bonded amount, in the smallest unit of the currency.
+		#[structopt(long, required_unless = "nominator")]
+		bond: Option<Balance>,
+
+		/// Instead of a strategy, backtest the actual rewards of this nominator, and compare them
+		/// with nominating the whole active set.
+		#[structopt(long, conflicts_with_all = &["targets", "bond"])]
+		nominator: Option<AccountId>,
+
+		/// The number of past eras to replay. Capped at the history depth.
+		#[structopt(long, default_value = "28")]
+		eras: u32,
+	},
+	/// Suggest nomination targets, subject to the constraints of a json file.
+	///
+	/// Targets predicted to be elected with little backing and a low commission are preferred.
+	Suggest {
+		/// The number of targets to suggest.
+		#[structopt(long, default_value = "16")]
+		count: usize,
+
+		/// A json file of constraints. See the docs of `subcommands::suggest` for the format.
+		#[structopt(long, parse(from_os_str))]
+		constraints: Option<PathBuf>,
+	},
+	/// Export the rewards of an account over a range of eras as csv, with the columns `era`,
+	/// `date`, `validator`, `amount` and `token`.
+	///
+	/// The rewards are computed from on-chain data, both as a nominator and as a validator. The
+	/// dates are estimates.
+	ExportRewards {
+		/// The account of which the rewards are exported.
+		#[structopt(long)]
+		who: AccountId,
+
+		/// The first era to export. Defaults to the oldest era kept in storage.
+		#[structopt(long)]
+		from_era: Option<u32>,
+
+		/// The last era to export. Defaults to the last finished era.
+		#[structopt(long)]
+		to_era: Option<u32>,
+
+		/// Write the csv to this file rather than stdout.
+		#[structopt(long, parse(from_os_str))]
+		output: Option<PathBuf>,
+	},
+	/// Report the churn of the nominators between two eras: who joined, left, changed targets or
+	/// changed bond, and the resulting stake flows.
+	///
+	/// The nominators are read at the first block of each era, which requires an archive node.
+	Churn {
+		/// The first era to compare.
+		#[structopt(long)]
+		from_era: u32,
+
+		/// The second era to compare. Defaults to the state at `--at`.
+		#[structopt(long)]
+		to_era: Option<u32>,
+	},
+	/// Export how the backing moved between the validators of two consecutive elections: stake
+	/// that stayed, moved from one validator to another, entered or exited.
+	///
+	/// The output is a Sankey-ready node-link json, or csv links.
+	StakeFlow {
+		/// The era of the second election, compared with the one before it. Defaults to the
+		/// active era.
+		#[structopt(long)]
+		era: Option<u32>,
+
+		/// The output file.
+		#[structopt(long, parse(from_os_str))]
+		output: PathBuf,
+
+		/// Write `source,target,value` csv links, rather than json.
+		#[structopt(long, parse(from_flag))]
+		csv: bool,
+	},
+	/// Simulate slashing a fraction of the exposure of a validator of the active era: who is
+	/// slashed by how much, and how the next election changes once the validator is chilled.
+	SimulateSlash {
+		/// The slashed validator.
 		#[structopt(long)]
 		who: AccountId,
+
+		/// The slashed fraction, in parts per billion, e.g. `100000000` for 10%.
+		#[structopt(long)]
+		fraction: u32,
+
+		/// Number of balancing rounds of the next election.
+		#[structopt(short, long, default_value = "0")]
+		iterations: usize,
+	},
+	/// Compare the size of the election snapshot (voters, targets and encoded bytes) with the
+	/// bounds of the chain, warning when the network approaches a bound that truncates voters.
+	Bounds {
+		/// Warn once a count reaches this percentage of its bound.
+		#[structopt(long, default_value = "90")]
+		warn_at: u8,
+	},
+	/// Scan the election snapshot for oddities: validators voting for themselves and others,
+	/// duplicate targets within one nomination, zero-weight voters, targets not in `Validators`
+	/// and nominations exceeding 16 targets.
+	AnomalyScan {
+		/// Scan a snapshot file rather than scraping the chain. No connection is made then.
+		#[structopt(long, parse(from_os_str))]
+		input: Option<PathBuf>,
+
+		/// The number of examples to print for each class of anomalies.
+		#[structopt(long, default_value = "5")]
+		examples: usize,
+	},
+	/// Store the prediction of the next staking election, and compare the stored predictions of
+	/// the eras that became active since with the actual validator set. Run it periodically to
+	/// track how much the predictions can be trusted.
+	Accuracy {
+		/// The json file in which the predictions and their outcomes are kept.
+		#[structopt(long, parse(from_os_str), default_value = "predictions.json")]
+		store: PathBuf,
+
+		/// The number of most recent resolved predictions that the statistics cover.
+		#[structopt(long, default_value = "10")]
+		window: usize,
+	},
+	/// Open an interactive session: connect and scrape the snapshot once, then run sub-commands
+	/// one after the other on the same connection and block, without scraping again.
+	Repl {},
+	/// Report the pairs of validators of the active set that share many of their nominators, and
+	/// whose backing is thus correlated for slashing.
+	Overlap {
+		/// Use the predicted set, rather than the active one.
+		#[structopt(long, parse(from_flag))]
+		predicted: bool,
+
+		/// Flag the pairs that share at least this fraction of the backing of the less backed
+		/// validator of the two.
+		#[structopt(long, default_value = "0.5")]
+		threshold: f64,
+
+		/// The number of pairs to list, from the most overlapping.
+		#[structopt(long, default_value = "20")]
+		top: usize,
+	},
+	/// Group the validators of the previous, active and predicted sets by their operator
+	/// (identity parent), and report the number of nodes and stake share of each operator.
+	Clusters {},
+	/// List all the validator candidates, active and waiting, with their commission, self-stake,
+	/// total potential backing and whether they are predicted to be elected.
+	Targets {
+		/// Only list the candidates that are not predicted to be elected, sorted by the additional
+		/// backing that they need to enter the predicted set.
+		#[structopt(long)]
+		waiting: bool,
+	},
+	/// Mine a solution for the unsigned phase of the staking election, as the offchain worker
+	/// of a validator would, and print its weight, priority and call data.
+	Mine(MineConfig),
+	/// Scrape the paged snapshot of the multi-block election and mine a paged solution for it, for
+	/// analysis only.
+	MultiBlock(MultiBlockConfig),
+	/// Detect the emergency phase of the multi-phase election and compute the emergency solution,
+	/// along with the call to be dispatched by governance.
+	Emergency {
+		/// Number of balancing rounds.
+		#[structopt(short, long, default_value = "10")]
+		iterations: usize,
+
+		/// Compute the solution even if the election is not in the emergency phase.
+		#[structopt(long, parse(from_flag))]
+		force: bool,
+	},
+}
+
+/// The sub-commands of the snapshot sub-command.
+#[derive(Debug, StructOpt, Clone)]
+pub enum SnapshotCommand {
+	/// Replace all account ids with deterministic pseudonyms, preserving the nomination graph and
+	/// stakes, so that the snapshot can be shared publicly.
+	Anonymize {
+		/// The snapshot file to anonymize.
+		#[structopt(long, parse(from_os_str))]
+		input: PathBuf,
+
+		/// The file to write the anonymized snapshot into.
+		#[structopt(long, parse(from_os_str))]
+		output: PathBuf,
+
+		/// A secret salt used to derive pseudonyms. Without it, the pseudonyms can be reversed.
+		#[structopt(long, default_value = "")]
+		salt: String,
+	},
+	/// Greedily remove voters and targets from a snapshot while a predicate still holds, producing
+	/// a small test case to reproduce a bug.
+	Shrink {
+		/// The snapshot file to shrink.
+		#[structopt(long, parse(from_os_str))]
+		input: PathBuf,
+
+		/// The file to write the shrunk snapshot into.
+		#[structopt(long, parse(from_os_str))]
+		output: PathBuf,
+
+		/// The predicate that must hold. Can be
+		/// algorithms-disagree|fails|score-mismatch:<min>,<sum>,<sum_sq>.
+		#[structopt(long)]
+		predicate: subcommands::snapshot::Predicate,
+
+		/// Number of balancing rounds used in the elections.
+		#[structopt(short, long, default_value = "0")]
+		iterations: usize,
+	},
+	/// Print a histogram of the stake of the nominators of a snapshot, in log-scaled buckets,
+	/// along with some percentiles.
+	Histogram {
+		/// The snapshot file to analyze.
+		#[structopt(long, parse(from_os_str))]
+		input: PathBuf,
+
+		/// A json file to export the histogram into.
+		#[structopt(long, parse(from_os_str))]
+		output: Option<PathBuf>,
+	},
+	/// Export the nomination graph of a snapshot as a sparse voter by target matrix, for graph and
+	/// machine learning tools.
+	///
+	/// Writes `graph.mtx` (matrix market, one-based), `graph.csv` (zero-based triples of voter,
+	/// target and weight), and the `voters.csv` and `targets.csv` mappings of indices to accounts.
+	Matrix {
+		/// The snapshot file to export.
+		#[structopt(long, parse(from_os_str))]
+		input: PathBuf,
+
+		/// The directory to write the files into.
+		#[structopt(long, parse(from_os_str))]
+		output: PathBuf,
+	},
+	/// Turn a snapshot, typically shrunk and anonymized, into the source of a regression test of
+	/// `sp-npos-elections`, using the builders of its test `mock` module.
+	Fixture {
+		/// The snapshot file to turn into a test.
+		#[structopt(long, parse(from_os_str))]
+		input: PathBuf,
+
+		/// The file to write the rust source into.
+		#[structopt(long, parse(from_os_str))]
+		output: PathBuf,
+
+		/// The name of the test function.
+		#[structopt(long, default_value = "regression_from_snapshot")]
+		name: String,
 	},
 }
 
@@ -278,6 +784,36 @@ pub struct StakingConfig {
 	#[structopt(parse(from_os_str))]
 	output: Option<PathBuf>,
 
+	/// Also write the supports alone into this json file, keyed by winner, with their total
+	/// backing and the `(voter, stake)` contributions.
+	#[structopt(long, parse(from_os_str))]
+	supports_output: Option<PathBuf>,
+
+	/// The format of the json output. Can be default|d3.
+	///
+	/// `d3` is a node-link graph of `{ id, group, value }` nodes and `{ source, target, value }`
+	/// links, as consumed by the community frontends.
+	#[structopt(long, default_value = "default")]
+	format: subcommands::staking::OutputFormat,
+
+	/// The top-level sections of the default json output, comma separated. Can be any of
+	/// winners|assignments|supports|snapshot|metrics|timing. Defaults to winners,supports.
+	#[structopt(long, use_delimiter = true)]
+	fields: Vec<subcommands::staking::OutputField>,
+
+	/// The version of the default json output to emit. Older versions are kept for existing
+	/// consumers. Defaults to the latest.
+	#[structopt(
+		long,
+		default_value = "1",
+		parse(try_from_str = subcommands::staking::parse_schema_version)
+	)]
+	schema_version: u32,
+
+	/// Print the json schema of the latest default json output, and exit.
+	#[structopt(long, parse(from_flag))]
+	schema: bool,
+
 	/// Number of balancing rounds.
 	#[structopt(short, long, default_value = "0")]
 	iterations: usize,
@@ -289,9 +825,265 @@ pub struct StakingConfig {
 	#[structopt(short, long, parse(from_flag))]
 	reduce: bool,
 
+	/// Write the assignments before and after reduce, and the edges of each voter that reduce
+	/// rerouted, into this json file.
+	#[structopt(long, parse(from_os_str), requires = "reduce")]
+	reduce_diff: Option<PathBuf>,
+
 	/// The override file to interpret
 	#[structopt(short, long, parse(from_os_str))]
 	manual_override: Option<PathBuf>,
+
+	/// Run the election on a snapshot file, rather than scraping the chain.
+	///
+	/// Either a json snapshot, as saved by `--save-snapshot`, or a state snapshot of
+	/// `remote-externalities`, as created by `try-runtime create-snapshot`.
+	#[structopt(long, parse(from_os_str))]
+	input: Option<PathBuf>,
+
+	/// Replace the voters of the snapshot with the ones of this json file, as a list of
+	/// `[voter, weight, [target, ..]]`, keeping everything else from the chain or `--input`. The
+	/// self-votes of the targets are voters too.
+	#[structopt(long, parse(from_os_str))]
+	voters_file: Option<PathBuf>,
+
+	/// Replace the targets of the snapshot with the ones of this file, one account per line,
+	/// keeping everything else from the chain or `--input`. The votes for the other accounts are
+	/// ignored by the election.
+	#[structopt(long, parse(from_os_str))]
+	targets_file: Option<PathBuf>,
+
+	/// Guarantee that no connection to any node is made: the election runs on `--input` alone,
+	/// and any option that needs the chain is an error.
+	#[structopt(long, parse(from_flag), requires = "input")]
+	offline: bool,
+
+	/// Save the snapshot of the election inputs into this file, to be used later with `--input`.
+	#[structopt(long, parse(from_os_str))]
+	save_snapshot: Option<PathBuf>,
+
+	/// Run the whole election pipeline twice on the same snapshot before anything else, and fail
+	/// if the outcomes differ.
+	#[structopt(long, parse(from_flag))]
+	verify_determinism: bool,
+
+	/// Simulate unbonding some stake of an account, as `<account>:<amount>`, and show the
+	/// impact on the elected set and on the rewards of the account. Can be given multiple times.
+	#[structopt(long)]
+	simulate_unbond: Vec<subcommands::staking::StakeChange>,
+
+	/// Simulate bonding extra stake to an account, as `<account>:<amount>`, and show the impact
+	/// on the elected set and on the rewards of the account. Can be given multiple times.
+	#[structopt(long)]
+	simulate_rebond: Vec<subcommands::staking::StakeChange>,
+
+	/// Truncate the voters, self-votes included, to this number before running the election.
+	#[structopt(long)]
+	max: Option<usize>,
+
+	/// How to pick the voters kept by `--max`. Can be top-stake|random:<seed>|bags-order.
+	///
+	/// `bags-order` follows the bags-list of the chain, like the chain's own truncation does.
+	#[structopt(long, default_value = "top-stake")]
+	max_strategy: subcommands::staking::MaxStrategy,
+
+	/// Only report on this account: whether it, or its targets, are elected, its assignments, its
+	/// distance from the cutoff and its projected reward.
+	#[structopt(long)]
+	who: Option<AccountId>,
+
+	/// Also emulate the on-chain fallback election (no balancing, no reduce, truncated as the
+	/// chain does), and compare it with the mined solution.
+	#[structopt(long, parse(from_flag))]
+	emulate_fallback: bool,
+
+	/// Instead of a single election, run all the combinations of the given configurations on the
+	/// same snapshot and print a table comparing them, e.g.
+	/// `algorithm=seq-phragmen,phragmms;iterations=0,10;reduce=off,on`.
+	///
+	/// The snapshot is scraped once, which dominates the runtime of comparing configurations.
+	#[structopt(long)]
+	matrix: Option<subcommands::staking::ConfigMatrix>,
+
+	/// Read the validator count that an ongoing referendum would set, and report the validators
+	/// that would enter (or leave) the predicted set with it.
+	#[structopt(long, parse(from_flag))]
+	pending_count: bool,
+
+	/// As `--pending-count`, but with the given validator count.
+	#[structopt(long, conflicts_with = "pending-count")]
+	assume_count: Option<usize>,
+}
+
+/// Arguments that can be passed to the mine sub-command.
+#[derive(Debug, StructOpt, Clone)]
+pub struct MineConfig {
+	/// Number of balancing rounds. Default is `Staking.MaxIterations`.
+	#[structopt(short, long)]
+	iterations: Option<usize>,
+
+	/// The base priority of unsigned solutions in the runtime. The default is the one of polkadot
+	/// and kusama.
+	#[structopt(long, default_value = "9223372036854775807")]
+	unsigned_priority: u64,
+
+	/// Produce the solution even if it exceeds the weight limit of unsigned solutions.
+	#[structopt(long, parse(from_flag))]
+	force_weight: bool,
+}
+
+/// Arguments that can be passed to the multi-block sub-command.
+#[derive(Debug, StructOpt, Clone)]
+pub struct MultiBlockConfig {
+	/// The name of the multi-block election pallet in the runtime.
+	#[structopt(long, default_value = "MultiBlockElection")]
+	pallet: String,
+
+	/// Number of balancing rounds.
+	#[structopt(short, long, default_value = "0")]
+	iterations: usize,
+
+	/// Json output file name of the paged solution.
+	///
+	/// The assignments of each page are listed by account, for analysis. This is not the compact
+	/// solution of the pallet, and cannot be submitted.
+	#[structopt(long, parse(from_os_str))]
+	output: Option<PathBuf>,
+
+	/// Save all the pages of the snapshot as a single snapshot file, to be used with `--input` of
+	/// the other sub-commands.
+	#[structopt(long, parse(from_os_str))]
+	save_snapshot: Option<PathBuf>,
+}
+
+/// Arguments that can be passed to the trace sub-command.
+#[derive(Debug, StructOpt, Clone)]
+pub struct TraceConfig {
+	/// The snapshot file to use, as created by `staking --save-snapshot`.
+	#[structopt(long, parse(from_os_str))]
+	input: PathBuf,
+
+	/// The json file to write the trace into.
+	#[structopt(long, parse(from_os_str))]
+	output: PathBuf,
+
+	/// Count of validators to elect. Default is the desired targets of the snapshot.
+	#[structopt(short, long)]
+	count: Option<usize>,
+
+	/// Number of balancing rounds.
+	#[structopt(short, long, default_value = "0")]
+	iterations: usize,
+}
+
+/// Arguments that can be passed to the bench sub-command.
+#[derive(Debug, StructOpt, Clone)]
+pub struct BenchConfig {
+	/// The snapshot file to use, as created by `staking --save-snapshot`.
+	#[structopt(long, parse(from_os_str))]
+	input: PathBuf,
+
+	/// Count of validators to elect. Default is the desired targets of the snapshot.
+	#[structopt(short, long)]
+	count: Option<usize>,
+
+	/// Number of balancing rounds.
+	#[structopt(short, long, default_value = "10")]
+	iterations: usize,
+
+	/// Number of times to run the whole pipeline.
+	#[structopt(long, default_value = "10")]
+	runs: usize,
+}
+
+/// Arguments that can be passed to the generate sub-command.
+#[derive(Debug, StructOpt, Clone)]
+pub struct GenerateConfig {
+	/// The file to write the snapshot into.
+	#[structopt(long, parse(from_os_str))]
+	output: PathBuf,
+
+	/// Number of voters (nominators) to generate.
+	#[structopt(long, default_value = "1000")]
+	voters: usize,
+
+	/// Number of targets (validator candidates) to generate. Each will also have a self-vote.
+	#[structopt(long, default_value = "100")]
+	targets: usize,
+
+	/// Number of targets desired to be elected.
+	#[structopt(long, default_value = "50")]
+	desired_targets: u32,
+
+	/// Maximum number of votes of each voter.
+	#[structopt(long, default_value = "16")]
+	edges: usize,
+
+	/// The distribution of stakes. Can be uniform|zipf|shape.
+	///
+	/// `shape` copies the stake and vote count distribution of the snapshot given by `--shape-of`.
+	#[structopt(long, default_value = "uniform")]
+	distribution: subcommands::generate::StakeDistribution,
+
+	/// A snapshot, e.g. one scraped from Kusama, to copy the shape of.
+	#[structopt(long, parse(from_os_str))]
+	shape_of: Option<PathBuf>,
+
+	/// Minimum stake of each voter.
+	#[structopt(long, default_value = "1000")]
+	min_stake: u64,
+
+	/// Maximum stake of each voter.
+	#[structopt(long, default_value = "1000000")]
+	max_stake: u64,
+
+	/// The exponent of the zipf distribution.
+	#[structopt(long, default_value = "1.0")]
+	zipf_exponent: f64,
+
+	/// The seed of the random generator. The same seed always generates the same snapshot.
+	#[structopt(long, default_value = "0")]
+	seed: u64,
+}
+
+/// Arguments that can be passed to the stress sub-command.
+#[derive(Debug, StructOpt, Clone)]
+pub struct StressConfig {
+	/// Number of voters of the smallest snapshot.
+	#[structopt(long, default_value = "10000")]
+	from: usize,
+
+	/// Maximum number of voters.
+	#[structopt(long, default_value = "1000000")]
+	to: usize,
+
+	/// The factor by which the number of voters grows in each step.
+	#[structopt(long, default_value = "10")]
+	factor: usize,
+
+	/// Number of targets in all snapshots.
+	#[structopt(long, default_value = "1000")]
+	targets: usize,
+
+	/// Number of targets to elect.
+	#[structopt(long, default_value = "300")]
+	desired_targets: u32,
+
+	/// Number of balancing rounds.
+	#[structopt(short, long, default_value = "10")]
+	iterations: usize,
+
+	/// The distribution of stakes. Can be uniform|zipf.
+	#[structopt(long, default_value = "uniform")]
+	distribution: subcommands::generate::StakeDistribution,
+
+	/// The seed of the random generator.
+	#[structopt(long, default_value = "0")]
+	seed: u64,
+
+	/// Also write the results into this csv file.
+	#[structopt(long, parse(from_os_str))]
+	csv: Option<PathBuf>,
 }
 
 /// Arguments that can be passed to the council sub-command.
@@ -309,40 +1101,58 @@ pub struct CouncilConfig {
 	/// The override file to interpret
 	#[structopt(short, long, parse(from_os_str))]
 	manual_override: Option<PathBuf>,
-}
 
-#[async_std::main]
-async fn main() -> () {
-	env_logger::Builder::from_default_env().format_module_path(false).format_level(true).init();
+	/// Instead of running the election, simulate the removal of these members mid-term (e.g.
+	/// because they lose their bond), and show the runner-up promotions and the prime that
+	/// follow. Can be given multiple times; removals are applied in order.
+	#[structopt(long)]
+	remove: Vec<AccountId>,
 
-	let mut opt = Opt::from_args();
+	/// Add a prospective vote, as `<account>:<stake>:<candidate>,<candidate>,..`, and report the
+	/// seats that it flips before running the election with it. The stake is in the smallest unit
+	/// of the currency. Can be given multiple times.
+	#[structopt(long)]
+	add_vote: Vec<subcommands::elections_phragmen::Vote>,
 
-	// connect to a node.
-	let client = jsonrpsee_ws_client::WsClient::new(
-		&opt.uri,
+	/// Instead of running the election, verify the last term change at or before `--at`: run the
+	/// election on the state just before it, and compare the members, runners-up and their
+	/// backing with what the chain elected.
+	#[structopt(long, parse(from_flag))]
+	verify: bool,
+
+	/// Instead of a single election, run one for each number of seats in the given range (e.g.
+	/// `13..19`), and report how the membership and the stake backing members change.
+	#[structopt(long)]
+	sweep: Option<subcommands::elections_phragmen::SeatRange>,
+
+	/// After the election, report the additional backing that each candidate that is not a
+	/// member needs to take the last seat, and the backing that the first runner-up needs to take
+	/// the seat of each member. This re-runs the election many times.
+	#[structopt(long, parse(from_flag))]
+	margin: bool,
+}
+
+/// Connect to the node at the given uri.
+pub(crate) async fn connect(uri: &str, rpc_timeout: u64) -> error::Result<Client> {
+	jsonrpsee_ws_client::WsClient::new(
+		uri,
 		jsonrpsee_ws_client::WsConfig {
 			max_request_body_size: 1024 * 1024 * 1024, // 1GB..
+			request_timeout: Some(std::time::Duration::from_secs(rpc_timeout)),
 			..Default::default()
 		},
 	)
 	.await
-	.unwrap();
-
-	// get the latest block hash
-	let head = storage::get_head(&client).await;
-
-	// potentially replace head with the given hash
-	let at = opt.at.unwrap_or(head);
-	opt.at = Some(at);
+	.map_err(|e| error::Error::Connection { uri: uri.into(), reason: e.to_string() })
+}
 
-	let runtime_version = sub_storage::get_runtime_version(&client, at).await;
-	let spec_name = runtime_version.spec_name;
-	let network_address = opt.clone().network.unwrap_or_else(|| spec_name.into());
+/// Set the address format and the currency of `network`.
+fn set_network(network_address: String) -> error::Result<()> {
 	let address_format = match &network_address[..] {
 		"polkadot" => Ss58AddressFormat::PolkadotAccount,
 		"kusama" => Ss58AddressFormat::KusamaAccount,
 		"substrate" => Ss58AddressFormat::SubstrateAccount,
-		_ => panic!("Invalid network/address format."),
+		_ => return Err(error::Error::UnknownNetwork(network_address)),
 	};
 
 	// setup address format and currency based on address format.
@@ -353,34 +1163,327 @@ async fn main() -> () {
 	} else if address_format.eq(&Ss58AddressFormat::KusamaAccount) {
 		sub_tokens::dynamic::set_name(&"KSM");
 		sub_tokens::dynamic::set_decimal_points(1000_000_000_000);
+	} else {
+		// back to the defaults, in case another network was set up before, see `--also`.
+		sub_tokens::dynamic::set_name(&"GTK");
+		sub_tokens::dynamic::set_decimal_points(1_000_000_000_000);
+	}
+	Ok(())
+}
+
+/// Connect to the node, and set up the block, the network and the total issuance.
+async fn setup(opt: &mut Opt) -> error::Result<Client> {
+	// connect to a node.
+	sub_storage::throttle::set_max_rps(opt.max_rps);
+	let client = connect(&opt.uri, opt.rpc_timeout).await?;
+
+	// potentially replace head with the given hash
+	let at = match opt.at {
+		Some(at) => {
+			network::check_finality(&client, at).await?;
+			at
+		}
+		None if opt.best => network::get_best_head(&client).await?,
+		None => network::get_finalized_head(&client).await?,
+	};
+	opt.at = Some(at);
+
+	let (spec_name, spec_version) = network::get_spec(&client, at).await?;
+	compat::check(&spec_name, spec_version, &client, at, opt.strict).await?;
+	network::detect_account_len(&client, at).await?;
+	match opt.network.clone() {
+		Some(network_address) => set_network(network_address)?,
+		None => {
+			// any other chain is still usable, only with the generic address format and token.
+			if let Err(e) = set_network(spec_name) {
+				log::warn!(target: LOG_TARGET, "{} Using the generic substrate format.", e);
+				set_network("substrate".to_string())?;
+			}
+		}
 	}
 
 	// set total issuance
-	network::issuance::set(&client, at).await;
+	network::issuance::set(&client, at).await?;
+	Ok(client)
+}
 
-	log::info!(target: LOG_TARGET, "program args: {:?}", opt);
-	log::info!(
-		target: LOG_TARGET,
-		"total_issuance = {:?}",
-		Currency::from(network::issuance::get())
-	);
+#[async_std::main]
+async fn main() -> () {
+	env_logger::Builder::from_default_env().format_module_path(false).format_level(true).init();
+
+	let mut opt = Opt::from_args();
+	if opt.plan && plan::reads_of(&opt.cmd).is_none() {
+		structopt::clap::Error::with_description(
+			"no plan is known for this sub-command, `--plan` cannot be used with it.",
+			structopt::clap::ErrorKind::ArgumentConflict,
+		)
+		.exit();
+	}
+	interrupt::install();
+
+	if let Some(deadline) = opt.deadline {
+		std::thread::spawn(move || {
+			std::thread::sleep(std::time::Duration::from_secs(deadline));
+			log::error!(target: LOG_TARGET, "❌ deadline of {}s reached, exiting.", deadline);
+			std::process::exit(2);
+		});
+	}
+
+	// sub-commands that work purely offline.
+	let offline = match opt.cmd {
+		SubCommands::Bench(ref conf) => Some(subcommands::bench::run(conf.clone())),
+		SubCommands::Generate(ref conf) => Some(subcommands::generate::run(conf.clone())),
+		SubCommands::Selftest {} => {
+			subcommands::selftest::run();
+			Some(Ok(()))
+		}
+		SubCommands::Snapshot(ref command) => Some(subcommands::snapshot::run(command.clone())),
+		SubCommands::Stress(ref conf) => Some(subcommands::stress::run(conf.clone())),
+		SubCommands::Trace(ref conf) => Some(subcommands::trace::run(conf.clone())),
+		SubCommands::Convert { ref input, ref output, desired_targets } => {
+			Some(subcommands::convert::run(input.clone(), output.clone(), desired_targets))
+		}
+		SubCommands::AnomalyScan { input: Some(ref input), examples } => Some(
+			snapshot::Snapshot::load(input)
+				.and_then(|snapshot| subcommands::anomaly_scan::run(snapshot, examples)),
+		),
+		SubCommands::Staking(ref conf) if conf.offline => {
+			let network = opt.network.clone().unwrap_or_else(|| "substrate".into());
+			match set_network(network) {
+				Ok(()) => {
+					Some(subcommands::staking::run_offline(conf.clone(), opt.verbosity).await)
+				}
+				Err(e) => Some(Err(e)),
+			}
+		}
+		SubCommands::Staking(ref conf) if conf.schema => {
+			let schema = subcommands::staking::output_schema();
+			println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+			Some(Ok(()))
+		}
+		_ => None,
+	};
+	if let Some(outcome) = offline {
+		if let Err(e) = outcome {
+			log::error!(target: LOG_TARGET, "❌ {}", e);
+			std::process::exit(1);
+		}
+		return;
+	}
+
+	// each node is reported one after the other, with the same sub-command.
+	let uris = std::iter::once(opt.uri.clone()).chain(opt.also.clone()).collect::<Vec<_>>();
+	if uris.len() > 1 && opt.network.is_some() {
+		log::warn!(
+			target: LOG_TARGET,
+			"--network is ignored with --also, the network of each node is detected."
+		);
+		opt.network = None;
+	}
+	let mut failed = false;
+	// the network and the number of failed runs of each node, `None` if it could not be set up.
+	let mut summary: Vec<(&String, Option<(&'static str, usize)>)> = vec![];
+	for uri in uris.iter() {
+		opt.uri = uri.clone();
+		if uris.len() > 1 {
+			// the head of each node is used, see `conflicts_with` of `--also`.
+			opt.at = None;
+		}
+		let client = match setup(&mut opt).await {
+			Ok(client) => client,
+			Err(e) => {
+				log::error!(target: LOG_TARGET, "❌ {}", e);
+				failed = true;
+				summary.push((uri, None));
+				continue;
+			}
+		};
+		let network_name = sub_tokens::dynamic::name();
+		if uris.len() > 1 {
+			println!("🌐 {} @ {}", network_name, uri);
+		}
+		let mut errors = 0;
+
+		log::info!(target: LOG_TARGET, "program args: {:?}", opt);
+		log::info!(
+			target: LOG_TARGET,
+			"total_issuance = {:?}",
+			Currency::from(network::issuance::get())
+		);
+		match opt.at_range.clone() {
+			Some(range) => {
+				// the same connection is used for all the blocks of the range.
+				for number in range.blocks() {
+					let at = match storage::get_block_hash(&client, number).await {
+						Some(at) => at,
+						None => {
+							log::warn!(target: LOG_TARGET, "block #{} does not exist yet.", number);
+							break;
+						}
+					};
+					opt.at = Some(at);
+					if let Err(e) = network::issuance::set(&client, at).await {
+						log::error!(target: LOG_TARGET, "❌ {}", e);
+						errors += 1;
+						continue;
+					}
+					println!("📍 block #{} ({:?})", number, at);
+					if let Err(e) = run(&client, opt.clone()).await {
+						log::error!(target: LOG_TARGET, "❌ {}", e);
+						errors += 1;
+					}
+				}
+			}
+			None => {
+				if let Err(e) = run(&client, opt.clone()).await {
+					log::error!(target: LOG_TARGET, "❌ {}", e);
+					errors += 1;
+				}
+			}
+		}
+		failed |= errors > 0;
+		summary.push((uri, Some((network_name, errors))));
+	}
+
+	if uris.len() > 1 {
+		println!("🌐 summary of {} nodes:", uris.len());
+		for (uri, outcome) in summary.iter() {
+			match outcome {
+				Some((network, 0)) => println!("\t✅ {} @ {}", network, uri),
+				Some((network, errors)) => {
+					println!("\t❌ {} @ {}: {} failed runs", network, uri, errors)
+				}
+				None => println!("\t❌ {}: setup failed", uri),
+			}
+		}
+	}
+
+	if failed {
+		std::process::exit(1);
+	}
+}
+
+/// Run the sub-command of `opt` against `client`.
+async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	if opt.plan {
+		return plan::run(client, &opt).await;
+	}
 
 	match opt.clone().cmd {
 		SubCommands::Current { .. } => subcommands::current::run(&client, opt.clone()).await,
+		SubCommands::SessionInfo { .. } => {
+			subcommands::session_info::run(&client, opt.clone()).await
+		}
 		SubCommands::Next { .. } => unimplemented!(),
 		SubCommands::Staking(conf) => subcommands::staking::run(&client, opt.clone(), conf).await,
 		SubCommands::Council(conf) => {
 			subcommands::elections_phragmen::run(&client, opt.clone(), conf).await
 		}
+		SubCommands::Bench(_)
+		| SubCommands::Generate(_)
+		| SubCommands::Selftest {}
+		| SubCommands::Snapshot(_)
+		| SubCommands::Stress(_)
+		| SubCommands::Trace(_)
+		| SubCommands::Convert { .. } => {
+			unreachable!("handled before connecting to a node.")
+		}
 		SubCommands::DanglingNominators { .. } => {
 			subcommands::dangling_nominators::run(&client, opt.clone()).await
 		}
+		SubCommands::CrossCheck { others, deep } => {
+			subcommands::cross_check::run(&client, opt.clone(), others, deep).await
+		}
 		SubCommands::CommandCenter { .. } => unimplemented!(),
-		SubCommands::NominatorCheck { who } => {
-			subcommands::nominator_check::run(&client, opt.clone(), who).await
+		SubCommands::NominatorCheck { who, eras } => {
+			subcommands::nominator_check::run(&client, opt.clone(), who, eras).await
 		}
-		SubCommands::ValidatorCheck { who } => {
-			subcommands::validator_check::run(&client, opt.clone(), who).await
+		SubCommands::ValidatorCheck { erratic: true, .. } => {
+			subcommands::validator_check::run_erratic(&client, opt.clone()).await
 		}
-	};
+		SubCommands::ValidatorCheck { who: Some(who), backing_needed, list_nominators, .. } => {
+			subcommands::validator_check::run(
+				&client,
+				opt.clone(),
+				who,
+				backing_needed,
+				list_nominators,
+			)
+			.await
+		}
+		SubCommands::ValidatorCheck { file: Some(file), .. } => {
+			subcommands::validator_check::run_batch(&client, opt.clone(), file).await
+		}
+		SubCommands::ValidatorCheck { .. } => {
+			unreachable!("structopt enforces one of the three.")
+		}
+		SubCommands::Ledger { who } => subcommands::ledger::run(&client, opt.clone(), who).await,
+		SubCommands::LedgerAudit { .. } => {
+			subcommands::ledger_audit::run(&client, opt.clone()).await
+		}
+		SubCommands::Controllers { .. } => {
+			subcommands::controllers::run(&client, opt.clone()).await
+		}
+		SubCommands::Payees { .. } => subcommands::payees::run(&client, opt.clone()).await,
+		SubCommands::CommissionHistory { who } => {
+			subcommands::commission_history::run(&client, opt.clone(), who).await
+		}
+		SubCommands::Unbonding { validator } => {
+			subcommands::unbonding::run(&client, opt.clone(), validator).await
+		}
+		SubCommands::Inflation { .. } => subcommands::inflation::run(&client, opt.clone()).await,
+		SubCommands::RewardPot { .. } => subcommands::reward_pot::run(&client, opt.clone()).await,
+		SubCommands::PayoutCheck { who, call, chunk_size } => {
+			subcommands::payout_check::run(&client, opt.clone(), who, call, chunk_size).await
+		}
+		SubCommands::Backtest { nominator: Some(who), eras, .. } => {
+			subcommands::backtest::run_nominator(&client, opt.clone(), who, eras).await
+		}
+		SubCommands::Backtest { targets: Some(targets), bond: Some(bond), eras, .. } => {
+			subcommands::backtest::run(&client, opt.clone(), targets, bond, eras).await
+		}
+		SubCommands::Backtest { .. } => unreachable!("structopt enforces one of the two."),
+		SubCommands::Churn { from_era, to_era } => {
+			subcommands::churn::run(&client, opt.clone(), from_era, to_era).await
+		}
+		SubCommands::StakeFlow { era, output, csv } => {
+			subcommands::stake_flow::run(&client, opt.clone(), era, output, csv).await
+		}
+		SubCommands::Accuracy { store, window } => {
+			subcommands::accuracy::run(&client, opt.clone(), store, window).await
+		}
+		SubCommands::Repl {} => subcommands::repl::run(&client, opt.clone()).await,
+		SubCommands::AnomalyScan { examples, .. } => {
+			let at = opt.at.ok_or_else(|| error::Error::Missing("the block to scan".into()))?;
+			subcommands::anomaly_scan::run(snapshot::Snapshot::fetch(&client, at).await?, examples)
+		}
+		SubCommands::Overlap { predicted, threshold, top } => {
+			subcommands::overlap::run(&client, opt.clone(), predicted, threshold, top).await
+		}
+		SubCommands::Bounds { warn_at } => {
+			subcommands::bounds::run(&client, opt.clone(), warn_at).await
+		}
+		SubCommands::SimulateSlash { who, fraction, iterations } => {
+			let fraction = sp_runtime::Perbill::from_parts(fraction);
+			subcommands::simulate_slash::run(&client, opt.clone(), who, fraction, iterations).await
+		}
+		SubCommands::ExportRewards { who, from_era, to_era, output } => {
+			subcommands::export_rewards::run(&client, opt.clone(), who, from_era, to_era, output)
+				.await
+		}
+		SubCommands::Suggest { count, constraints } => {
+			subcommands::suggest::run(&client, opt.clone(), count, constraints).await
+		}
+		SubCommands::Clusters { .. } => subcommands::clusters::run(&client, opt.clone()).await,
+		SubCommands::Targets { waiting } => {
+			subcommands::targets::run(&client, opt.clone(), waiting).await
+		}
+		SubCommands::Mine(conf) => subcommands::mine::run(&client, opt.clone(), conf).await,
+		SubCommands::MultiBlock(conf) => {
+			subcommands::multi_block::run(&client, opt.clone(), conf).await
+		}
+		SubCommands::Emergency { iterations, force } => {
+			subcommands::emergency::run(&client, opt.clone(), iterations, force).await
+		}
+	}
 }
@@ -1,7 +1,9 @@
 use crate::{
-	primitives::{AccountId, Balance, Hash},
-	storage, Client,
+	error::{self, Error, Result},
+	primitives::{self, AccountId, Balance, BlockNumber, Hash, Header},
+	storage, Client, LOG_TARGET,
 };
+use jsonrpsee_types::jsonrpc::{to_value as to_json_value, Params};
 use atomic_refcell::AtomicRefCell as RefCell;
 use codec::Encode;
 use sp_runtime::traits::Convert;
@@ -10,7 +12,7 @@ static ISSUANCE: RefCell<Balance> = RefCell::new(0);
 /// Deals with total issuance
 pub mod issuance {
 	use super::{get_total_issuance, ISSUANCE};
-	use crate::{Balance, Client, Hash};
+	use crate::{error::Result, Balance, Client, Hash};
 
 	/// Get the previously set total issuance.
 	pub fn get() -> Balance {
@@ -19,8 +21,14 @@ pub mod issuance {
 
 	/// Set the total issuance. Any code wanting to use `CurrencyToVoteHandler` must call this first
 	/// to set correct value in the global pointer.
-	pub async fn set(client: &Client, at: Hash) {
-		let total_issuance = get_total_issuance(client, at).await;
+	pub async fn set(client: &Client, at: Hash) -> Result<()> {
+		let total_issuance = get_total_issuance(client, at).await?;
+		*ISSUANCE.borrow_mut() = total_issuance;
+		Ok(())
+	}
+
+	/// Set the total issuance to a known value, e.g. one read from a state snapshot.
+	pub fn set_to(total_issuance: Balance) {
 		*ISSUANCE.borrow_mut() = total_issuance;
 	}
 }
@@ -45,12 +53,12 @@ impl Convert<u128, u128> for CurrencyToVoteHandler {
 }
 
 /// Get total issuance of the chain.
-async fn get_total_issuance(client: &Client, at: Hash) -> Balance {
+async fn get_total_issuance(client: &Client, at: Hash) -> Result<Balance> {
 	let maybe_total_issuance =
-		storage::read::<Balance>(storage::value_key(b"Balances", b"TotalIssuance"), &client, at)
-			.await;
+		error::try_read::<Balance>(storage::value_key(b"Balances", b"TotalIssuance"), &client, at)
+			.await?;
 
-	maybe_total_issuance.unwrap_or(0)
+	Ok(maybe_total_issuance.unwrap_or(0))
 }
 
 pub async fn get_validators_and_expo_at(
@@ -97,3 +105,139 @@ pub async fn get_validators_and_expo_at(
 
 	(era, validators_and_expo)
 }
+
+/// A range of block numbers, parsed from `<start>..<end>:<step>`, end excluded.
+#[derive(Debug, Clone)]
+pub struct BlockRange {
+	start: BlockNumber,
+	end: BlockNumber,
+	step: BlockNumber,
+}
+
+impl BlockRange {
+	/// The block numbers of the range.
+	pub fn blocks(&self) -> impl Iterator<Item = BlockNumber> {
+		(self.start..self.end).step_by(self.step as usize)
+	}
+}
+
+impl std::str::FromStr for BlockRange {
+	type Err = &'static str;
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		const ERR: &str = "Invalid block range. Expected <start>..<end>:<step>.";
+		let (range, step) = match s.find(':') {
+			Some(i) => (&s[..i], s[i + 1..].parse::<BlockNumber>().map_err(|_| ERR)?),
+			None => (s, 1),
+		};
+		let mut parts = range.splitn(2, "..");
+		let start = parts.next().and_then(|p| p.parse::<BlockNumber>().ok()).ok_or(ERR)?;
+		let end = parts.next().and_then(|p| p.parse::<BlockNumber>().ok()).ok_or(ERR)?;
+		if step == 0 || start >= end {
+			return Err(ERR);
+		}
+		Ok(Self { start, end, step })
+	}
+}
+
+/// Get the header of the block `at`.
+async fn get_header(client: &Client, at: Hash) -> Result<Header> {
+	let params =
+		Params::Array(vec![to_json_value(at).expect("Block hash serialization infallible")]);
+	error::request::<Option<Header>>(client, "chain_getHeader", params)
+		.await?
+		.ok_or(Error::UnknownBlock(at))
+}
+
+/// Get the finalized head of the chain.
+pub async fn get_finalized_head(client: &Client) -> Result<Hash> {
+	error::request::<Hash>(client, "chain_getFinalizedHead", Params::None).await
+}
+
+/// Get the best, possibly not finalized, head of the chain.
+pub async fn get_best_head(client: &Client) -> Result<Hash> {
+	error::request::<Option<Hash>>(client, "chain_getBlockHash", Params::None)
+		.await?
+		.ok_or(Error::Rpc { method: "chain_getBlockHash", reason: "no best head".into() })
+}
+
+/// Get the spec name, e.g. `polkadot`, and the spec version of the runtime at `at`.
+pub async fn get_spec(client: &Client, at: Hash) -> Result<(String, u32)> {
+	let params =
+		Params::Array(vec![to_json_value(at).expect("Block hash serialization infallible")]);
+	let version =
+		error::request::<serde_json::Value>(client, "state_getRuntimeVersion", params).await?;
+	match (version["specName"].as_str(), version["specVersion"].as_u64()) {
+		(Some(name), Some(spec_version)) => Ok((name.into(), spec_version as u32)),
+		_ => Err(Error::Rpc {
+			method: "state_getRuntimeVersion",
+			reason: format!("no spec name or version in {}", version),
+		}),
+	}
+}
+
+/// Detect the length of the account ids of the chain from the first key of `System::Account`, and
+/// set it for all the accounts decoded from now on. Chains with no accounts keep the default.
+pub async fn detect_account_len(client: &Client, at: Hash) -> Result<()> {
+	let prefix = storage::map_prefix_key(b"System", b"Account");
+	let keys = storage::get_keys_paged(prefix.clone(), 1, None, client, at).await;
+	let len = match keys.first() {
+		Some(key) => storage::unhash_concat(&key.0[prefix.0.len()..]).map_or(32, |k| k.len()),
+		None => 32,
+	};
+	primitives::set_account_len(len).map_err(|e| Error::Incompatible(e.into()))?;
+	log::debug!(target: LOG_TARGET, "account ids are {} bytes long", len);
+	Ok(())
+}
+
+/// Check that `at` is part of the finalized chain, and warn loudly if not.
+///
+/// A block that is not finalized might be reorged away, and with it any prediction made on it.
+pub async fn check_finality(client: &Client, at: Hash) -> Result<()> {
+	let header = get_header(client, at).await?;
+	let finalized_head = get_finalized_head(client).await?;
+	let finalized = get_header(client, finalized_head).await?;
+
+	if header.number > finalized.number {
+		log::warn!(
+			target: LOG_TARGET,
+			"⚠️  block #{} ({:?}) is NOT finalized yet (finalized = #{}). Results might be computed \
+			 on a block that gets reorged away.",
+			header.number,
+			at,
+			finalized.number,
+		);
+	} else if storage::get_block_hash(client, header.number).await != Some(at) {
+		log::warn!(
+			target: LOG_TARGET,
+			"⚠️  block #{} ({:?}) is NOT on the finalized chain. It belongs to a fork that has been \
+			 abandoned.",
+			header.number,
+			at,
+		);
+	} else {
+		log::debug!(target: LOG_TARGET, "block #{} is finalized.", header.number);
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::str::FromStr;
+
+	#[test]
+	fn block_range_from_str_works() {
+		let range = BlockRange::from_str("10..20:5").unwrap();
+		assert_eq!(range.blocks().collect::<Vec<_>>(), vec![10, 15]);
+		// the step defaults to one, and the end is excluded.
+		let range = BlockRange::from_str("10..13").unwrap();
+		assert_eq!(range.blocks().collect::<Vec<_>>(), vec![10, 11, 12]);
+
+		assert!(BlockRange::from_str("10..10").is_err());
+		assert!(BlockRange::from_str("20..10").is_err());
+		assert!(BlockRange::from_str("10..20:0").is_err());
+		assert!(BlockRange::from_str("10..20:").is_err());
+		assert!(BlockRange::from_str("10").is_err());
+		assert!(BlockRange::from_str("..20").is_err());
+	}
+}
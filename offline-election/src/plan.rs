@@ -0,0 +1,117 @@
+//! The plan of what a sub-command reads from the chain, see `--plan`.
+//!
+//! Only the keys of the storage maps are counted, no values are fetched. The number of requests
+//! that follow from each key is an estimate of the current implementation of the sub-commands,
+//! and an upper bound where it depends on the values, e.g. the number of targets of a nominator.
+
+use crate::{error, primitives::Hash, storage, Client, Opt, SubCommands};
+use std::time::{Duration, Instant};
+
+/// The page size used to count the keys of a map.
+const PAGE: u32 = 1000;
+
+/// The maximum number of targets of a nominator, each of which is checked for slashes.
+const MAX_NOMINATIONS: u64 = 16;
+
+/// A storage item read by a sub-command.
+#[derive(Debug, Clone, Copy)]
+enum Read {
+	/// A single value.
+	Value(&'static str, &'static str),
+	/// All the pairs of a map, in one request, followed by the given number of requests per key.
+	Map(&'static str, &'static str, u64),
+}
+
+/// What the staking election reads to build its snapshot.
+const SNAPSHOT: &[Read] = &[
+	Read::Value("Staking", "ValidatorCount"),
+	// the self-vote of each candidate: `Bonded` and `Ledger`.
+	Read::Map("Staking", "Validators", 2),
+	// `Bonded`, `Ledger`, and the `SlashingSpans` of each target.
+	Read::Map("Staking", "Nominators", 2 + MAX_NOMINATIONS),
+];
+
+/// What the council election reads.
+const COUNCIL: &[Read] = &[
+	Read::Value("PhragmenElection", "Candidates"),
+	Read::Value("PhragmenElection", "Members"),
+	Read::Value("PhragmenElection", "RunnersUp"),
+	Read::Map("PhragmenElection", "Voting", 0),
+];
+
+/// The reads of `cmd`, if known. `--plan` is rejected for the sub-commands that have none,
+/// including those that run without a node.
+pub(crate) fn reads_of(cmd: &SubCommands) -> Option<&'static [Read]> {
+	match cmd {
+		SubCommands::AnomalyScan { input: Some(_), .. } => None,
+		SubCommands::Staking(conf) if conf.offline || conf.schema => None,
+		SubCommands::Staking(_)
+		| SubCommands::Accuracy { .. }
+		| SubCommands::AnomalyScan { .. }
+		| SubCommands::Bounds { .. }
+		| SubCommands::Emergency { .. }
+		| SubCommands::Mine(_) => Some(SNAPSHOT),
+		SubCommands::Council(_) => Some(COUNCIL),
+		SubCommands::DanglingNominators { .. } => {
+			Some(&[Read::Map("Staking", "Nominators", MAX_NOMINATIONS)])
+		}
+		_ => None,
+	}
+}
+
+/// Count the keys under the prefix of the map `module::item`, in pages of [`PAGE`].
+async fn count_keys(module: &str, item: &str, client: &Client, at: Hash) -> (u64, u64) {
+	let prefix = storage::map_prefix_key(module.as_bytes(), item.as_bytes());
+	let (mut keys, mut pages) = (0u64, 0u64);
+	let mut start = None;
+	loop {
+		let page = storage::get_keys_paged(prefix.clone(), PAGE, start, client, at).await;
+		pages += 1;
+		keys += page.len() as u64;
+		if page.len() < PAGE as usize {
+			break (keys, pages);
+		}
+		start = page.last().cloned();
+	}
+}
+
+/// Print the plan of the sub-command of `opt`, without running it.
+pub async fn run(client: &Client, opt: &Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let reads = reads_of(&opt.cmd).expect("sub-commands without a plan are rejected in main; qed");
+
+	println!("🗺  plan at {:?}:", at);
+	let mut requests = 0u64;
+	let mut counting = 0u64;
+	for read in reads.iter() {
+		match read {
+			Read::Value(module, item) => {
+				println!("\t{}::{}: value", module, item);
+				requests += 1;
+			}
+			Read::Map(module, item, per_key) => {
+				let (keys, pages) = count_keys(module, item, client, at).await;
+				counting += pages;
+				println!(
+					"\t{}::{}: {} keys, 1 request + up to {} per key",
+					module, item, keys, per_key
+				);
+				requests += 1 + keys * per_key;
+			}
+		}
+	}
+
+	// the latency of a cheap request, the sub-commands send their requests one after the other.
+	let start = Instant::now();
+	let _ = storage::get_storage_size(storage::value_key(b"System", b"Number"), client, at).await;
+	let latency = start.elapsed();
+	let interval = match opt.max_rps {
+		0 => Duration::default(),
+		rps => Duration::from_secs(1) / rps,
+	};
+	let duration = latency.max(interval) * requests.min(u32::max_value() as u64) as u32;
+
+	println!("\tup to {} requests, in about {:?}", requests, duration);
+	println!("\t({} requests were needed to count the keys)", counting);
+	Ok(())
+}
@@ -1,14 +1,149 @@
 //! Some primitive types re-exported to the entire crate.
 //!
-//! As long as these are the same in kusama/polkadot/your-chain, then we are good.
+//! As long as these are the same in kusama/polkadot/your-chain, then we are good. The account id is
+//! the exception, as some chains use 20 byte accounts, see [`set_account_len`].
+
+use codec::{Decode, Encode, Input, Output};
+use sp_core::crypto::AccountId32;
+use std::{
+	fmt,
+	str::FromStr,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The length of the account ids of the chain, in bytes.
+static ACCOUNT_LEN: AtomicUsize = AtomicUsize::new(32);
+
+/// Set the length of the account ids of the chain: 32 for ss58 accounts, 20 for ethereum-style
+/// (H160) accounts. Any other length is not supported.
+pub fn set_account_len(len: usize) -> Result<(), &'static str> {
+	match len {
+		20 | 32 => Ok(ACCOUNT_LEN.store(len, Ordering::SeqCst)),
+		_ => Err("account ids must be 20 or 32 bytes long."),
+	}
+}
+
+/// The length of the account ids of the chain, in bytes. 32 unless set otherwise.
+pub fn account_len() -> usize {
+	ACCOUNT_LEN.load(Ordering::SeqCst)
+}
 
 /// The account id type.
-pub type AccountId = sp_core::crypto::AccountId32;
+///
+/// Either a 32 byte account, displayed in ss58, or a 20 byte ethereum-style account, displayed in
+/// hex with a `0x` prefix. Which of the two is decoded depends on [`account_len`], while both are
+/// parsed from strings.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum AccountId {
+	/// A 32 byte account.
+	Id32(AccountId32),
+	/// A 20 byte ethereum-style account.
+	Id20([u8; 20]),
+}
+
+impl Default for AccountId {
+	fn default() -> Self {
+		match account_len() {
+			20 => Self::Id20(Default::default()),
+			_ => Self::Id32(Default::default()),
+		}
+	}
+}
+
+impl AsRef<[u8]> for AccountId {
+	fn as_ref(&self) -> &[u8] {
+		match self {
+			Self::Id32(who) => who.as_ref(),
+			Self::Id20(who) => &who[..],
+		}
+	}
+}
+
+impl From<[u8; 32]> for AccountId {
+	fn from(raw: [u8; 32]) -> Self {
+		Self::Id32(raw.into())
+	}
+}
+
+impl From<[u8; 20]> for AccountId {
+	fn from(raw: [u8; 20]) -> Self {
+		Self::Id20(raw)
+	}
+}
+
+impl Encode for AccountId {
+	fn size_hint(&self) -> usize {
+		self.as_ref().len()
+	}
+
+	fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+		dest.write(self.as_ref())
+	}
+}
+
+impl codec::EncodeLike for AccountId {}
+
+impl Decode for AccountId {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+		match account_len() {
+			20 => <[u8; 20]>::decode(input).map(Self::Id20),
+			_ => AccountId32::decode(input).map(Self::Id32),
+		}
+	}
+}
+
+impl fmt::Display for AccountId {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Id32(who) => write!(f, "{}", who),
+			Self::Id20(who) => write!(f, "0x{}", hex::encode(who)),
+		}
+	}
+}
+
+impl fmt::Debug for AccountId {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Id32(who) => write!(f, "{:?}", who),
+			Self::Id20(who) => write!(f, "0x{}", hex::encode(who)),
+		}
+	}
+}
+
+impl FromStr for AccountId {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.strip_prefix("0x") {
+			Some(h160) if h160.len() == 40 => {
+				let mut raw = [0u8; 20];
+				hex::decode_to_slice(h160, &mut raw).map_err(|_| "invalid H160 address.")?;
+				Ok(Self::Id20(raw))
+			}
+			_ => AccountId32::from_str(s).map(Self::Id32),
+		}
+	}
+}
+
+impl serde::Serialize for AccountId {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_string())
+	}
+}
+
+impl<'de> serde::Deserialize<'de> for AccountId {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		Self::from_str(&s).map_err(serde::de::Error::custom)
+	}
+}
+
 /// The balance type.
 pub type Balance = u128;
 /// The hash type.
 pub type Hash = sp_core::hash::H256;
 /// The block number type
 pub type BlockNumber = u32;
+/// The header type.
+pub type Header = sp_runtime::generic::Header<BlockNumber, sp_runtime::traits::BlakeTwo256>;
 /// Re-exported hashing types.
 pub use sp_core::hashing::{blake2_256, twox_128};
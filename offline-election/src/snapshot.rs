@@ -0,0 +1,208 @@
+//! A snapshot of the inputs of the staking election, which can be stored on disk and used later
+//! without the need to scrape a chain again.
+
+use crate::{
+	error::{self, Error},
+	network,
+	primitives::{self, account_len, AccountId, Balance, Hash},
+	storage,
+	subcommands::staking,
+	interrupt, Client, LOG_TARGET,
+};
+use codec::Decode;
+use frame_support::{Blake2_128Concat, Twox64Concat};
+use pallet_staking::{Nominations, StakingLedger};
+use sp_npos_elections::VoteWeight;
+use std::{
+	collections::BTreeMap,
+	fs::File,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+lazy_static::lazy_static! {
+	/// The last snapshot scraped by this process, see [`Snapshot::fetch`].
+	static ref LAST_FETCHED: Mutex<Option<Snapshot>> = Mutex::new(None);
+}
+
+/// A voter, its vote weight, and its targets.
+pub type Voter = (AccountId, VoteWeight, Vec<AccountId>);
+
+/// The inputs of a staking election.
+///
+/// Similar to the snapshot of the election provider on chain, the voters include the self-vote of
+/// each target.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct Snapshot {
+	/// The block at which this snapshot was scraped, if it was scraped from a chain.
+	pub at: Option<Hash>,
+	/// The number of targets that the chain desires to elect.
+	pub desired_targets: u32,
+	/// All the validator candidates.
+	pub targets: Vec<AccountId>,
+	/// All the voters.
+	pub voters: Vec<Voter>,
+}
+
+impl Snapshot {
+	/// The file in which an interrupted scrape at `at` keeps its progress.
+	fn partial_path(at: Hash) -> PathBuf {
+		PathBuf::from(format!("partial-snapshot-{:?}.json", at))
+	}
+
+	/// Scrape a new snapshot from the chain at the given block.
+	///
+	/// On ctrl-c, the voters fetched so far are saved in the working directory, and the process
+	/// exits. A later scrape of the same block resumes from them.
+	///
+	/// The last scraped snapshot is kept in memory, and a later scrape of the same block in the
+	/// same process is served from it, e.g. by the `repl`.
+	pub async fn fetch(client: &Client, at: Hash) -> error::Result<Self> {
+		if let Some(cached) = LAST_FETCHED.lock().unwrap().as_ref().filter(|s| s.at == Some(at)) {
+			log::debug!(target: LOG_TARGET, "snapshot at {:?} served from memory", at);
+			return Ok(cached.clone());
+		}
+
+		let desired_targets = staking::get_validator_count(client, at).await;
+		let targets = staking::get_candidates(client, at).await;
+
+		let partial_path = Self::partial_path(at);
+		let done = if partial_path.exists() {
+			let partial = Self::load(&partial_path)?;
+			log::info!(target: LOG_TARGET, "resuming a scrape with {} voters", partial.voters.len());
+			partial.voters
+		} else {
+			vec![]
+		};
+
+		interrupt::set_scraping(true);
+		let outcome = staking::get_voters(client, at, done).await;
+		interrupt::set_scraping(false);
+		let mut voters = match outcome {
+			Ok(voters) => voters,
+			Err(partial) => {
+				let count = partial.len();
+				Self { at: Some(at), desired_targets, targets, voters: partial }
+					.save(&partial_path)?;
+				println!(
+					"⏸  scrape interrupted after {} voters, saved to {:?}. Run the same command with \
+					 `--at {:?}` to resume.",
+					count, partial_path, at
+				);
+				std::process::exit(130);
+			}
+		};
+		let _ = std::fs::remove_file(&partial_path);
+
+		voters.extend(staking::get_self_votes(&targets, client, at).await);
+		let snapshot = Self { at: Some(at), desired_targets, targets, voters };
+		*LAST_FETCHED.lock().unwrap() = Some(snapshot.clone());
+		Ok(snapshot)
+	}
+
+	/// Load a snapshot from a file.
+	///
+	/// The file is either a json snapshot, as saved by [`Snapshot::save`], or a state snapshot of
+	/// `remote-externalities`, as created by `try-runtime create-snapshot`.
+	pub fn load(path: &Path) -> error::Result<Self> {
+		log::info!(target: LOG_TARGET, "loading snapshot from {:?}", path);
+		let bytes = std::fs::read(path).map_err(|e| Error::file(path, e))?;
+		match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+			Some(b'{') => serde_json::from_slice(&bytes).map_err(|e| Error::file(path, e)),
+			_ => Self::from_state(&bytes).map_err(|e| Error::file(path, e)),
+		}
+	}
+
+	/// Build a snapshot from the raw key-value pairs of a `remote-externalities` state snapshot.
+	///
+	/// This also sets the total issuance to the one of the state, so that stakes are converted to
+	/// vote weights as they would be on chain, and the length of its account ids.
+	fn from_state(bytes: &[u8]) -> Result<Self, String> {
+		let pairs = <Vec<(Vec<u8>, Vec<u8>)>>::decode(&mut &*bytes)
+			.map_err(|_| "neither a json nor a state snapshot".to_string())?;
+		log::info!(target: LOG_TARGET, "state snapshot with {} keys", pairs.len());
+		let state = pairs.into_iter().collect::<BTreeMap<_, _>>();
+
+		let accounts = storage::map_prefix_key(b"System", b"Account").0;
+		let first =
+			state.range(accounts.clone()..).next().filter(|(k, _)| k.starts_with(&accounts));
+		if let Some((key, _)) = first {
+			if let Some(account) = storage::unhash_concat(&key[accounts.len()..]) {
+				primitives::set_account_len(account.len())?;
+			}
+		}
+
+		fn get<T: Decode>(
+			state: &BTreeMap<Vec<u8>, Vec<u8>>,
+			key: storage::StorageKey,
+		) -> Result<Option<T>, String> {
+			state
+				.get(&key.0)
+				.map(|v| T::decode(&mut v.as_slice()))
+				.transpose()
+				.map_err(|e| format!("0x{} failed to decode: {}", hex::encode(&key.0), e))
+		}
+		let keys_of = |module: &[u8], item: &[u8]| -> Result<Vec<(AccountId, Vec<u8>)>, String> {
+			let prefix = storage::map_prefix_key(module, item).0;
+			state
+				.range(prefix.clone()..)
+				.take_while(|(k, _)| k.starts_with(&prefix))
+				.map(|(k, v)| {
+					let key = k[k.len() - account_len()..].to_vec();
+					AccountId::decode(&mut key.as_slice())
+						.map(|who| (who, v.clone()))
+						.map_err(|e| format!("key 0x{} failed to decode: {}", hex::encode(k), e))
+				})
+				.collect::<Result<Vec<_>, _>>()
+		};
+		let stake_of = |stash: &AccountId| -> Result<VoteWeight, String> {
+			let ctrl = get::<AccountId>(
+				&state,
+				storage::map_key::<Twox64Concat>(b"Staking", b"Bonded", stash.as_ref()),
+			)?
+			.ok_or_else(|| format!("{:?} has no 'Bonded' storage", stash))?;
+			let ledger = get::<StakingLedger<AccountId, Balance>>(
+				&state,
+				storage::map_key::<Blake2_128Concat>(b"Staking", b"Ledger", ctrl.as_ref()),
+			)?
+			.ok_or_else(|| format!("{:?} has no 'Ledger' storage", ctrl))?;
+			Ok(staking::to_vote_weight(ledger.active))
+		};
+
+		network::issuance::set_to(
+			get::<Balance>(&state, storage::value_key(b"Balances", b"TotalIssuance"))?
+				.unwrap_or_default(),
+		);
+		let desired_targets =
+			get::<u32>(&state, storage::value_key(b"Staking", b"ValidatorCount"))?.unwrap_or(50);
+		let targets =
+			keys_of(b"Staking", b"Validators")?.into_iter().map(|(t, _)| t).collect::<Vec<_>>();
+		let mut voters = keys_of(b"Staking", b"Nominators")?
+			.into_iter()
+			.map(|(who, v)| -> Result<Voter, String> {
+				let nominations = <Nominations<AccountId>>::decode(&mut v.as_slice())
+					.map_err(|e| format!("nominations of {:?} failed to decode: {}", who, e))?;
+				let stake = stake_of(&who)?;
+				Ok((who, stake, nominations.targets))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		for t in targets.iter() {
+			voters.push((t.clone(), stake_of(t)?, vec![t.clone()]));
+		}
+
+		Ok(Self { at: None, desired_targets, targets, voters })
+	}
+
+	/// Save the snapshot to a json file.
+	pub fn save(&self, path: &Path) -> error::Result<()> {
+		log::info!(
+			target: LOG_TARGET,
+			"saving snapshot with {} voters and {} targets to {:?}",
+			self.voters.len(),
+			self.targets.len(),
+			path
+		);
+		let file = File::create(path).map_err(|e| Error::file(path, e))?;
+		serde_json::to_writer(std::io::BufWriter::new(file), self).map_err(|e| Error::file(path, e))
+	}
+}
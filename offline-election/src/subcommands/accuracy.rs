@@ -0,0 +1,148 @@
+//! Track the accuracy of the predictions of the staking election.
+//!
+//! Each run stores the prediction of the next election in a json file, and compares the stored
+//! predictions of the eras that became active since with the validators that the chain actually
+//! elected. Running it periodically, e.g. once per session, builds up the accuracy statistics.
+//!
+//! The actual set is read from `ErasStakers`, which is only kept for the history depth: a
+//! prediction that is not compared within it is never resolved.
+
+use crate::{
+	error, network,
+	primitives::{AccountId, Balance, Hash},
+	subcommands::{clusters, session_info, staking},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use pallet_staking::EraIndex;
+use sp_runtime::traits::Convert;
+use std::path::PathBuf;
+
+/// The score of a validator set, in the smallest unit of the currency.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+struct Score {
+	/// The backing of the least backed validator.
+	minimal_stake: Balance,
+	/// The backing of all validators.
+	total_stake: Balance,
+}
+
+impl Score {
+	fn new(backings: impl Iterator<Item = Balance>) -> Self {
+		let backings = backings.collect::<Vec<_>>();
+		Self {
+			minimal_stake: backings.iter().min().cloned().unwrap_or_default(),
+			total_stake: backings.iter().sum(),
+		}
+	}
+}
+
+/// The comparison of a prediction with the actual outcome.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct Outcome {
+	/// The share of the actual set that was predicted, in percent.
+	overlap: f64,
+	/// The difference of the minimal stake of the prediction from the actual one, in percent.
+	score_gap: f64,
+	score: Score,
+}
+
+/// A stored prediction.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct Prediction {
+	/// The era that the predicted election is for.
+	era: EraIndex,
+	/// The block at which the prediction was made.
+	at: Hash,
+	winners: Vec<AccountId>,
+	score: Score,
+	/// The comparison with the actual set, once the era is active.
+	outcome: Option<Outcome>,
+}
+
+fn load(path: &PathBuf) -> Vec<Prediction> {
+	if !path.exists() {
+		return vec![];
+	}
+	let file = std::fs::read(path).expect("Failed to read predictions file.");
+	serde_json::from_slice(&file).expect("Invalid predictions file.")
+}
+
+fn save(path: &PathBuf, predictions: &[Prediction]) {
+	let file = std::fs::File::create(path).expect("Failed to create predictions file.");
+	serde_json::to_writer_pretty(file, predictions).expect("Failed to write predictions file.");
+}
+
+/// Compare `prediction` with the validators actually elected in its era.
+async fn resolve(prediction: &Prediction, client: &Client, at: Hash) -> Option<Outcome> {
+	let exposures = clusters::exposures_at(prediction.era, client, at).await;
+	if exposures.is_empty() {
+		return None;
+	}
+	let score = Score::new(exposures.iter().map(|(_, e)| e.total));
+	let predicted = exposures.iter().filter(|(v, _)| prediction.winners.contains(v)).count();
+	let overlap = predicted as f64 * 100f64 / exposures.len() as f64;
+	let score_gap = (prediction.score.minimal_stake as f64 - score.minimal_stake as f64) * 100f64
+		/ (score.minimal_stake as f64).max(1f64);
+	Some(Outcome { overlap, score_gap, score })
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, store: PathBuf, window: usize) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let mut predictions = load(&store);
+
+	for prediction in predictions.iter_mut().filter(|p| p.outcome.is_none()) {
+		if prediction.era <= active_era {
+			prediction.outcome = resolve(prediction, client, at).await;
+			if prediction.outcome.is_none() {
+				log::warn!(target: LOG_TARGET, "era {} is out of history.", prediction.era);
+			}
+		}
+	}
+
+	// the next election plans the era after the current one, which may already be planned.
+	let era = staking::get_current_era(client, at).await + 1;
+	let (winners, supports) = staking::predict(client, at).await?;
+	let score = Score::new(
+		supports
+			.values()
+			.map(|s| <network::CurrencyToVoteHandler as Convert<u128, u128>>::convert(s.total)),
+	);
+	predictions.retain(|p| p.era != era);
+	predictions.push(Prediction { era, at, winners, score, outcome: None });
+	predictions.sort_by_key(|p| p.era);
+	save(&store, &predictions);
+	println!("🔮 prediction of era {} stored in {:?}.", era, store);
+
+	let recent = predictions
+		.iter()
+		.filter_map(|p| p.outcome.as_ref().map(|o| (p.era, o)))
+		.collect::<Vec<_>>();
+	let recent = &recent[recent.len().saturating_sub(window)..];
+	if recent.is_empty() {
+		println!("🎯 no prediction is resolved yet, run again once era {} is active.", era);
+		return Ok(());
+	}
+
+	println!("🎯 accuracy of the last {} resolved predictions:", recent.len());
+	for (era, outcome) in recent.iter() {
+		println!(
+			"\tera {}: {:.2}% of the set predicted, minimal stake {:?} off by {:.2}%",
+			era,
+			outcome.overlap,
+			Currency::from(outcome.score.minimal_stake),
+			outcome.score_gap,
+		);
+	}
+	let mean = |f: fn(&Outcome) -> f64| {
+		recent.iter().map(|(_, o)| f(o)).sum::<f64>() / recent.len() as f64
+	};
+	println!(
+		"\tmean: {:.2}% of the set predicted, minimal stake off by {:.2}% ({:.2}% in absolute)",
+		mean(|o| o.overlap),
+		mean(|o| o.score_gap),
+		mean(|o| o.score_gap.abs()),
+	);
+	Ok(())
+}
@@ -0,0 +1,89 @@
+//! Scan the election snapshot for oddities that the chain should not, but might, let through.
+//!
+//! None of these break the election, but each is either a bug of the runtime, of the scrape, or a
+//! voter that is not getting what it thinks it gets.
+
+use crate::{error, primitives::AccountId, snapshot::Snapshot, LOG_TARGET};
+use std::collections::BTreeSet;
+
+/// The maximum number of targets of a nomination.
+const MAX_NOMINATIONS: usize = 16;
+
+/// A class of anomalies, with all the voters that fall in it.
+struct Anomaly {
+	what: &'static str,
+	voters: Vec<AccountId>,
+}
+
+/// Find all the anomalies of `snapshot`, as the classes and the unknown targets.
+fn scan(snapshot: &Snapshot) -> (Vec<Anomaly>, BTreeSet<AccountId>) {
+	let targets = snapshot.targets.iter().collect::<BTreeSet<_>>();
+	let mut self_and_others = vec![];
+	let mut duplicates = vec![];
+	let mut zero_weight = vec![];
+	let mut unknown_targets = vec![];
+	let mut too_many = vec![];
+	let mut unknown = BTreeSet::new();
+
+	for (voter, weight, votes) in snapshot.voters.iter() {
+		// the self-vote of a target is expected, anything else from a target is not.
+		if targets.contains(voter) && votes.iter().any(|t| t != voter) {
+			self_and_others.push(voter.clone());
+		}
+		if votes.iter().collect::<BTreeSet<_>>().len() < votes.len() {
+			duplicates.push(voter.clone());
+		}
+		if *weight == 0 {
+			zero_weight.push(voter.clone());
+		}
+		let not_validators =
+			votes.iter().filter(|t| !targets.contains(t)).cloned().collect::<Vec<_>>();
+		if !not_validators.is_empty() {
+			unknown_targets.push(voter.clone());
+			unknown.extend(not_validators);
+		}
+		if votes.len() > MAX_NOMINATIONS {
+			too_many.push(voter.clone());
+		}
+	}
+
+	let anomalies = vec![
+		Anomaly { what: "validators voting for themselves and others", voters: self_and_others },
+		Anomaly { what: "nominations with duplicate targets", voters: duplicates },
+		Anomaly { what: "zero-weight voters", voters: zero_weight },
+		Anomaly { what: "nominations of targets not in `Validators`", voters: unknown_targets },
+		Anomaly { what: "nominations exceeding 16 targets", voters: too_many },
+	];
+	(anomalies, unknown)
+}
+
+/// Main run function of the sub-command.
+///
+/// Note that this sub-command does not need a connection to any node once the snapshot exists.
+pub fn run(snapshot: Snapshot, examples: usize) -> error::Result<()> {
+	log::info!(
+		target: LOG_TARGET,
+		"scanning {} voters and {} targets",
+		snapshot.voters.len(),
+		snapshot.targets.len()
+	);
+	let (anomalies, unknown) = scan(&snapshot);
+
+	let mut found = 0;
+	for anomaly in anomalies.iter() {
+		let marker = if anomaly.voters.is_empty() { "✅" } else { "⚠️ " };
+		println!("{} {} {}", marker, anomaly.voters.len(), anomaly.what);
+		for voter in anomaly.voters.iter().take(examples) {
+			let (_, weight, votes) =
+				snapshot.voters.iter().find(|(v, _, _)| v == voter).expect("voter is scanned; qed");
+			println!("\t{:?} [weight = {}] -> {:?}", voter, weight, votes);
+		}
+		found += anomaly.voters.len();
+	}
+	if !unknown.is_empty() {
+		println!("\t{} distinct targets are not in `Validators`:", unknown.len());
+		unknown.iter().take(examples).for_each(|t| println!("\t\t{:?}", t));
+	}
+	println!("📊 {} anomalies among {} voters.", found, snapshot.voters.len());
+	Ok(())
+}
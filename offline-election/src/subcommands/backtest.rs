@@ -0,0 +1,231 @@
+//! Replay past eras to evaluate the returns of a nomination strategy.
+
+use crate::{
+	error,
+	primitives::{account_len, AccountId, Balance, Hash},
+	storage,
+	subcommands::{
+		clusters, commission_history, inflation, payout_check, reward_pot, session_info, staking,
+		validator_check,
+	},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use codec::{Decode, Encode};
+use frame_support::{StorageHasher, Twox64Concat};
+use pallet_staking::{EraIndex, Exposure};
+use sp_runtime::Perbill;
+use std::path::PathBuf;
+
+/// Number of milliseconds in a year, as used by the inflation model.
+const MILLISECONDS_PER_YEAR: f64 = 1000f64 * 3600f64 * 24f64 * 36525f64 / 100f64;
+
+/// The reward that `bond`, evenly distributed among the elected `targets`, would have earned in
+/// `era`. Returns the number of elected targets along with the reward.
+async fn strategy_reward(
+	targets: &[AccountId],
+	bond: Balance,
+	era: EraIndex,
+	client: &Client,
+	at: Hash,
+) -> (usize, Balance) {
+	let mut elected = vec![];
+	for t in targets.iter() {
+		let exposure = staking::exposure_of(t, era, client, at).await;
+		if exposure.total > 0 {
+			elected.push((t, exposure));
+		}
+	}
+
+	// the bond is assumed to be evenly distributed among the elected targets.
+	let share = bond / elected.len().max(1) as Balance;
+	let mut reward: Balance = 0;
+	for (t, exposure) in elected.iter() {
+		let payout = payout_check::estimate_payout(t, era, client, at).await;
+		let commission =
+			commission_history::commission_at(t, era, client, at).await.unwrap_or_default();
+		let nominators_payout = payout.total - commission * payout.total;
+		reward +=
+			Perbill::from_rational_approximation(share, exposure.total + share) * nominators_payout;
+	}
+	(elected.len(), reward)
+}
+
+/// Get the clipped exposures of all the validators of `era`, i.e. the ones that are rewarded.
+async fn clipped_exposures_at(
+	era: EraIndex,
+	client: &Client,
+	at: Hash,
+) -> Vec<(AccountId, Exposure<AccountId, Balance>)> {
+	let mut prefix = storage::module_prefix_raw(b"Staking", b"ErasStakersClipped");
+	prefix.extend(Twox64Concat::hash(era.encode().as_ref()));
+	storage::get_pairs(storage::StorageKey(prefix), client, at)
+		.await
+		.into_iter()
+		.map(|(k, v)| {
+			let key = k.0[k.0.len() - account_len()..].to_vec();
+			let who = AccountId::decode(&mut key.as_slice()).expect("stash must decode");
+			let exposure = <Exposure<AccountId, Balance>>::decode(&mut v.0.as_slice())
+				.expect("exposure must decode");
+			(who, exposure)
+		})
+		.collect::<Vec<_>>()
+}
+
+/// The rewards that `who` actually earned in `era` as a nominator, from the clipped exposures, per
+/// validator.
+pub(crate) async fn nominator_rewards(
+	who: &AccountId,
+	era: EraIndex,
+	client: &Client,
+	at: Hash,
+) -> Vec<(AccountId, Balance)> {
+	let mut rewards = vec![];
+	for (v, exposure) in clipped_exposures_at(era, client, at).await {
+		if let Some(value) = exposure.others.iter().find(|e| &e.who == who).map(|e| e.value) {
+			let payout = payout_check::estimate_payout(&v, era, client, at).await;
+			let commission =
+				commission_history::commission_at(&v, era, client, at).await.unwrap_or_default();
+			let nominators_payout = payout.total - commission * payout.total;
+			let reward = Perbill::from_rational_approximation(value, exposure.total.max(1))
+				* nominators_payout;
+			rewards.push((v, reward));
+		}
+	}
+	rewards
+}
+
+/// Reconstruct the rewards of the nominator `who` over the past eras, and compare its realized
+/// returns with nominating the entire active set.
+///
+/// The returns are relative to the current active bond of `who`, which is also used as the bond
+/// of the baseline.
+pub async fn run_nominator(
+	client: &Client,
+	opt: Opt,
+	who: AccountId,
+	eras: u32,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let bond = staking::stake_of(&who, client, at).await;
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let depth = commission_history::history_depth(client, at).await;
+	let first_era = active_era.saturating_sub(eras.min(depth));
+	let eras_per_year = MILLISECONDS_PER_YEAR / session_info::era_duration(client, at).await as f64;
+	log::info!(
+		target: LOG_TARGET,
+		"replaying eras {}..{} for {:?} with a bond of {:?}",
+		first_era,
+		active_era,
+		who,
+		Currency::from(bond)
+	);
+
+	let mut total_reward: Balance = 0;
+	let mut missed = vec![];
+	let mut realized_rates = vec![];
+	let mut baseline_rates = vec![];
+	for era in first_era..active_era {
+		let reward = nominator_rewards(&who, era, client, at)
+			.await
+			.into_iter()
+			.map(|(_, r)| r)
+			.sum::<Balance>();
+		let active_set = clusters::elected_at(era, client, at)
+			.await
+			.into_iter()
+			.map(|(v, _)| v)
+			.collect::<Vec<_>>();
+		let (_, baseline) = strategy_reward(&active_set, bond, era, client, at).await;
+		if reward == 0 {
+			missed.push(era);
+		}
+
+		let realized_rate = reward as f64 / bond.max(1) as f64;
+		let baseline_rate = baseline as f64 / bond.max(1) as f64;
+		println!(
+			"\tera {}: reward {:?} / return {:.4}% vs active set {:.4}%",
+			era,
+			Currency::from(reward),
+			realized_rate * 100f64,
+			baseline_rate * 100f64,
+		);
+		total_reward += reward;
+		realized_rates.push(realized_rate);
+		baseline_rates.push(baseline_rate);
+	}
+
+	let mean = |rates: &[f64]| rates.iter().sum::<f64>() / rates.len().max(1) as f64;
+	println!(
+		"💰 total reward over {} eras: {:?}",
+		realized_rates.len(),
+		Currency::from(total_reward)
+	);
+	println!("🕳  {} eras without rewards: {:?}", missed.len(), missed);
+	println!(
+		"📈 realized APY {:.2}% vs nominating the whole active set {:.2}%",
+		mean(&realized_rates) * eras_per_year * 100f64,
+		mean(&baseline_rates) * eras_per_year * 100f64,
+	);
+	Ok(())
+}
+
+/// Main run function of the sub-command.
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	targets: PathBuf,
+	bond: Balance,
+	eras: u32,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let targets = validator_check::accounts_from_file(&targets)?;
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let depth = commission_history::history_depth(client, at).await;
+	let first_era = active_era.saturating_sub(eras.min(depth));
+	let eras_per_year = MILLISECONDS_PER_YEAR / session_info::era_duration(client, at).await as f64;
+	log::info!(
+		target: LOG_TARGET,
+		"replaying eras {}..{} with {} targets and a bond of {:?}",
+		first_era,
+		active_era,
+		targets.len(),
+		Currency::from(bond)
+	);
+
+	let mut total_reward: Balance = 0;
+	let mut strategy_rates = vec![];
+	let mut network_rates = vec![];
+	for era in first_era..active_era {
+		let (elected, reward) = strategy_reward(&targets, bond, era, client, at).await;
+
+		let era_reward = reward_pot::era_reward_of(era, client, at).await.unwrap_or_default();
+		let era_stake = inflation::total_stake_at(era, client, at).await;
+		let strategy_rate = reward as f64 / bond.max(1) as f64;
+		let network_rate = era_reward as f64 / era_stake.max(1) as f64;
+		println!(
+			"\tera {}: {}/{} targets elected / reward {:?} / return {:.4}% vs network {:.4}%",
+			era,
+			elected,
+			targets.len(),
+			Currency::from(reward),
+			strategy_rate * 100f64,
+			network_rate * 100f64,
+		);
+		total_reward += reward;
+		strategy_rates.push(strategy_rate);
+		network_rates.push(network_rate);
+	}
+
+	let mean = |rates: &[f64]| rates.iter().sum::<f64>() / rates.len().max(1) as f64;
+	println!(
+		"💰 total reward over {} eras: {:?}",
+		strategy_rates.len(),
+		Currency::from(total_reward)
+	);
+	println!(
+		"📈 annualized return {:.2}% vs network average {:.2}%",
+		mean(&strategy_rates) * eras_per_year * 100f64,
+		mean(&network_rates) * eras_per_year * 100f64,
+	);
+	Ok(())
+}
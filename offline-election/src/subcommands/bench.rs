@@ -0,0 +1,119 @@
+//! Benchmark the election pipeline on a snapshot.
+
+use crate::{
+	error::{self, Error},
+	primitives::AccountId,
+	snapshot::Snapshot,
+	BenchConfig,
+};
+use sp_npos_elections::*;
+use std::{collections::BTreeMap, time::Instant};
+
+/// Summary statistics of a number of measurements, in milliseconds.
+struct Stats {
+	min: f64,
+	max: f64,
+	mean: f64,
+	std_dev: f64,
+}
+
+impl Stats {
+	fn new(samples: &[f64]) -> Self {
+		let n = samples.len() as f64;
+		let mean = samples.iter().sum::<f64>() / n;
+		let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+		Self {
+			min: samples.iter().cloned().fold(f64::INFINITY, f64::min),
+			max: samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+			mean,
+			std_dev: variance.sqrt(),
+		}
+	}
+}
+
+impl std::fmt::Display for Stats {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"mean {:>10.2}ms ± {:>8.2}ms [min {:>10.2}ms / max {:>10.2}ms]",
+			self.mean, self.std_dev, self.min, self.max
+		)
+	}
+}
+
+pub(crate) fn elapsed_ms(since: Instant) -> f64 {
+	since.elapsed().as_secs_f64() * 1000f64
+}
+
+/// Main run function of the sub-command.
+///
+/// Note that this sub-command does not need a connection to any node.
+pub fn run(conf: BenchConfig) -> error::Result<()> {
+	let Snapshot { desired_targets, targets, voters, .. } = Snapshot::load(&conf.input)?;
+	let count = conf.count.unwrap_or(desired_targets as usize);
+	if conf.runs == 0 {
+		return Err(Error::Invalid("at least one run is needed.".into()));
+	}
+	let iterations = conf.iterations;
+	println!(
+		"🏋️ Benchmarking {} runs of electing {} out of {} targets with {} voters.",
+		conf.runs,
+		count,
+		targets.len(),
+		voters.len()
+	);
+
+	let weights = voters.iter().map(|(v, w, _)| (v.clone(), *w)).collect::<BTreeMap<_, _>>();
+	let weight_of =
+		|who: &AccountId| -> VoteWeight { weights.get(who).cloned().unwrap_or_default() };
+
+	let mut phragmen_samples = vec![];
+	let mut balancing_samples = vec![];
+	let mut reduce_samples = vec![];
+	for r in 0..conf.runs {
+		let start = Instant::now();
+		let (candidates, election_voters) = setup_inputs(targets.clone(), voters.clone());
+		let (_, mut election_voters) =
+			seq_phragmen_core::<AccountId>(count, candidates, election_voters)
+				.map_err(|e| Error::Election(format!("{:?}", e)))?;
+		let phragmen = elapsed_ms(start);
+
+		// balancing runs on the unbalanced output of the same run, as `seq_phragmen` does.
+		let start = Instant::now();
+		let balance_iterations = balance::<AccountId>(&mut election_voters, iterations, 0);
+		let balancing = elapsed_ms(start);
+
+		let assignments = election_voters
+			.into_iter()
+			.filter_map(|v| v.into_assignment::<pallet_staking::ChainAccuracy>())
+			.collect::<Vec<_>>();
+		let mut staked_assignments = assignment_ratio_to_staked(assignments, weight_of);
+		let start = Instant::now();
+		let removed = reduce(&mut staked_assignments);
+		let reduced = elapsed_ms(start);
+
+		log::debug!(
+			target: crate::LOG_TARGET,
+			"run #{}: phragmen {:.2}ms, balancing {:.2}ms ({} iterations), reduce {:.2}ms ({} \
+			 edges removed)",
+			r,
+			phragmen,
+			balancing,
+			balance_iterations,
+			reduced,
+			removed,
+		);
+		phragmen_samples.push(phragmen);
+		balancing_samples.push(balancing);
+		reduce_samples.push(reduced);
+	}
+
+	println!("  {:<30} {}", "seq-phragmen:", Stats::new(&phragmen_samples));
+	println!(
+		"  {:<30} {}",
+		format!("balancing ({} iterations):", iterations),
+		Stats::new(&balancing_samples)
+	);
+	println!("  {:<30} {}", "reduce:", Stats::new(&reduce_samples));
+	Ok(())
+}
@@ -0,0 +1,78 @@
+//! Compare the size of the election snapshot with the bounds that the chain puts on it.
+//!
+//! Once the voters exceed `MaxElectingVoters`, the chain silently drops the voters at the bottom of
+//! the bags-list from the election. This reports how close the network is to that point.
+
+use crate::{error, primitives::Hash, snapshot::Snapshot, storage, Client, Opt, LOG_TARGET};
+use codec::Encode;
+
+const MODULE: &str = "ElectionProviderMultiPhase";
+
+/// The size of the snapshot of the multi-phase election, as stored on chain while it exists.
+#[derive(codec::Decode, Debug, Clone, Copy)]
+struct SnapshotMetadata {
+	#[codec(compact)]
+	voters: u32,
+	#[codec(compact)]
+	targets: u32,
+}
+
+/// Read a bound of the election provider. The bounds are `u32` in some runtimes and `u16` in
+/// others.
+async fn bound(name: &str, client: &Client, at: Hash) -> Option<u32> {
+	match storage::get_const::<u32>(client, MODULE, name, at).await {
+		Some(bound) => Some(bound),
+		None => storage::get_const::<u16>(client, MODULE, name, at).await.map(Into::into),
+	}
+}
+
+/// Print `what` against its `bound`, and warn if it exceeds `warn_at` percent of it.
+fn report(what: &str, value: u32, bound: Option<u32>, warn_at: u8) {
+	match bound {
+		Some(bound) => {
+			let usage = value as f64 * 100.0 / (bound as f64).max(1.0);
+			println!("\t{}: {} / {} ({:.2}%)", what, value, bound, usage);
+			if value > bound {
+				log::warn!(
+					target: LOG_TARGET,
+					"⚠️  {} exceed the bound by {}, the rest are truncated.",
+					what,
+					value - bound
+				);
+			} else if usage >= warn_at as f64 {
+				log::warn!(target: LOG_TARGET, "⚠️  {} are approaching the bound.", what);
+			}
+		}
+		None => println!("\t{}: {} (unbounded)", what, value),
+	}
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, warn_at: u8) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let metadata = storage::read::<SnapshotMetadata>(
+		storage::value_key(MODULE.as_bytes(), b"SnapshotMetadata"),
+		client,
+		at,
+	)
+	.await;
+
+	// the snapshot of the chain only exists during the election, else compute what it would be.
+	let snapshot = Snapshot::fetch(client, at).await?;
+	let (voters, targets) = match metadata {
+		Some(SnapshotMetadata { voters, targets }) => {
+			println!("📸 snapshot of the ongoing election:");
+			(voters, targets)
+		}
+		None => {
+			println!("📸 snapshot that the next election would take:");
+			(snapshot.voters.len() as u32, snapshot.targets.len() as u32)
+		}
+	};
+
+	report("voters", voters, bound("MaxElectingVoters", client, at).await, warn_at);
+	report("targets", targets, bound("MaxElectableTargets", client, at).await, warn_at);
+	let bytes = snapshot.voters.encoded_size() + snapshot.targets.encoded_size();
+	println!("\tencoded size: {} bytes ({:.2} MiB)", bytes, bytes as f64 / 1024.0 / 1024.0);
+	Ok(())
+}
@@ -0,0 +1,121 @@
+//! Report the churn of the nominators between two eras.
+//!
+//! The nominators are not stored per era, hence they are read at the first block of each era,
+//! which requires an archive node.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance, Hash, Header},
+	storage,
+	subcommands::{session_info, staking},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use pallet_staking::{ActiveEraInfo, EraIndex, Nominations};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The nominators at a block, with their active stake and sorted targets.
+type Nominators = BTreeMap<AccountId, (Balance, Vec<AccountId>)>;
+
+/// Find the first block of `era`, searching the blocks up to `at`.
+async fn first_block_of(era: EraIndex, client: &Client, at: Hash) -> Hash {
+	let header = storage::get_header::<Header>(client, at).await.expect("`at` must have a header.");
+	let (mut low, mut high) = (0u32, header.number);
+	while low < high {
+		let mid = low + (high - low) / 2;
+		let hash = storage::get_block_hash(client, mid).await.expect("block must exist.");
+		// blocks before staking existed have no active era.
+		let active_era = storage::read::<ActiveEraInfo>(
+			storage::value_key(b"Staking", b"ActiveEra"),
+			client,
+			hash,
+		)
+		.await
+		.map_or(0, |a| a.index);
+		if active_era < era {
+			low = mid + 1;
+		} else {
+			high = mid;
+		}
+	}
+	storage::get_block_hash(client, low).await.expect("block must exist.")
+}
+
+async fn nominators_at(client: &Client, at: Hash) -> Nominators {
+	let nominators = storage::enumerate_map::<AccountId, Nominations<AccountId>>(
+		b"Staking",
+		b"Nominators",
+		client,
+		at,
+	)
+	.await
+	.expect("Staking::nominators should be enumerable");
+
+	let mut result = Nominators::new();
+	for (who, mut nominations) in nominators {
+		let stake = staking::stake_of(&who, client, at).await;
+		nominations.targets.sort();
+		result.insert(who, (stake, nominations.targets));
+	}
+	result
+}
+
+/// Main run function of the sub-command.
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	from_era: EraIndex,
+	to_era: Option<EraIndex>,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let from = first_block_of(from_era, client, at).await;
+	let (to, to_era) = match to_era {
+		Some(era) => (first_block_of(era, client, at).await, era),
+		None => (at, session_info::get_active_era(client, at).await.index),
+	};
+	log::info!(target: LOG_TARGET, "era {} starts at {:?}, era {} at {:?}", from_era, from, to_era, to);
+
+	let before = nominators_at(client, from).await;
+	let after = nominators_at(client, to).await;
+	let whos = before.keys().chain(after.keys()).collect::<BTreeSet<_>>();
+
+	let (mut joined, mut left, mut retargeted, mut rebonded) = (0, 0, 0, 0);
+	let (mut stake_in, mut stake_out) = (0 as Balance, 0 as Balance);
+	for who in whos {
+		match (before.get(who), after.get(who)) {
+			(None, Some((stake, _))) => {
+				joined += 1;
+				stake_in += stake;
+			}
+			(Some((stake, _)), None) => {
+				left += 1;
+				stake_out += stake;
+			}
+			(Some((stake_before, targets_before)), Some((stake_after, targets_after))) => {
+				if targets_before != targets_after {
+					retargeted += 1;
+				}
+				if stake_before != stake_after {
+					rebonded += 1;
+					stake_in += stake_after.saturating_sub(*stake_before);
+					stake_out += stake_before.saturating_sub(*stake_after);
+				}
+			}
+			(None, None) => unreachable!("all accounts are in at least one of the sets."),
+		}
+	}
+
+	println!("🔄 nominator churn from era {} to era {}", from_era, to_era);
+	println!("\tnominators: {} -> {}", before.len(), after.len());
+	println!("\tjoined: {}", joined);
+	println!("\tleft: {}", left);
+	println!("\tchanged targets: {}", retargeted);
+	println!("\tchanged bond: {}", rebonded);
+	println!("\tstake in: {:?}", Currency::from(stake_in));
+	println!("\tstake out: {:?}", Currency::from(stake_out));
+	if stake_in >= stake_out {
+		println!("\tnet flow: +{:?}", Currency::from(stake_in - stake_out));
+	} else {
+		println!("\tnet flow: -{:?}", Currency::from(stake_out - stake_in));
+	}
+	Ok(())
+}
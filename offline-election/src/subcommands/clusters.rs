@@ -0,0 +1,108 @@
+//! Group the active and predicted validator sets by operator.
+
+use crate::{
+	error,
+	primitives::{account_len, AccountId, Balance, Hash},
+	storage,
+	subcommands::{session_info, staking, suggest},
+	Client, Opt,
+};
+use codec::{Decode, Encode};
+use frame_support::{StorageHasher, Twox64Concat};
+use pallet_staking::{EraIndex, Exposure};
+use std::collections::BTreeMap;
+
+/// A validator set, as a list of validators and their total backing.
+type ValidatorSet = Vec<(AccountId, Balance)>;
+
+/// The clusters of a validator set, from operator to the number of nodes and their total stake.
+type Clusters = BTreeMap<AccountId, (usize, Balance)>;
+
+/// Get the validators elected in `era`, with their full exposure.
+pub(crate) async fn exposures_at(
+	era: EraIndex,
+	client: &Client,
+	at: Hash,
+) -> Vec<(AccountId, Exposure<AccountId, Balance>)> {
+	let mut prefix = storage::module_prefix_raw(b"Staking", b"ErasStakers");
+	prefix.extend(Twox64Concat::hash(era.encode().as_ref()));
+	storage::get_pairs(storage::StorageKey(prefix), client, at)
+		.await
+		.into_iter()
+		.map(|(k, v)| {
+			let key = k.0[k.0.len() - account_len()..].to_vec();
+			let who = AccountId::decode(&mut key.as_slice()).expect("stash must decode");
+			let exposure = <Exposure<AccountId, Balance>>::decode(&mut v.0.as_slice())
+				.expect("exposure must decode");
+			(who, exposure)
+		})
+		.collect::<Vec<_>>()
+}
+
+/// Get the validators elected in `era`, with their total backing.
+pub(crate) async fn elected_at(era: EraIndex, client: &Client, at: Hash) -> ValidatorSet {
+	exposures_at(era, client, at)
+		.await
+		.into_iter()
+		.map(|(who, exposure)| (who, exposure.total))
+		.collect::<Vec<_>>()
+}
+
+/// Group a validator set by operator.
+async fn clusters_of(set: &ValidatorSet, client: &Client, at: Hash) -> Clusters {
+	let mut clusters = Clusters::new();
+	for (v, stake) in set.iter() {
+		let entry = clusters.entry(suggest::operator_of(v, client, at).await).or_default();
+		entry.0 += 1;
+		entry.1 += stake;
+	}
+	clusters
+}
+
+/// Print the clusters of a set with more than one node, largest stake share first.
+async fn print_clusters(name: &str, clusters: &Clusters, client: &Client, at: Hash) {
+	let total = clusters.values().map(|(_, s)| s).sum::<Balance>().max(1);
+	let nodes = clusters.values().map(|(n, _)| n).sum::<usize>();
+	let mut sorted = clusters.iter().filter(|(_, (n, _))| *n > 1).collect::<Vec<_>>();
+	sorted.sort_by_key(|(_, (_, s))| std::cmp::Reverse(*s));
+
+	println!("🏭 {}: {} nodes / {} operators", name, nodes, clusters.len());
+	for (operator, (n, stake)) in sorted {
+		println!(
+			"\t[{}] {:?}: {} nodes / {:.2}% of the stake",
+			storage::helpers::get_identity::<AccountId, Balance>(operator.as_ref(), client, at)
+				.await,
+			operator,
+			n,
+			*stake as f64 * 100f64 / total as f64,
+		);
+	}
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let active_era = session_info::get_active_era(client, at).await.index;
+
+	let previous = elected_at(active_era.saturating_sub(1), client, at).await;
+	let active = elected_at(active_era, client, at).await;
+	let (_, supports) = staking::predict(client, at).await?;
+	let predicted = supports.iter().map(|(v, s)| (v.clone(), s.total)).collect::<ValidatorSet>();
+
+	let previous = clusters_of(&previous, client, at).await;
+	let active = clusters_of(&active, client, at).await;
+	let predicted = clusters_of(&predicted, client, at).await;
+	print_clusters(&format!("era {}", active_era.saturating_sub(1)), &previous, client, at).await;
+	print_clusters(&format!("era {} (active)", active_era), &active, client, at).await;
+	print_clusters("predicted", &predicted, client, at).await;
+
+	println!("🔀 Changes from the active to the predicted set:");
+	let nodes = |c: &Clusters, o: &AccountId| c.get(o).map(|(n, _)| *n).unwrap_or_default();
+	for operator in active.keys().chain(predicted.keys().filter(|o| !active.contains_key(o))) {
+		let (before, after) = (nodes(&active, operator), nodes(&predicted, operator));
+		if before != after && before.max(after) > 1 {
+			println!("\t{:?}: {} => {} nodes", operator, before, after);
+		}
+	}
+	Ok(())
+}
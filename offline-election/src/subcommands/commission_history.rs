@@ -0,0 +1,135 @@
+//! Report the commission history of validators over the eras kept in storage.
+
+use crate::{
+	error,
+	primitives::{account_len, AccountId, Hash},
+	storage, subcommands, Client, Opt, LOG_TARGET,
+};
+use codec::{Decode, Encode};
+use frame_support::{StorageHasher, Twox64Concat};
+use pallet_staking::EraIndex;
+use sp_runtime::Perbill;
+use std::collections::BTreeMap;
+
+/// The commission of a validator in each era in which it has been elected.
+pub(crate) type Trajectory = Vec<(EraIndex, Perbill)>;
+
+/// Get the number of eras of history kept in storage.
+pub(crate) async fn history_depth(client: &Client, at: Hash) -> u32 {
+	storage::read::<u32>(storage::value_key(b"Staking", b"HistoryDepth"), client, at)
+		.await
+		.unwrap_or(84)
+}
+
+/// Get the commission of all the validators elected in `era`.
+async fn commissions_at(era: EraIndex, client: &Client, at: Hash) -> Vec<(AccountId, Perbill)> {
+	let mut prefix = storage::module_prefix_raw(b"Staking", b"ErasValidatorPrefs");
+	prefix.extend(Twox64Concat::hash(era.encode().as_ref()));
+	storage::get_pairs(storage::StorageKey(prefix), client, at)
+		.await
+		.into_iter()
+		.map(|(k, v)| {
+			let key = k.0[k.0.len() - account_len()..].to_vec();
+			let who = AccountId::decode(&mut key.as_slice()).expect("stash must decode");
+			let prefs = subcommands::staking::OldValidatorPrefs::decode(&mut v.0.as_slice())
+				.expect("prefs must decode");
+			(who, prefs.commission)
+		})
+		.collect::<Vec<_>>()
+}
+
+/// Get the commission of `who` in `era`, if it was elected.
+pub(crate) async fn commission_at(
+	who: &AccountId,
+	era: EraIndex,
+	client: &Client,
+	at: Hash,
+) -> Option<Perbill> {
+	storage::read::<subcommands::staking::OldValidatorPrefs>(
+		storage::double_map_key::<Twox64Concat, Twox64Concat>(
+			b"Staking",
+			b"ErasValidatorPrefs",
+			era.encode().as_ref(),
+			who.as_ref(),
+		),
+		client,
+		at,
+	)
+	.await
+	.map(|p| p.commission)
+}
+
+/// The eras in which the commission was raised, only to be lowered again in the next era.
+///
+/// These are the eras in which the rewards of the nominators have been claimed with a higher
+/// commission.
+pub(crate) fn spikes(trajectory: &Trajectory) -> Vec<EraIndex> {
+	trajectory
+		.windows(3)
+		.filter(|w| w[1].1 > w[0].1 && w[1].1 > w[2].1)
+		.map(|w| w[1].0)
+		.collect::<Vec<_>>()
+}
+
+/// Print the trajectory, only showing the eras in which the commission changed.
+fn print_trajectory(who: &AccountId, trajectory: &Trajectory) {
+	let spikes = spikes(trajectory);
+	println!("{} {:?}", if spikes.is_empty() { "✅" } else { "⚠️ " }, who);
+	let mut last: Option<Perbill> = None;
+	for (era, commission) in trajectory.iter() {
+		if last != Some(*commission) {
+			println!("\tera {}: {:?}", era, commission);
+			last = Some(*commission);
+		}
+	}
+	if !spikes.is_empty() {
+		println!("\tcommission spiked in eras {:?}", spikes);
+	}
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, who: Option<AccountId>) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let current_era = subcommands::staking::get_current_era(client, at).await;
+	let first_era = current_era.saturating_sub(history_depth(client, at).await);
+	log::info!(target: LOG_TARGET, "scanning eras {}..={}", first_era, current_era);
+
+	if let Some(who) = who {
+		let mut trajectory = Trajectory::new();
+		for era in first_era..=current_era {
+			if let Some(commission) = commission_at(&who, era, client, at).await {
+				trajectory.push((era, commission));
+			}
+		}
+		if trajectory.is_empty() {
+			log::warn!(
+				target: LOG_TARGET,
+				"{:?} has not been elected in the last {} eras.",
+				who,
+				current_era - first_era
+			);
+			return Ok(());
+		}
+		print_trajectory(&who, &trajectory);
+	} else {
+		let mut trajectories: BTreeMap<AccountId, Trajectory> = BTreeMap::new();
+		for era in first_era..=current_era {
+			for (who, commission) in commissions_at(era, client, at).await {
+				trajectories.entry(who).or_default().push((era, commission));
+			}
+		}
+
+		let changed = trajectories
+			.iter()
+			.filter(|(_, t)| t.windows(2).any(|w| w[0].1 != w[1].1))
+			.collect::<Vec<_>>();
+		changed.iter().for_each(|(who, t)| print_trajectory(who, t));
+		println!(
+			"📊 {} validators / {} changed their commission / {} with spikes.",
+			trajectories.len(),
+			changed.len(),
+			changed.iter().filter(|(_, t)| !spikes(t).is_empty()).count(),
+		);
+	}
+	Ok(())
+}
@@ -0,0 +1,58 @@
+//! Report on the stash to controller mapping of the `Bonded` map.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance},
+	storage, Client, Opt, LOG_TARGET,
+};
+use pallet_staking::StakingLedger;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let bonded = storage::enumerate_map::<AccountId, AccountId>(b"Staking", b"Bonded", client, at)
+		.await
+		.expect("Staking::bonded should be enumerable.")
+		.into_iter()
+		.collect::<BTreeMap<AccountId, AccountId>>();
+	let ledgers = storage::enumerate_map::<AccountId, StakingLedger<AccountId, Balance>>(
+		b"Staking", b"Ledger", client, at,
+	)
+	.await
+	.expect("Staking::ledger should be enumerable.")
+	.into_iter()
+	.collect::<BTreeMap<AccountId, StakingLedger<AccountId, Balance>>>();
+
+	log::info!(target: LOG_TARGET, "{} bonded stashes, {} ledgers.", bonded.len(), ledgers.len());
+
+	let distinct = bonded.iter().filter(|(s, c)| s != c).collect::<Vec<_>>();
+	let controllers = bonded.values().cloned().collect::<BTreeSet<AccountId>>();
+
+	println!("🔀 {} stashes with a controller different from the stash:", distinct.len());
+	for (stash, ctrl) in distinct.iter() {
+		println!("\tstash {:?} || ctrl = {:?}", stash, ctrl);
+	}
+
+	let idle = bonded
+		.iter()
+		.filter(|(_, c)| !ledgers.get(*c).map_or(false, |l| bonded.get(&l.stash) == Some(*c)))
+		.collect::<Vec<_>>();
+	println!("👻 {} controllers controlling nothing:", idle.len());
+	for (stash, ctrl) in idle.iter() {
+		println!("\tctrl {:?} (bonded by {:?}) has no matching ledger", ctrl, stash);
+	}
+	for (ctrl, ledger) in ledgers.iter().filter(|(c, _)| !controllers.contains(*c)) {
+		println!("\tctrl {:?} has a ledger of {:?} but is not bonded", ctrl, ledger.stash);
+	}
+
+	// setting the controller of every stash to itself collides with a stash that is currently
+	// the controller of another one.
+	let colliding = distinct.iter().filter(|(s, _)| controllers.contains(*s)).collect::<Vec<_>>();
+	println!("⚠️  {} stashes affected by a controller deprecation migration:", distinct.len());
+	println!("\tof which {} are also the controller of another stash:", colliding.len());
+	for (stash, ctrl) in colliding.iter() {
+		println!("\tstash {:?} || ctrl = {:?}", stash, ctrl);
+	}
+	Ok(())
+}
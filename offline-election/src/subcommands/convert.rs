@@ -0,0 +1,67 @@
+//! Upgrade files produced by older versions of this tool to the current formats.
+
+use crate::{
+	error::{self, Error},
+	snapshot::Snapshot,
+	subcommands::staking::OUTPUT_SCHEMA_VERSION,
+};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Upgrade an election output to the latest schema version.
+///
+/// The oldest dumps listed the winners as `[account, approval_stake]` pairs; these are reduced to
+/// the account alone.
+fn convert_output(mut value: Value) -> Value {
+	let version = value.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(0);
+	log::info!(target: crate::LOG_TARGET, "converting output from schema version {}", version);
+	if let Some(winners) = value.get_mut("winners").and_then(|w| w.as_array_mut()) {
+		for w in winners.iter_mut() {
+			if let Some(who) = w.as_array().and_then(|pair| pair.first()).cloned() {
+				*w = who;
+			}
+		}
+	}
+	value["schema_version"] = OUTPUT_SCHEMA_VERSION.into();
+	value
+}
+
+/// Upgrade a snapshot, filling the fields that older snapshots did not have.
+fn convert_snapshot(mut value: Value, desired_targets: Option<u32>) -> Result<Snapshot, String> {
+	if value.get("at").is_none() {
+		value["at"] = Value::Null;
+	}
+	if value.get("desired_targets").is_none() {
+		let desired_targets = desired_targets
+			.ok_or("the snapshot has no desired targets, `--desired-targets` must be provided.")?;
+		value["desired_targets"] = desired_targets.into();
+	}
+	serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Main run function of the sub-command.
+///
+/// Note that this sub-command does not need a connection to any node.
+pub fn run(input: PathBuf, output: PathBuf, desired_targets: Option<u32>) -> error::Result<()> {
+	let file = std::fs::File::open(&input).map_err(|e| Error::file(&input, e))?;
+	let value: Value = serde_json::from_reader(std::io::BufReader::new(file))
+		.map_err(|e| Error::file(&input, e))?;
+
+	if value.get("voters").is_some() && value.get("targets").is_some() {
+		let snapshot =
+			convert_snapshot(value, desired_targets).map_err(|e| Error::file(&input, e))?;
+		snapshot.save(&output)?;
+		println!("📝 Snapshot converted into {:?}", output);
+	} else if value.get("supports").is_some() {
+		let converted = convert_output(value);
+		let file = std::fs::File::create(&output).map_err(|e| Error::file(&output, e))?;
+		serde_json::to_writer_pretty(file, &converted).map_err(|e| Error::file(&output, e))?;
+		println!(
+			"📝 Output converted to schema version {} into {:?}",
+			OUTPUT_SCHEMA_VERSION, output
+		);
+	} else {
+		return Err(Error::file(&input, "neither a snapshot nor an election output"));
+	}
+	Ok(())
+}
@@ -0,0 +1,122 @@
+//! Compare the storage of multiple nodes at the same block.
+
+use crate::{error, primitives::blake2_256, storage, Client, Opt, LOG_TARGET};
+use sub_storage::{StorageData, StorageKey};
+
+/// The storage values that are compared.
+const VALUES: &[(&[u8], &[u8])] = &[
+	(b"Balances", b"TotalIssuance"),
+	(b"Session", b"CurrentIndex"),
+	(b"Session", b"Validators"),
+	(b"Staking", b"ActiveEra"),
+	(b"Staking", b"CurrentEra"),
+	(b"Staking", b"ValidatorCount"),
+];
+
+/// The storage maps that are compared in deep mode.
+const MAPS: &[(&[u8], &[u8])] = &[
+	(b"Staking", b"Bonded"),
+	(b"Staking", b"Ledger"),
+	(b"Staking", b"Nominators"),
+	(b"Staking", b"Validators"),
+];
+
+fn name(module: &[u8], item: &[u8]) -> String {
+	format!("{}::{}", String::from_utf8_lossy(module), String::from_utf8_lossy(item))
+}
+
+/// A fingerprint of an entire map, and its number of keys.
+async fn map_fingerprint(
+	prefix: StorageKey,
+	client: &Client,
+	at: sub_storage::Hash,
+) -> ([u8; 32], usize) {
+	let mut pairs = storage::get_pairs(prefix, client, at).await;
+	pairs.sort_by(|(k1, _), (k2, _)| k1.0.cmp(&k2.0));
+	let count = pairs.len();
+	let mut concat = vec![];
+	for (k, v) in pairs {
+		concat.extend(k.0);
+		concat.extend(v.0);
+	}
+	(blake2_256(&concat), count)
+}
+
+/// Main run function of the sub-command.
+///
+/// All `others` nodes are compared against the main node of `opt.uri`, at the same block.
+pub async fn run(client: &Client, opt: Opt, others: Vec<String>, deep: bool) -> error::Result<()> {
+	let at = opt.at.unwrap();
+
+	let mut reference_values: Vec<Option<StorageData>> = vec![];
+	for (module, item) in VALUES {
+		reference_values
+			.push(storage::read_raw(storage::value_key(module, item), client, at).await);
+	}
+	let mut reference_maps = vec![];
+	if deep {
+		for (module, item) in MAPS {
+			reference_maps
+				.push(map_fingerprint(storage::map_prefix_key(module, item), client, at).await);
+		}
+	}
+
+	let mut mismatches = 0;
+	for uri in others.iter() {
+		println!("🔗 comparing {} against {} at {:?}", uri, opt.uri, at);
+		let other = match crate::connect(uri, opt.rpc_timeout).await {
+			Ok(other) => other,
+			Err(e) => {
+				println!("\t❌ {}", e);
+				mismatches += 1;
+				continue;
+			}
+		};
+
+		if storage::get_header::<serde_json::Value>(&other, at).await.is_none() {
+			println!("\t❌ block {:?} is not known, the node might be lagging or on a fork.", at);
+			mismatches += 1;
+			continue;
+		}
+
+		for ((module, item), reference) in VALUES.iter().zip(reference_values.iter()) {
+			let value = storage::read_raw(storage::value_key(module, item), &other, at).await;
+			if &value == reference {
+				log::debug!(target: LOG_TARGET, "{} matches", name(module, item));
+			} else {
+				mismatches += 1;
+				println!(
+					"\t❌ {} mismatch: {:?} vs {:?}",
+					name(module, item),
+					value.map(|v| hex::encode(v.0)),
+					reference.as_ref().map(|v| hex::encode(&v.0)),
+				);
+			}
+		}
+
+		for ((module, item), (hash, count)) in MAPS.iter().zip(reference_maps.iter()) {
+			let (other_hash, other_count) =
+				map_fingerprint(storage::map_prefix_key(module, item), &other, at).await;
+			if &other_hash == hash {
+				log::debug!(target: LOG_TARGET, "{} matches ({} keys)", name(module, item), count);
+			} else {
+				mismatches += 1;
+				println!(
+					"\t❌ {} mismatch: {} keys vs {} keys, fingerprint {} vs {}",
+					name(module, item),
+					other_count,
+					count,
+					hex::encode(other_hash),
+					hex::encode(hash),
+				);
+			}
+		}
+	}
+
+	if mismatches == 0 {
+		println!("✅ all {} nodes are consistent with {}.", others.len(), opt.uri);
+	} else {
+		println!("❌ found {} mismatches. Do not trust the inconsistent nodes.", mismatches);
+	}
+	Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::{
+	error,
 	primitives::{AccountId, Balance},
 	Client, Currency, Opt, LOG_TARGET,
 };
@@ -6,7 +7,7 @@ use sp_runtime::traits::Bounded;
 use sub_storage::helpers::*;
 
 /// Main run function of the sub-command.
-pub async fn run(client: &Client, config: Opt) {
+pub async fn run(client: &Client, config: Opt) -> error::Result<()> {
 	let at = config.at.unwrap();
 	let (era, validators_and_expo) = crate::network::get_validators_and_expo_at(&client, at).await;
 	log::info!(target: LOG_TARGET, "working on era {:?}", era);
@@ -29,4 +30,5 @@ pub async fn run(client: &Client, config: Opt) {
 	}
 
 	log::info!(target: LOG_TARGET, "min-staker (score[0]) is {:?}", Currency::from(min_stake));
+	Ok(())
 }
@@ -1,10 +1,51 @@
 use crate::{
+	error,
 	primitives::{AccountId, Hash},
 	storage,
 	subcommands::staking::slashing_span_of,
 	Client, Opt, LOG_TARGET,
 };
-use pallet_staking::Nominations;
+use codec::Decode;
+use pallet_staking::{EraIndex, Nominations, UnappliedSlash};
+use std::collections::BTreeMap;
+
+/// The status of the slash that made a vote dangling.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum SlashStatus {
+	/// The slash has been applied, or cancelled by governance. Either way, the vote is dangling
+	/// and must be re-submitted.
+	Applied,
+	/// The slash is deferred and will be applied at the given era, unless cancelled by
+	/// governance before then.
+	Pending(EraIndex),
+}
+
+/// Get all the validators with a deferred slash, and the era at which the slash is applied.
+pub(crate) async fn unapplied_slashes(
+	client: &Client,
+	at: Hash,
+) -> error::Result<BTreeMap<AccountId, EraIndex>> {
+	type Slashes = Vec<UnappliedSlash<AccountId, crate::primitives::Balance>>;
+	let prefix = storage::map_prefix_key(b"Staking", b"UnappliedSlashes");
+	let mut pending = BTreeMap::new();
+	for (k, v) in storage::get_pairs(prefix, client, at).await {
+		let decode_error = |ty, e: codec::Error| error::Error::Decode {
+			key: k.clone(),
+			at,
+			ty,
+			reason: e.to_string(),
+		};
+		let era_bytes = k.0[k.0.len() - 4..].to_vec();
+		let era =
+			EraIndex::decode(&mut era_bytes.as_slice()).map_err(|e| decode_error("EraIndex", e))?;
+		let slashes = Slashes::decode(&mut v.0.as_slice())
+			.map_err(|e| decode_error(std::any::type_name::<Slashes>(), e))?;
+		for slash in slashes {
+			pending.insert(slash.validator, era);
+		}
+	}
+	Ok(pending)
+}
 
 /// Check if a vote submitted at the given era for this target is dangling or not.
 pub async fn is_dangling(
@@ -21,7 +62,7 @@ pub async fn is_dangling(
 }
 
 /// Main run function of the sub-command.
-pub async fn run(client: &Client, opt: Opt) {
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
 	let at = opt.at.unwrap();
 	let nominators: Vec<(AccountId, Nominations<AccountId>)> =
 		storage::enumerate_map::<AccountId, Nominations<AccountId>>(
@@ -31,22 +72,38 @@ pub async fn run(client: &Client, opt: Opt) {
 			at,
 		)
 		.await
-		.expect("Staking::nominators should be enumerable");
+		.map_err(|e| error::Error::Decode {
+			key: storage::map_prefix_key(b"Staking", b"Nominators"),
+			at,
+			ty: "Nominations",
+			reason: e.into(),
+		})?;
+
+	let pending_slashes = unapplied_slashes(client, at).await?;
+	log::info!(target: LOG_TARGET, "{} validators have pending slashes.", pending_slashes.len());
 
 	let count = nominators.len();
 	let mut ok = 0;
 	let mut nok = 0;
+	let mut pending = 0;
 	for (idx, (who, n)) in nominators.into_iter().enumerate() {
 		// retain only targets who have not been yet slashed recently. This is highly dependent
 		// on the staking implementation.
 		let submitted_in = n.submitted_in;
 		let targets = n.targets;
 		let mut filtered_targets = vec![];
+		let mut statuses = vec![];
 		// TODO: move back to closures and retain, but async-std::block_on can't work well here for
 		// whatever reason. Or move to streams?
 		for target in targets.iter() {
 			if !is_dangling(target, submitted_in, client, at).await {
 				filtered_targets.push(target.clone());
+			} else {
+				statuses.push(
+					pending_slashes
+						.get(target)
+						.map_or(SlashStatus::Applied, |era| SlashStatus::Pending(*era)),
+				);
 			}
 		}
 
@@ -60,6 +117,18 @@ pub async fn run(client: &Client, opt: Opt) {
 				targets.len()
 			);
 			ok += 1;
+		} else if statuses.iter().all(|s| matches!(s, SlashStatus::Pending(_))) {
+			log::warn!(
+				target: LOG_TARGET,
+				"[{}/{}] {:?} has {} votes for validators with pending slashes {:?}. These may be \
+				cancelled, no action needed yet.",
+				idx,
+				count,
+				who,
+				statuses.len(),
+				statuses,
+			);
+			pending += 1;
 		} else {
 			log::warn!(
 				target: LOG_TARGET,
@@ -75,5 +144,11 @@ pub async fn run(client: &Client, opt: Opt) {
 	}
 
 	log::info!(target: LOG_TARGET, "✅ {} nominators have effective votes.", ok);
+	log::info!(
+		target: LOG_TARGET,
+		"⏳ {} nominators have votes dangling on pending slashes only.",
+		pending
+	);
 	log::info!(target: LOG_TARGET, "❌ {} nominators have dangling votes.", nok);
+	Ok(())
 }
@@ -1,47 +1,124 @@
 use crate::{
-	network,
-	primitives::{AccountId, Balance, Hash},
+	error, network,
+	primitives::{AccountId, Balance, Hash, Header},
 	storage, Client, CouncilConfig, Currency, Opt, LOG_TARGET,
 };
 use sp_npos_elections::*;
 use sp_runtime::traits::{Convert, Zero};
-use std::{collections::BTreeMap};
+use std::{collections::BTreeMap, str::FromStr};
 
 const MODULE: &[u8] = b"PhragmenElection";
 
-async fn get_candidates(client: &Client, at: Hash) -> Vec<AccountId> {
-	let mut members = storage::read::<Vec<(AccountId, Balance, Balance)>>(
-		storage::value_key(MODULE, b"Members"),
-		client,
-		at,
-	)
-	.await
-	.expect("Members must exist")
-	.into_iter()
-	.map(|(m, _, _)| m)
-	.collect::<Vec<AccountId>>();
-
-	let runners = storage::read::<Vec<(AccountId, Balance, Balance)>>(
-		storage::value_key(MODULE, b"RunnersUp"),
-		client,
-		at,
-	)
-	.await
-	.expect("Runners-up must exists")
-	.into_iter()
-	.map(|(m, _, _)| m)
-	.collect::<Vec<AccountId>>();
-
-	let candidates = storage::read::<Vec<(AccountId, Balance)>>(
-		storage::value_key(MODULE, b"Candidates"),
-		client,
-		at,
-	)
-	.await
-	.unwrap_or_default()
-	.into_iter()
-	.map(|(c, _)| c)
-	.collect::<Vec<_>>();
+/// The shape of the storage items of the elections-phragmen pallet, which changed across runtime
+/// versions.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum StorageVersion {
+	/// Members and runners-up as `(who, stake)`, voting as `(stake, votes)` and candidates as
+	/// plain accounts.
+	V1,
+	/// Members and runners-up as `SeatHolder { who, stake, deposit }`, voting as
+	/// `Voter { votes, stake, deposit }` and candidates as `(who, deposit)`.
+	V2,
+}
+
+/// The version of the metadata that the types of storage items can be read from.
+const METADATA_VERSION: u8 = 12;
+
+/// Detect the storage version of the pallet at `at` from the metadata.
+///
+/// Only metadata V12 is decoded. Runtimes with an older metadata predate the V2 layout, and those
+/// with a newer one postdate it.
+pub(crate) async fn storage_version(client: &Client, at: Hash) -> error::Result<StorageVersion> {
+	// the metadata is prefixed by the magic number `meta`, then its version.
+	let metadata = storage::get_metadata(client, at).await.0;
+	match metadata.get(4).cloned() {
+		Some(version) if version < METADATA_VERSION => return Ok(StorageVersion::V1),
+		Some(version) if version > METADATA_VERSION => return Ok(StorageVersion::V2),
+		Some(_) => {}
+		None => return Err(error::Error::Missing("the metadata version".into())),
+	}
+
+	let ty =
+		storage::get_storage_type(client, "ElectionsPhragmen", "Members", at).await.ok_or_else(
+			|| error::Error::Missing("ElectionsPhragmen::Members in the metadata".into()),
+		)?;
+	if ty.contains("SeatHolder") || ty.matches("BalanceOf").count() >= 2 {
+		Ok(StorageVersion::V2)
+	} else {
+		Ok(StorageVersion::V1)
+	}
+}
+
+/// A member or runner-up, with the stake backing it and its deposit.
+#[derive(Debug, Clone)]
+pub(crate) struct SeatHolder {
+	pub who: AccountId,
+	pub stake: Balance,
+	pub deposit: Balance,
+}
+
+async fn get_seat_holders(
+	item: &[u8],
+	version: StorageVersion,
+	client: &Client,
+	at: Hash,
+) -> Option<Vec<SeatHolder>> {
+	let key = storage::value_key(MODULE, item);
+	match version {
+		StorageVersion::V1 => {
+			storage::read::<Vec<(AccountId, Balance)>>(key, client, at).await.map(|v| {
+				v.into_iter().map(|(who, stake)| SeatHolder { who, stake, deposit: 0 }).collect()
+			})
+		}
+		StorageVersion::V2 => {
+			storage::read::<Vec<(AccountId, Balance, Balance)>>(key, client, at).await.map(|v| {
+				v.into_iter()
+					.map(|(who, stake, deposit)| SeatHolder { who, stake, deposit })
+					.collect()
+			})
+		}
+	}
+}
+
+/// Get the current members, in the order stored on chain.
+pub(crate) async fn get_members(
+	version: StorageVersion,
+	client: &Client,
+	at: Hash,
+) -> Vec<SeatHolder> {
+	get_seat_holders(b"Members", version, client, at).await.expect("Members must exist")
+}
+
+/// Get the current runners-up, in the order stored on chain.
+pub(crate) async fn get_runners_up(
+	version: StorageVersion,
+	client: &Client,
+	at: Hash,
+) -> Vec<SeatHolder> {
+	get_seat_holders(b"RunnersUp", version, client, at).await.expect("Runners-up must exists")
+}
+
+async fn get_new_candidates(version: StorageVersion, client: &Client, at: Hash) -> Vec<AccountId> {
+	let key = storage::value_key(MODULE, b"Candidates");
+	match version {
+		StorageVersion::V1 => {
+			storage::read::<Vec<AccountId>>(key, client, at).await.unwrap_or_default()
+		}
+		StorageVersion::V2 => storage::read::<Vec<(AccountId, Balance)>>(key, client, at)
+			.await
+			.unwrap_or_default()
+			.into_iter()
+			.map(|(c, _)| c)
+			.collect::<Vec<_>>(),
+	}
+}
+
+async fn get_candidates(version: StorageVersion, client: &Client, at: Hash) -> Vec<AccountId> {
+	let mut members =
+		get_members(version, client, at).await.into_iter().map(|m| m.who).collect::<Vec<_>>();
+	let runners =
+		get_runners_up(version, client, at).await.into_iter().map(|m| m.who).collect::<Vec<_>>();
+	let candidates = get_new_candidates(version, client, at).await;
 
 	log::trace!(
 		target: LOG_TARGET,
@@ -57,22 +134,463 @@ async fn get_candidates(client: &Client, at: Hash) -> Vec<AccountId> {
 	members
 }
 
-async fn get_voters_and_budget(
+pub(crate) async fn get_voters_and_budget(
+	version: StorageVersion,
 	client: &Client,
 	at: Hash,
 ) -> Vec<(AccountId, Balance, Vec<AccountId>)> {
-	storage::enumerate_map::<AccountId, (Vec<AccountId>, Balance, Balance)>(
-		MODULE, b"Voting", client, at,
-	)
-	.await
-	.unwrap()
-	.into_iter()
-	.map(|(n, (t, b, _))| (n, b, t))
-	.collect::<Vec<_>>()
+	match version {
+		StorageVersion::V1 => storage::enumerate_map::<AccountId, (Balance, Vec<AccountId>)>(
+			MODULE, b"Voting", client, at,
+		)
+		.await
+		.unwrap()
+		.into_iter()
+		.map(|(n, (b, t))| (n, b, t))
+		.collect::<Vec<_>>(),
+		StorageVersion::V2 => {
+			storage::enumerate_map::<AccountId, (Vec<AccountId>, Balance, Balance)>(
+				MODULE, b"Voting", client, at,
+			)
+			.await
+			.unwrap()
+			.into_iter()
+			.map(|(n, (t, b, _))| (n, b, t))
+			.collect::<Vec<_>>()
+		}
+	}
+}
+
+/// Get the prime member of the council, if any.
+async fn get_prime(client: &Client, at: Hash) -> Option<AccountId> {
+	storage::read::<AccountId>(storage::value_key(b"Council", b"Prime"), client, at).await
+}
+
+/// Simulate the removal of the given members, in order, as the pallet does when a member loses
+/// its bond or is removed by governance mid-term.
+///
+/// Each removed member is replaced by the best runner-up, which is the last one stored. Any change
+/// of the members kills the prime of the collective, hence there is no prime after the first
+/// removal until the next election.
+async fn simulate_removals(
+	removed: &[AccountId],
+	version: StorageVersion,
+	client: &Client,
+	at: Hash,
+) {
+	let mut members = get_members(version, client, at).await;
+	let mut runners_up = get_runners_up(version, client, at).await;
+	let mut prime = get_prime(client, at).await;
+
+	for who in removed {
+		let ident =
+			storage::helpers::get_identity::<AccountId, Balance>(who.as_ref(), client, at).await;
+		let index = match members.iter().position(|m| &m.who == who) {
+			Some(index) => index,
+			None => {
+				log::warn!(target: LOG_TARGET, "{:?} ({}) is not a member, skipping.", who, ident);
+				continue;
+			}
+		};
+		let outgoing = members.remove(index);
+		println!(
+			"➖ {:?} ({}) removed, losing a deposit of {:?}",
+			who,
+			ident,
+			Currency::from(outgoing.deposit)
+		);
+
+		match runners_up.pop() {
+			Some(replacement) => {
+				println!(
+					"\t➕ replaced by runner-up {:?} ({}) with a backing of {:?}",
+					replacement.who,
+					storage::helpers::get_identity::<AccountId, Balance>(
+						replacement.who.as_ref(),
+						client,
+						at
+					)
+					.await,
+					Currency::from(replacement.stake)
+				);
+				members.push(replacement);
+				members.sort_by(|a, b| a.who.cmp(&b.who));
+			}
+			None => println!("\t⚠️  no runner-up left, the seat stays empty."),
+		}
+
+		if prime.take().is_some() {
+			println!("\t👑 the prime is cleared, there is no prime until the next election.");
+		}
+	}
+
+	println!("👨🏻‍⚖️ Members after the removals:");
+	for (i, m) in members.iter().enumerate() {
+		println!(
+			"#{} --> {} [{:?}]{}",
+			i + 1,
+			storage::helpers::get_identity::<AccountId, Balance>(m.who.as_ref(), client, at).await,
+			m.who,
+			if prime.as_ref() == Some(&m.who) { " 👑" } else { "" },
+		);
+	}
+	println!("🏃 {} runners-up left.", runners_up.len());
+}
+
+/// An inclusive range of seat counts to sweep over, in the form of `<from>..<to>`.
+#[derive(Debug, Clone, Copy)]
+pub struct SeatRange {
+	from: u32,
+	to: u32,
+}
+
+impl FromStr for SeatRange {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(2, "..");
+		let from = parts.next().and_then(|f| f.parse::<u32>().ok());
+		let to = parts.next().and_then(|t| t.parse::<u32>().ok());
+		match (from, to) {
+			(Some(from), Some(to)) if from > 0 && from <= to => Ok(Self { from, to }),
+			_ => Err("Invalid seat range. Must be <from>..<to>, with 0 < from <= to."),
+		}
+	}
+}
+
+/// Run the election once for each `desired_members` in `range`, and report how the membership
+/// and the share of the voting stake that backs members change.
+async fn sweep(
+	range: SeatRange,
+	desired_runners_up: u32,
+	candidates: Vec<AccountId>,
+	all_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	client: &Client,
+	at: Hash,
+) {
+	let total_stake =
+		all_voters.iter().map(|(_, b, _)| *b as ExtendedBalance).sum::<ExtendedBalance>();
+	let voter_weight =
+		all_voters.iter().map(|(v, b, _)| (v.clone(), *b)).collect::<BTreeMap<_, _>>();
+	let weight_of = |who: &AccountId| -> VoteWeight { *voter_weight.get(who).unwrap() };
+
+	let mut previous: Vec<AccountId> = vec![];
+	for desired_members in range.from..=range.to {
+		let ElectionResult { winners, assignments } =
+			seq_phragmen::<AccountId, pallet_staking::ChainAccuracy>(
+				(desired_members + desired_runners_up) as usize,
+				candidates.clone(),
+				all_voters.clone(),
+				None,
+			)
+			.expect("Phragmen failed to elect.");
+
+		let elected = winners.iter().map(|(s, _)| s.clone()).collect::<Vec<AccountId>>();
+		let staked_assignments = assignment_ratio_to_staked(assignments, weight_of);
+		let supports =
+			to_support_map::<AccountId>(&elected, staked_assignments.as_slice()).unwrap();
+
+		let members = elected.into_iter().take(desired_members as usize).collect::<Vec<_>>();
+		let backings = members.iter().map(|m| supports.get(m).unwrap().total).collect::<Vec<_>>();
+		let backing_members = backings.iter().sum::<ExtendedBalance>();
+		let efficiency = backing_members as f64 / total_stake.max(1) as f64 * 100.0;
+
+		println!(
+			"🪑 {} seats: {} members / min backing {:?} / stake backing members {:?} ({:.2}%)",
+			desired_members,
+			members.len(),
+			Currency::from(backings.iter().min().copied().unwrap_or_default() as Balance),
+			Currency::from(backing_members as Balance),
+			efficiency,
+		);
+		for m in members.iter().filter(|m| !previous.contains(m)) {
+			println!(
+				"\t➕ {:?} ({})",
+				m,
+				storage::helpers::get_identity::<AccountId, Balance>(m.as_ref(), client, at).await,
+			);
+		}
+		for m in previous.iter().filter(|m| !members.contains(m)) {
+			println!(
+				"\t➖ {:?} ({})",
+				m,
+				storage::helpers::get_identity::<AccountId, Balance>(m.as_ref(), client, at).await,
+			);
+		}
+		previous = members;
+	}
+}
+
+/// A prospective vote, in the form of `<account>:<stake>:<candidate>,<candidate>,..`.
+///
+/// The stake is in the smallest unit of the currency. A vote of an existing voter replaces its
+/// current vote.
+#[derive(Debug, Clone)]
+pub struct Vote {
+	who: AccountId,
+	stake: Balance,
+	votes: Vec<AccountId>,
+}
+
+impl FromStr for Vote {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		const ERR: &str = "Invalid vote. Must be <account>:<stake>:<candidate>,<candidate>,..";
+		let mut parts = s.splitn(3, ':');
+		let who = parts.next().and_then(|w| AccountId::from_str(w).ok()).ok_or(ERR)?;
+		let stake = parts.next().and_then(|s| s.parse::<Balance>().ok()).ok_or(ERR)?;
+		let votes = parts
+			.next()
+			.ok_or(ERR)?
+			.split(',')
+			.map(|c| AccountId::from_str(c.trim()).map_err(|_| ERR))
+			.collect::<Result<Vec<_>, _>>()?;
+		if votes.is_empty() || votes.len() > 16 {
+			return Err(ERR);
+		}
+		Ok(Self { who, stake, votes })
+	}
+}
+
+/// Elect `count` seats, and split them into members and runners-up.
+fn seats(
+	count: usize,
+	desired_members: u32,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> (Vec<AccountId>, Vec<AccountId>) {
+	let ElectionResult { winners, .. } =
+		seq_phragmen::<AccountId, pallet_staking::ChainAccuracy>(count, candidates, voters, None)
+			.expect("Phragmen failed to elect.");
+	let mut members = winners.into_iter().map(|(w, _)| w).collect::<Vec<_>>();
+	let runners_up = members.split_off((desired_members as usize).min(members.len()));
+	(members, runners_up)
+}
+
+/// Apply the prospective `votes` to the voters, and report the seats that they flip.
+async fn add_votes(
+	votes: &[Vote],
+	count: usize,
+	desired_members: u32,
+	candidates: &[AccountId],
+	all_voters: &mut Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	client: &Client,
+	at: Hash,
+) {
+	let (members_before, runners_up_before) =
+		seats(count, desired_members, candidates.to_vec(), all_voters.clone());
+	for Vote { who, stake, votes } in votes.iter() {
+		if let Some(unknown) = votes.iter().find(|v| !candidates.contains(v)) {
+			log::warn!(target: LOG_TARGET, "{:?} is not a candidate, the vote for it is lost.", unknown);
+		}
+		let weight =
+			<network::CurrencyToVoteHandler as Convert<Balance, VoteWeight>>::convert(*stake);
+		all_voters.retain(|(v, _, _)| v != who);
+		all_voters.push((who.clone(), weight, votes.clone()));
+	}
+	let (members_after, runners_up_after) =
+		seats(count, desired_members, candidates.to_vec(), all_voters.clone());
+
+	let name = |who: AccountId| async move {
+		storage::helpers::get_identity::<AccountId, Balance>(who.as_ref(), client, at).await
+	};
+	println!("🗳  with {} added votes:", votes.len());
+	if members_before == members_after && runners_up_before == runners_up_after {
+		println!("\tno seat changes.");
+	}
+	for m in members_after.iter().filter(|m| !members_before.contains(m)) {
+		let from = if runners_up_before.contains(m) { "promoted from runner-up" } else { "new" };
+		println!("\t➕ member {:?} ({}), {}", m, name(m.clone()).await, from);
+	}
+	for m in members_before.iter().filter(|m| !members_after.contains(m)) {
+		println!("\t➖ member {:?} ({})", m, name(m.clone()).await);
+	}
+	for r in runners_up_after.iter().filter(|r| !runners_up_before.contains(r)) {
+		if !members_before.contains(r) {
+			println!("\t➕ runner-up {:?} ({})", r, name(r.clone()).await);
+		}
+	}
+	for r in runners_up_before.iter().filter(|r| !runners_up_after.contains(r)) {
+		if !members_after.contains(r) {
+			println!("\t➖ runner-up {:?} ({})", r, name(r.clone()).await);
+		}
+	}
+}
+
+/// The number of elections to run while searching for the margin of a seat.
+const MARGIN_SEARCH_ROUNDS: usize = 24;
+
+/// Search for the smallest weight of a synthetic vote for which `flips` holds, assuming it holds
+/// for any larger weight. Returns `None` if it does not even hold for `high`.
+fn margin_search(high: VoteWeight, flips: impl Fn(VoteWeight) -> bool) -> Option<VoteWeight> {
+	if !flips(high) {
+		return None;
+	}
+	let mut low: VoteWeight = 0;
+	let mut high = high;
+	for _ in 0..MARGIN_SEARCH_ROUNDS {
+		let mid = low + (high - low) / 2;
+		if flips(mid) {
+			high = mid;
+		} else {
+			low = mid;
+		}
+	}
+	Some(high)
+}
+
+/// Report how much more backing each losing candidate needs to take the last member seat, and
+/// how much backing the first runner-up needs to take the seat of each member.
+///
+/// Each margin is the stake of a single synthetic voter, voting only for the challenger, and
+/// searched by re-running the election.
+async fn margins(
+	count: usize,
+	desired_members: u32,
+	candidates: &[AccountId],
+	all_voters: &[(AccountId, VoteWeight, Vec<AccountId>)],
+	client: &Client,
+	at: Hash,
+) {
+	let synthetic = AccountId::from([0xff; 32]);
+	let total = all_voters.iter().fold(0 as VoteWeight, |acc, (_, w, _)| acc.saturating_add(*w));
+	let members_with = |challenger: &AccountId, weight: VoteWeight| {
+		let mut voters = all_voters.to_vec();
+		voters.push((synthetic.clone(), weight, vec![challenger.clone()]));
+		seats(count, desired_members, candidates.to_vec(), voters).0
+	};
+	let to_currency = |weight: VoteWeight| {
+		Currency::from(<network::CurrencyToVoteHandler as Convert<u128, u128>>::convert(
+			weight as u128,
+		))
+	};
+	let name = |who: AccountId| async move {
+		storage::helpers::get_identity::<AccountId, Balance>(who.as_ref(), client, at).await
+	};
+
+	let (members, runners_up) =
+		seats(count, desired_members, candidates.to_vec(), all_voters.to_vec());
+	log::info!(target: LOG_TARGET, "searching the margins of {} candidates", candidates.len());
+
+	println!("📏 backing needed to take the last seat:");
+	for c in candidates.iter().filter(|c| !members.contains(c)) {
+		match margin_search(total, |w| members_with(c, w).contains(c)) {
+			Some(w) => println!("\t{:?} ({}): {:?}", c, name(c.clone()).await, to_currency(w)),
+			None => println!("\t{:?} ({}): cannot be elected", c, name(c.clone()).await),
+		}
+	}
+
+	let challenger = match runners_up.first() {
+		Some(challenger) => challenger,
+		None => {
+			println!("📏 no runner-up to challenge the members.");
+			return;
+		}
+	};
+	println!("📏 backing that {:?} needs to take the seat of:", challenger);
+	for m in members.iter() {
+		match margin_search(total, |w| !members_with(challenger, w).contains(m)) {
+			Some(w) => println!("\t{:?} ({}): {:?}", m, name(m.clone()).await, to_currency(w)),
+			None => println!("\t{:?} ({}): safe", m, name(m.clone()).await),
+		}
+	}
+}
+
+/// Run the election at the block before the last term change at or before `at`, and compare it
+/// with the members and runners-up that the chain elected in that term change.
+///
+/// The term changes at the blocks that are a multiple of `TermDuration`.
+async fn verify(
+	desired_members: u32,
+	desired_runners_up: u32,
+	client: &Client,
+	at: Hash,
+) -> error::Result<()> {
+	let number = storage::get_header::<Header>(client, at).await.expect("`at` must exist.").number;
+	let term_duration = storage::get_const::<u32>(client, "ElectionsPhragmen", "TermDuration", at)
+		.await
+		.expect("TermDuration const must exist.");
+	let term_change = number - number % term_duration.max(1);
+	assert!(term_change > 0, "no term change happened before block {}.", number);
+	let before = storage::get_block_hash(client, term_change - 1).await.expect("block must exist.");
+	let after = storage::get_block_hash(client, term_change).await.expect("block must exist.");
+	println!("🔍 verifying the term change at block #{} ({:?})", term_change, after);
+
+	// the votes are converted with the issuance of the block in which the election ran.
+	network::issuance::set(client, before).await.expect("total issuance must be readable.");
+	let version = storage_version(client, before).await?;
+	let candidates = get_candidates(version, client, before).await;
+	let voters = get_voters_and_budget(version, client, before)
+		.await
+		.into_iter()
+		.map(|(n, b, t)| {
+			(n, <network::CurrencyToVoteHandler as Convert<Balance, VoteWeight>>::convert(b), t)
+		})
+		.collect::<Vec<_>>();
+	let weights = voters.iter().map(|(v, w, _)| (v.clone(), *w)).collect::<BTreeMap<_, _>>();
+	let ElectionResult { winners, assignments } =
+		seq_phragmen::<AccountId, pallet_staking::ChainAccuracy>(
+			(desired_members + desired_runners_up) as usize,
+			candidates,
+			voters,
+			None,
+		)
+		.expect("Phragmen failed to elect.");
+	let elected = winners.into_iter().map(|(w, _)| w).collect::<Vec<_>>();
+	let staked = assignment_ratio_to_staked(assignments, |w| *weights.get(w).unwrap());
+	let supports = to_support_map::<AccountId>(&elected, &staked).unwrap();
+	let backing_of = |who: &AccountId| -> Balance {
+		supports.get(who).map_or(0, |s| {
+			<network::CurrencyToVoteHandler as Convert<u128, u128>>::convert(s.total)
+		})
+	};
+
+	let version = storage_version(client, after).await?;
+	let mut mismatches = 0;
+	for (role, offline, on_chain) in [
+		(
+			"member",
+			&elected[..(desired_members as usize).min(elected.len())],
+			get_members(version, client, after).await,
+		),
+		(
+			"runner-up",
+			&elected[(desired_members as usize).min(elected.len())..],
+			get_runners_up(version, client, after).await,
+		),
+	]
+	.iter()
+	{
+		for w in offline.iter().filter(|w| !on_chain.iter().any(|s| &s.who == *w)) {
+			println!("\t❌ {:?} is a {} offline, not on chain.", w, role);
+			mismatches += 1;
+		}
+		for s in on_chain.iter() {
+			if !offline.contains(&s.who) {
+				println!("\t❌ {:?} is a {} on chain, not offline.", s.who, role);
+				mismatches += 1;
+			} else if backing_of(&s.who) != s.stake {
+				println!(
+					"\t⚠️  {} {:?} is backed by {:?} offline, {:?} on chain.",
+					role,
+					s.who,
+					Currency::from(backing_of(&s.who)),
+					Currency::from(s.stake),
+				);
+				mismatches += 1;
+			}
+		}
+	}
+
+	if mismatches == 0 {
+		println!("\t✅ members, runners-up and their backing match.");
+	} else {
+		println!("\t❌ {} mismatches.", mismatches);
+	}
+	Ok(())
 }
 
 /// Main run function of the sub-command.
-pub async fn run(client: &Client, opt: Opt, conf: CouncilConfig) {
+pub async fn run(client: &Client, opt: Opt, conf: CouncilConfig) -> error::Result<()> {
 	let at = opt.at.unwrap();
 	let verbosity = opt.verbosity;
 	let desired_members =
@@ -90,11 +608,23 @@ pub async fn run(client: &Client, opt: Opt, conf: CouncilConfig) {
 		<network::CurrencyToVoteHandler as Convert<Balance, VoteWeight>>::convert(b)
 	};
 
+	let version = storage_version(client, at).await?;
+	log::info!(target: LOG_TARGET, "elections-phragmen storage version {:?}", version);
+
+	if conf.verify {
+		return verify(desired_members, desired_runners_up, client, at).await;
+	}
+
+	if !conf.remove.is_empty() {
+		simulate_removals(&conf.remove, version, client, at).await;
+		return Ok(());
+	}
+
 	// all candidates
-	let mut candidates = get_candidates(client, at).await;
+	let mut candidates = get_candidates(version, client, at).await;
 
 	// all voters.
-	let mut all_voters = get_voters_and_budget(&client, at)
+	let mut all_voters = get_voters_and_budget(version, &client, at)
 		.await
 		.into_iter()
 		.map(|(n, b, t)| (n, to_votes(b), t))
@@ -160,6 +690,16 @@ pub async fn run(client: &Client, opt: Opt, conf: CouncilConfig) {
 		all_voters.retain(|v| !manual.voters_remove.contains(&v.0));
 	}
 
+	if !conf.add_vote.is_empty() {
+		add_votes(&conf.add_vote, count, desired_members, &candidates, &mut all_voters, client, at)
+			.await;
+	}
+
+	if let Some(range) = conf.sweep {
+		sweep(range, desired_runners_up, candidates, all_voters, client, at).await;
+		return Ok(());
+	}
+
 	// budget of each voter
 	let mut voter_weight: BTreeMap<AccountId, VoteWeight> = BTreeMap::new();
 
@@ -172,11 +712,14 @@ pub async fn run(client: &Client, opt: Opt, conf: CouncilConfig) {
 
 	// run phragmen
 	t_start!(phragmen_run);
-	let ElectionResult { winners, assignments } = seq_phragmen::<
-		AccountId,
-		pallet_staking::ChainAccuracy,
-	>(count, candidates, all_voters.clone(), None)
-	.expect("Phragmen failed to elect.");
+	let ElectionResult { winners, assignments } =
+		seq_phragmen::<AccountId, pallet_staking::ChainAccuracy>(
+			count,
+			candidates.clone(),
+			all_voters.clone(),
+			None,
+		)
+		.expect("Phragmen failed to elect.");
 	t_stop!(phragmen_run);
 
 	let elected_stashes = winners.iter().map(|(s, _)| s.clone()).collect::<Vec<AccountId>>();
@@ -215,12 +758,12 @@ pub async fn run(client: &Client, opt: Opt, conf: CouncilConfig) {
 	let mut new_members = winners.into_iter().take(desired_members as usize).collect::<Vec<_>>();
 	new_members.sort_by_key(|(m, _)| m.clone());
 	let mut prime_votes: Vec<_> = new_members.iter().map(|(c, _)| (c, Balance::zero())).collect();
-	for (_, stake, targets) in all_voters.into_iter() {
+	for (_, stake, targets) in all_voters.iter() {
 		for (vote_multiplier, who) in
 			targets.iter().enumerate().map(|(vote_position, who)| ((16 - vote_position) as u32, who))
 		{
 			if let Ok(i) = prime_votes.binary_search_by_key(&who, |k| k.0) {
-				prime_votes[i].1 += (stake as Balance) * (vote_multiplier as Balance);
+				prime_votes[i].1 += (*stake as Balance) * (vote_multiplier as Balance);
 			}
 		}
 	}
@@ -233,4 +776,58 @@ pub async fn run(client: &Client, opt: Opt, conf: CouncilConfig) {
 			storage::helpers::get_identity::<AccountId, Balance>(prime.as_ref(), &client, at).await
 		);
 	}
+
+	if conf.margin {
+		margins(count, desired_members, &candidates, &all_voters, client, at).await;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+	const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+	#[test]
+	fn seat_range_from_str_works() {
+		let range = SeatRange::from_str("13..19").unwrap();
+		assert_eq!((range.from, range.to), (13, 19));
+		let range = SeatRange::from_str("7..7").unwrap();
+		assert_eq!((range.from, range.to), (7, 7));
+
+		// from <= to, and at least one seat.
+		assert!(SeatRange::from_str("19..13").is_err());
+		assert!(SeatRange::from_str("0..13").is_err());
+		assert!(SeatRange::from_str("13").is_err());
+		assert!(SeatRange::from_str("13..").is_err());
+		assert!(SeatRange::from_str("..13").is_err());
+		assert!(SeatRange::from_str("a..b").is_err());
+	}
+
+	#[test]
+	fn vote_from_str_works() {
+		let vote = Vote::from_str(&format!("{}:100:{}, {}", ALICE, ALICE, BOB)).unwrap();
+		assert_eq!(vote.who, AccountId::from_str(ALICE).unwrap());
+		assert_eq!(vote.stake, 100);
+		assert_eq!(
+			vote.votes,
+			vec![ALICE, BOB].into_iter().map(|c| c.parse().unwrap()).collect::<Vec<AccountId>>()
+		);
+
+		// at most 16 votes, as the pallet allows.
+		let candidates = |n: usize| vec![BOB; n].join(",");
+		assert_eq!(
+			Vote::from_str(&format!("{}:100:{}", ALICE, candidates(16))).unwrap().votes.len(),
+			16
+		);
+		assert!(Vote::from_str(&format!("{}:100:{}", ALICE, candidates(17))).is_err());
+
+		// at least one candidate.
+		assert!(Vote::from_str(&format!("{}:100:", ALICE)).is_err());
+		assert!(Vote::from_str(&format!("{}:100", ALICE)).is_err());
+		assert!(Vote::from_str(&format!("{}:stake:{}", ALICE, BOB)).is_err());
+		assert!(Vote::from_str(&format!("alice:100:{}", BOB)).is_err());
+	}
 }
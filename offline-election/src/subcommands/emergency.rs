@@ -0,0 +1,74 @@
+//! Compute the emergency solution of the multi-phase election, to be submitted by governance.
+
+use crate::{
+	error,
+	election::{self, Algorithm},
+	primitives::{AccountId, BlockNumber, Hash},
+	snapshot::Snapshot,
+	storage, Client, Opt, LOG_TARGET,
+};
+use codec::Encode;
+use sp_npos_elections::Support;
+
+/// The phase of the multi-phase election.
+#[derive(codec::Decode, Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Phase {
+	Off,
+	Signed,
+	Unsigned((bool, BlockNumber)),
+	Emergency,
+}
+
+/// Get the current phase of the multi-phase election, if the chain has one.
+pub(crate) async fn current_phase(client: &Client, at: Hash) -> Option<Phase> {
+	storage::read::<Phase>(
+		storage::value_key(b"ElectionProviderMultiPhase", b"CurrentPhase"),
+		client,
+		at,
+	)
+	.await
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, iterations: usize, force: bool) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	match current_phase(client, at).await {
+		Some(Phase::Emergency) => println!("🚨 The election is in the emergency phase."),
+		Some(phase) if force => {
+			log::warn!(target: LOG_TARGET, "not in the emergency phase ({:?}), forced.", phase)
+		}
+		Some(phase) => {
+			println!("✅ The election is not in the emergency phase ({:?}).", phase);
+			return Ok(());
+		}
+		None => {
+			log::warn!(target: LOG_TARGET, "this chain has no multi-phase election.");
+			return Ok(());
+		}
+	}
+
+	// the emergency solution is not bound by any weight or length limit.
+	let snapshot = Snapshot::fetch(client, at).await?;
+	let outcome = election::elect(
+		&snapshot,
+		snapshot.desired_targets as usize,
+		Algorithm::SeqPhragmen,
+		iterations,
+		false,
+	)
+	.map_err(error::Error::Election)?;
+	println!("🏆 emergency solution with score {:?}", outcome.score);
+
+	let supports = outcome.supports.into_iter().collect::<Vec<(AccountId, Support<AccountId>)>>();
+	let (pallet_index, call_index) = error::call_index(
+		client,
+		"ElectionProviderMultiPhase",
+		"set_emergency_election_result",
+		at,
+	)
+	.await?;
+	let mut call = vec![pallet_index, call_index];
+	call.extend(supports.encode());
+	println!("📦 call, to be dispatched by governance: 0x{}", hex::encode(call));
+	Ok(())
+}
@@ -0,0 +1,103 @@
+//! Export the staking rewards of an account as csv, for tax and accounting tools.
+//!
+//! All values are computed from on-chain data, as `backtest --nominator` and `payout-check` do.
+//! Dates are estimated from the start of the active era and the expected duration of an era.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance, Hash},
+	subcommands::{backtest, commission_history, payout_check, session_info, staking},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use pallet_staking::EraIndex;
+use std::{io::Write, path::PathBuf};
+
+/// Format a unix timestamp, in milliseconds, as a `YYYY-MM-DD` date.
+fn date_of(timestamp: u64) -> String {
+	// civil from days, http://howardhinnant.github.io/date_algorithms.html
+	let days = (timestamp / 1000 / 86400) as i64 + 719468;
+	let era = days / 146097;
+	let doe = days - era * 146097;
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+	format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Format `amount` in units of the token, with all the decimal points.
+fn units_of(amount: Balance) -> String {
+	let decimals = sub_tokens::dynamic::decimal_points();
+	let digits = (decimals as f64).log10().round() as usize;
+	format!("{}.{:0width$}", amount / decimals, amount % decimals, width = digits)
+}
+
+/// Main run function of the sub-command.
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	who: AccountId,
+	from_era: Option<EraIndex>,
+	to_era: Option<EraIndex>,
+	output: Option<PathBuf>,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let active_era = session_info::get_active_era(client, at).await;
+	let depth = commission_history::history_depth(client, at).await;
+	let first_era = active_era.index.saturating_sub(depth);
+	let from_era = from_era.unwrap_or(first_era).max(first_era);
+	// the rewards of the active era are not known yet.
+	let to_era = to_era.unwrap_or(active_era.index).min(active_era.index.saturating_sub(1));
+	if from_era > to_era {
+		log::warn!(target: LOG_TARGET, "no era in the range {}..={} can be exported.", from_era, to_era);
+		return Ok(());
+	}
+
+	let era_duration = session_info::era_duration(client, at).await;
+	let active_start = active_era.start.unwrap_or_default();
+	let mut out: Box<dyn Write> = match output {
+		Some(ref path) => {
+			Box::new(std::fs::File::create(path).expect("Failed to create csv file."))
+		}
+		None => Box::new(std::io::stdout()),
+	};
+	writeln!(out, "era,date,validator,amount,token").unwrap();
+
+	let mut total: Balance = 0;
+	for era in from_era..=to_era {
+		let start = active_start.saturating_sub((active_era.index - era) as u64 * era_duration);
+		let mut rows = backtest::nominator_rewards(&who, era, client, at).await;
+		// the commission and own stake of `who`, if it was itself elected.
+		if staking::exposure_of(&who, era, client, at).await.total > 0 {
+			let payout = payout_check::estimate_payout(&who, era, client, at).await;
+			rows.push((who.clone(), payout.validator));
+		}
+
+		for (validator, amount) in rows.into_iter().filter(|(_, a)| *a > 0) {
+			writeln!(
+				out,
+				"{},{},{},{},{}",
+				era,
+				date_of(start),
+				validator,
+				units_of(amount),
+				sub_tokens::dynamic::name()
+			)
+			.unwrap();
+			total += amount;
+		}
+	}
+
+	if let Some(ref path) = output {
+		println!(
+			"📦 rewards of eras {}..={} written to {:?}, total {:?}",
+			from_era,
+			to_era,
+			path,
+			Currency::from(total)
+		);
+	}
+	Ok(())
+}
@@ -0,0 +1,159 @@
+//! Generate synthetic snapshots of the staking election.
+
+use crate::{
+	error::{self, Error},
+	primitives::{blake2_256, AccountId},
+	snapshot::{Snapshot, Voter},
+	GenerateConfig, LOG_TARGET,
+};
+use codec::Encode;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use sp_npos_elections::VoteWeight;
+use std::str::FromStr;
+
+/// The distribution from which the stake of voters are drawn.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StakeDistribution {
+	/// Uniformly between the minimum and maximum stake.
+	Uniform,
+	/// A Zipf (power law) distribution, where the stake of the voter with rank `r` is proportional
+	/// to `1 / r^s`.
+	Zipf,
+	/// Copy the shape of an existing snapshot, e.g. one scraped from Kusama. Both the stake and
+	/// the number of votes of voters are sampled from the given snapshot.
+	Shape,
+}
+
+impl FromStr for StakeDistribution {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"uniform" => Ok(Self::Uniform),
+			"zipf" => Ok(Self::Zipf),
+			"shape" => Ok(Self::Shape),
+			_ => Err("Invalid stake distribution. Can be uniform|zipf|shape."),
+		}
+	}
+}
+
+/// Create a deterministic account id from a domain and an index.
+pub(crate) fn account(domain: &[u8], index: usize) -> AccountId {
+	AccountId::from(blake2_256(&(domain, index as u64).encode()))
+}
+
+/// Draws stakes and vote counts for generated voters.
+struct Sampler {
+	distribution: StakeDistribution,
+	min: VoteWeight,
+	max: VoteWeight,
+	exponent: f64,
+	// (stake, number of votes) of nominators in the shape snapshot.
+	shape_nominators: Vec<(VoteWeight, usize)>,
+	// stake of self-votes in the shape snapshot.
+	shape_self_votes: Vec<VoteWeight>,
+}
+
+impl Sampler {
+	/// The stake of the voter with the given rank.
+	fn stake(&self, rng: &mut StdRng, rank: usize, self_vote: bool) -> VoteWeight {
+		match self.distribution {
+			StakeDistribution::Uniform => rng.gen_range(self.min, self.max.max(self.min + 1)),
+			StakeDistribution::Zipf => {
+				let stake = self.max as f64 / ((rank + 1) as f64).powf(self.exponent);
+				(stake as VoteWeight).max(self.min)
+			}
+			StakeDistribution::Shape if self_vote => {
+				*self.shape_self_votes.choose(rng).expect("checked in generate; qed")
+			}
+			StakeDistribution::Shape => {
+				self.shape_nominators.choose(rng).expect("checked in generate; qed").0
+			}
+		}
+	}
+
+	/// The number of votes of a voter.
+	fn votes(&self, rng: &mut StdRng, max_votes: usize) -> usize {
+		match self.distribution {
+			StakeDistribution::Shape => self
+				.shape_nominators
+				.choose(rng)
+				.expect("checked in generate; qed")
+				.1
+				.min(max_votes)
+				.max(1),
+			_ => rng.gen_range(1, max_votes + 1),
+		}
+	}
+}
+
+/// Generate a new snapshot with the given configuration.
+pub fn generate(conf: &GenerateConfig) -> error::Result<Snapshot> {
+	let mut rng = StdRng::seed_from_u64(conf.seed);
+	let (shape_nominators, shape_self_votes) = match conf.shape_of {
+		Some(ref path) => {
+			let shape = Snapshot::load(path)?;
+			let (self_votes, nominators): (Vec<_>, Vec<_>) =
+				shape.voters.into_iter().partition(|(v, _, t)| t == &vec![v.clone()]);
+			if self_votes.is_empty() || nominators.is_empty() {
+				return Err(Error::file(path, "the shape needs both nominators and self-votes"));
+			}
+			(
+				nominators.into_iter().map(|(_, w, t)| (w, t.len())).collect::<Vec<_>>(),
+				self_votes.into_iter().map(|(_, w, _)| w).collect::<Vec<_>>(),
+			)
+		}
+		None if conf.distribution == StakeDistribution::Shape => {
+			return Err(Error::Invalid(
+				"`--shape-of` must be provided with the shape distribution.".into(),
+			))
+		}
+		None => (vec![], vec![]),
+	};
+	let sampler = Sampler {
+		distribution: conf.distribution,
+		min: conf.min_stake,
+		max: conf.max_stake,
+		exponent: conf.zipf_exponent,
+		shape_nominators,
+		shape_self_votes,
+	};
+
+	let targets = (0..conf.targets).map(|i| account(b"target", i)).collect::<Vec<_>>();
+	let mut voters: Vec<Voter> = Vec::with_capacity(conf.voters + conf.targets);
+
+	// ranks are shuffled, so that stake is not correlated with the order of the voters.
+	let mut ranks = (0..conf.voters).collect::<Vec<_>>();
+	ranks.shuffle(&mut rng);
+	for (i, rank) in ranks.into_iter().enumerate() {
+		let stake = sampler.stake(&mut rng, rank, false);
+		let count = sampler.votes(&mut rng, conf.edges.min(targets.len()));
+		let votes = targets.choose_multiple(&mut rng, count).cloned().collect::<Vec<_>>();
+		voters.push((account(b"voter", i), stake, votes));
+	}
+
+	let mut ranks = (0..conf.targets).collect::<Vec<_>>();
+	ranks.shuffle(&mut rng);
+	for (t, rank) in targets.iter().zip(ranks.into_iter()) {
+		let stake = sampler.stake(&mut rng, rank, true);
+		voters.push((t.clone(), stake, vec![t.clone()]));
+	}
+
+	Ok(Snapshot { at: None, desired_targets: conf.desired_targets, targets, voters })
+}
+
+/// Main run function of the sub-command.
+///
+/// Note that this sub-command does not need a connection to any node.
+pub fn run(conf: GenerateConfig) -> error::Result<()> {
+	log::info!(target: LOG_TARGET, "generating snapshot with {:?}", conf);
+	let snapshot = generate(&conf)?;
+	snapshot.save(&conf.output)?;
+	println!(
+		"🎲 generated {} voters and {} targets ({:?} stake distribution) into {:?}",
+		snapshot.voters.len(),
+		snapshot.targets.len(),
+		conf.distribution,
+		conf.output
+	);
+	Ok(())
+}
@@ -0,0 +1,137 @@
+//! The staking rate and inflation model of the chain.
+
+use crate::{
+	error, network,
+	primitives::{Balance, Hash},
+	storage,
+	subcommands::session_info,
+	Client, Currency, Opt,
+};
+use codec::Encode;
+use pallet_staking::EraIndex;
+use sp_runtime::curve::PiecewiseLinear;
+
+// The reward curve of both polkadot and kusama.
+pallet_staking_reward_curve::build! {
+	const REWARD_CURVE: PiecewiseLinear<'static> = curve!(
+		min_inflation: 0_025_000,
+		max_inflation: 0_100_000,
+		ideal_stake: 0_750_000,
+		falloff: 0_050_000,
+		max_piece_count: 40,
+		test_precision: 0_005_000,
+	);
+}
+
+/// The inflation model of the chain, at a given block.
+#[derive(Debug, Clone)]
+pub(crate) struct Model {
+	/// Total stake of the active era.
+	pub staked: Balance,
+	/// Total issuance of the chain.
+	pub issuance: Balance,
+	/// The staking rate for which the inflation is maximum.
+	pub ideal_rate: f64,
+	/// The yearly inflation given to the stakers, as a fraction of the total issuance.
+	pub staking_inflation: f64,
+	/// The maximum yearly inflation, as a fraction of the total issuance.
+	pub max_inflation: f64,
+	/// The duration of an era, in milliseconds.
+	pub era_duration: u64,
+	/// The payout of an era given to the validators and nominators.
+	pub era_payout: Balance,
+	/// The maximum payout of an era. The remainder goes to the treasury.
+	pub era_max_payout: Balance,
+}
+
+impl Model {
+	/// The current staking rate, `staked / issuance`.
+	pub(crate) fn staking_rate(&self) -> f64 {
+		self.staked as f64 / self.issuance.max(1) as f64
+	}
+
+	/// The share of the era payout that is not given to the stakers.
+	pub(crate) fn era_remainder(&self) -> Balance {
+		self.era_max_payout.saturating_sub(self.era_payout)
+	}
+}
+
+/// Get the total stake of the given era.
+pub(crate) async fn total_stake_at(era: EraIndex, client: &Client, at: Hash) -> Balance {
+	storage::read::<Balance>(
+		storage::map_key::<frame_support::Twox64Concat>(
+			b"Staking",
+			b"ErasTotalStake",
+			era.encode().as_ref(),
+		),
+		client,
+		at,
+	)
+	.await
+	.unwrap_or_default()
+}
+
+/// Compute the inflation model of the chain at `at`.
+///
+/// `issuance::set` must have been called before.
+pub(crate) async fn model(client: &Client, at: Hash) -> Model {
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let staked = total_stake_at(active_era, client, at).await;
+	let issuance = network::issuance::get();
+	let era_duration = session_info::era_duration(client, at).await;
+	let (era_payout, era_max_payout) = pallet_staking::inflation::compute_total_payout(
+		&REWARD_CURVE,
+		staked,
+		issuance,
+		era_duration,
+	);
+	let ideal_rate = REWARD_CURVE
+		.points
+		.iter()
+		.max_by_key(|(_, y)| *y)
+		.map(|(x, _)| x.deconstruct() as f64 / 1_000_000_000f64)
+		.unwrap_or_default();
+	let staking_inflation = REWARD_CURVE.calculate_for_fraction_times_denominator(staked, issuance)
+		as f64 / issuance.max(1) as f64;
+	let max_inflation = REWARD_CURVE.maximum.deconstruct() as f64 / 1_000_000_000f64;
+
+	Model {
+		staked,
+		issuance,
+		ideal_rate,
+		staking_inflation,
+		max_inflation,
+		era_duration,
+		era_payout,
+		era_max_payout,
+	}
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let model = model(client, at).await;
+
+	println!("💰 total staked = {:?}", Currency::from(model.staked));
+	println!("💰 total issuance = {:?}", Currency::from(model.issuance));
+	println!(
+		"📈 staking rate = {:.2}% / ideal = {:.2}%",
+		model.staking_rate() * 100f64,
+		model.ideal_rate * 100f64
+	);
+	println!(
+		"📈 yearly inflation to stakers = {:.2}% / total = {:.2}%",
+		model.staking_inflation * 100f64,
+		model.max_inflation * 100f64
+	);
+	println!(
+		"🎁 era reward = {:?} to stakers / {:?} in total",
+		Currency::from(model.era_payout),
+		Currency::from(model.era_max_payout)
+	);
+	println!(
+		"📈 yearly return of stakers = {:.2}%",
+		model.staking_inflation * 100f64 / model.staking_rate().max(f64::EPSILON)
+	);
+	Ok(())
+}
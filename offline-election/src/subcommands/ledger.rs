@@ -0,0 +1,60 @@
+//! Dump the staking ledger of a stash in human readable units.
+
+use crate::{
+	error,
+	primitives::{AccountId, Hash},
+	subcommands::{session_info, staking},
+	Client, Currency, Opt,
+};
+use pallet_staking::EraIndex;
+
+/// Format a duration in milliseconds as days and hours.
+pub(crate) fn human_duration(ms: u64) -> String {
+	let hours = ms / 1000 / 3600;
+	format!("{}d {}h", hours / 24, hours % 24)
+}
+
+/// Approximate time until `era` becomes the active era.
+pub(crate) async fn eta_of(era: EraIndex, client: &Client, at: Hash) -> String {
+	let active_era = session_info::get_active_era(client, at).await.index;
+	if era <= active_era {
+		"now".to_string()
+	} else {
+		let era_duration = session_info::era_duration(client, at).await;
+		format!("~{}", human_duration((era - active_era) as u64 * era_duration))
+	}
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, who: AccountId) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let ctrl = match staking::controller_of(&who, client, at).await {
+		Some(ctrl) => ctrl,
+		None => {
+			log::warn!("{:?} is not a bonded stash.", who);
+			return Ok(());
+		}
+	};
+	let ledger = staking::ledger_of(&ctrl, client, at)
+		.await
+		.expect("All controllers must have a 'Ledger' storage");
+
+	println!("🗒 stash {:?} || ctrl = {:?}", ledger.stash, ctrl);
+	println!("💰 total = {:?}", Currency::from(ledger.total));
+	println!("💰 active = {:?}", Currency::from(ledger.active));
+	if ledger.unlocking.is_empty() {
+		println!("🔓 no unlocking chunks.");
+	} else {
+		println!("🔓 unlocking chunks:");
+		for chunk in ledger.unlocking.iter() {
+			println!(
+				"\t{:?} at era {} (eta {})",
+				Currency::from(chunk.value),
+				chunk.era,
+				eta_of(chunk.era, client, at).await,
+			);
+		}
+	}
+	println!("🎁 claimed rewards: {:?}", ledger.claimed_rewards);
+	Ok(())
+}
@@ -0,0 +1,130 @@
+//! Audit all staking ledgers for inconsistencies.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance, Hash},
+	storage, Client, Currency, Opt, LOG_TARGET,
+};
+use frame_system::AccountInfo;
+use pallet_balances::{AccountData, BalanceLock};
+use pallet_staking::StakingLedger;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The identifier of the staking lock.
+const STAKING_ID: [u8; 8] = *b"staking ";
+
+/// Get the account data of an account.
+pub(crate) async fn account_data_of(
+	who: &AccountId,
+	client: &Client,
+	at: Hash,
+) -> AccountData<Balance> {
+	storage::read::<AccountInfo<u32, AccountData<Balance>>>(
+		storage::map_key::<frame_support::Blake2_128Concat>(b"System", b"Account", who.as_ref()),
+		client,
+		at,
+	)
+	.await
+	.map(|i| i.data)
+	.unwrap_or_default()
+}
+
+/// Get the staking lock of an account, if any.
+pub(crate) async fn staking_lock_of(who: &AccountId, client: &Client, at: Hash) -> Option<Balance> {
+	storage::read::<Vec<BalanceLock<Balance>>>(
+		storage::map_key::<frame_support::Twox64Concat>(b"Balances", b"Locks", who.as_ref()),
+		client,
+		at,
+	)
+	.await
+	.unwrap_or_default()
+	.into_iter()
+	.find(|l| l.id == STAKING_ID)
+	.map(|l| l.amount)
+}
+
+/// Check a single ledger in isolation, returning the list of problems found.
+fn check_ledger(ledger: &StakingLedger<AccountId, Balance>) -> Vec<String> {
+	let mut problems = vec![];
+	let unlocking = ledger.unlocking.iter().map(|c| c.value).sum::<Balance>();
+	if ledger.total != ledger.active + unlocking {
+		problems.push(format!(
+			"total ({:?}) != active ({:?}) + unlocking ({:?})",
+			Currency::from(ledger.total),
+			Currency::from(ledger.active),
+			Currency::from(unlocking),
+		));
+	}
+
+	let mut eras = BTreeSet::new();
+	for chunk in ledger.unlocking.iter() {
+		if !eras.insert(chunk.era) {
+			problems.push(format!("duplicate unlocking chunk at era {}", chunk.era));
+		}
+	}
+
+	problems
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let ledgers = storage::enumerate_map::<AccountId, StakingLedger<AccountId, Balance>>(
+		b"Staking", b"Ledger", client, at,
+	)
+	.await
+	.expect("Staking::ledger should be enumerable.");
+	let bonded = storage::enumerate_map::<AccountId, AccountId>(b"Staking", b"Bonded", client, at)
+		.await
+		.expect("Staking::bonded should be enumerable.")
+		.into_iter()
+		.collect::<BTreeMap<AccountId, AccountId>>();
+
+	log::info!(target: LOG_TARGET, "auditing {} ledgers.", ledgers.len());
+
+	let mut affected = 0;
+	for (ctrl, ledger) in ledgers.iter() {
+		let mut problems = check_ledger(ledger);
+
+		match bonded.get(&ledger.stash) {
+			Some(c) if c == ctrl => {}
+			Some(c) => problems.push(format!("orphaned controller, stash is bonded to {:?}", c)),
+			None => problems.push("orphaned controller, stash is not bonded".to_string()),
+		}
+
+		let data = account_data_of(&ledger.stash, client, at).await;
+		if ledger.total > data.free {
+			problems.push(format!(
+				"bond ({:?}) exceeds free balance ({:?})",
+				Currency::from(ledger.total),
+				Currency::from(data.free),
+			));
+		}
+		match staking_lock_of(&ledger.stash, client, at).await {
+			Some(lock) if lock != ledger.total => problems.push(format!(
+				"staking lock ({:?}) differs from bond ({:?})",
+				Currency::from(lock),
+				Currency::from(ledger.total),
+			)),
+			None => problems.push("no staking lock".to_string()),
+			_ => {}
+		}
+
+		if !problems.is_empty() {
+			affected += 1;
+			println!("❌ stash {:?} || ctrl = {:?}", ledger.stash, ctrl);
+			problems.iter().for_each(|p| println!("\t{}", p));
+		}
+	}
+
+	let controllers = ledgers.iter().map(|(c, _)| c.clone()).collect::<BTreeSet<_>>();
+	for (stash, ctrl) in bonded.iter() {
+		if !controllers.contains(ctrl) {
+			affected += 1;
+			println!("❌ stash {:?} is bonded to {:?} which has no ledger.", stash, ctrl);
+		}
+	}
+
+	println!("📊 {} accounts affected out of {} ledgers.", affected, ledgers.len());
+	Ok(())
+}
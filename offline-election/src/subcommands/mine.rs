@@ -0,0 +1,294 @@
+//! Mine a solution for the unsigned phase of the staking election, as the offchain worker of a
+//! validator would.
+
+use crate::{
+	election::{self, Algorithm},
+	error::{self, Error},
+	network,
+	primitives::{AccountId, Hash},
+	snapshot::Snapshot,
+	storage,
+	subcommands::{
+		emergency::{current_phase, Phase},
+		staking,
+	},
+	weights, Client, MineConfig, Opt, LOG_TARGET,
+};
+use codec::Encode;
+use frame_support::weights::{DispatchClass, Weight};
+use frame_system::limits::{BlockLength, BlockWeights};
+use pallet_staking::{
+	CompactAssignments, ElectionSize, NominatorIndex, OffchainAccuracy, ValidatorIndex,
+};
+use sp_npos_elections::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A solution, ready to be submitted with `staking.submit_election_solution_unsigned`.
+pub(crate) struct Solution {
+	pub winners: Vec<ValidatorIndex>,
+	pub compact: CompactAssignments,
+	pub score: ElectionScore,
+	pub size: ElectionSize,
+}
+
+/// The weight of a solution submission in the runtime named `spec_name`, with its benchmark formula
+/// of `submit_solution_better`.
+///
+/// - `targets`: the number of validators in the snapshot.
+/// - `voters`: the number of nominators in the snapshot, self-votes included.
+/// - `active_voters`: the number of voters with at least one assignment in the solution.
+/// - `winners`: the number of elected validators.
+pub(crate) fn solution_weight(
+	spec_name: &str,
+	targets: u32,
+	voters: u32,
+	active_voters: u32,
+	winners: u32,
+) -> Weight {
+	weights::submit_solution_better(spec_name, targets, voters, active_voters, winners)
+}
+
+impl Solution {
+	/// The weight of the submission in the runtime named `spec_name`.
+	pub(crate) fn weight(&self, spec_name: &str) -> Weight {
+		solution_weight(
+			spec_name,
+			self.size.validators.into(),
+			self.size.nominators,
+			self.compact.len() as u32,
+			self.winners.len() as u32,
+		)
+	}
+
+	/// The SCALE-encoded length of the compact assignments.
+	pub(crate) fn encoded_length(&self) -> usize {
+		self.compact.encoded_size()
+	}
+
+	/// The priority of the unsigned transaction, which is the base priority of the runtime plus
+	/// the minimal backing of the solution.
+	pub(crate) fn priority(&self, base: u64) -> u64 {
+		base.saturating_add(self.score[0].min(u64::max_value() as u128) as u64)
+	}
+}
+
+/// Get the validators and nominators of the on-chain snapshot, if the election window is open.
+async fn snapshot_indices(client: &Client, at: Hash) -> Option<(Vec<AccountId>, Vec<AccountId>)> {
+	let validators = storage::read::<Vec<AccountId>>(
+		storage::value_key(b"Staking", b"SnapshotValidators"),
+		client,
+		at,
+	)
+	.await?;
+	let nominators = storage::read::<Vec<AccountId>>(
+		storage::value_key(b"Staking", b"SnapshotNominators"),
+		client,
+		at,
+	)
+	.await?;
+	Some((validators, nominators))
+}
+
+async fn block_weights(client: &Client, at: Hash) -> error::Result<BlockWeights> {
+	error::constant::<BlockWeights>(client, "System", "BlockWeights", at).await
+}
+
+/// The maximum weight of a normal extrinsic.
+pub(crate) async fn max_extrinsic(client: &Client, at: Hash) -> error::Result<Weight> {
+	let weights = block_weights(client, at).await?;
+	Ok(weights.get(DispatchClass::Normal).max_extrinsic.unwrap_or(weights.max_block))
+}
+
+/// The maximum weight of an unsigned solution, as configured in the polkadot and kusama runtimes:
+/// the maximum weight of a normal extrinsic, minus the base weights of the block and extrinsic.
+pub(crate) async fn weight_limit(client: &Client, at: Hash) -> error::Result<Weight> {
+	let weights = block_weights(client, at).await?;
+	Ok(max_extrinsic(client, at)
+		.await?
+		.saturating_sub(weights.base_block)
+		.saturating_sub(weights.get(DispatchClass::Normal).base_extrinsic))
+}
+
+/// The maximum length of a solution: `MinerMaxLength` of the multi-phase election pallet if the
+/// chain has it, the maximum length of a normal extrinsic otherwise.
+pub(crate) async fn length_limit(client: &Client, at: Hash) -> u32 {
+	match storage::get_const::<u32>(client, "ElectionProviderMultiPhase", "MinerMaxLength", at)
+		.await
+	{
+		Some(max) => max,
+		None => *storage::get_const::<BlockLength>(client, "System", "BlockLength", at)
+			.await
+			.expect("System::BlockLength const must exist.")
+			.max
+			.get(DispatchClass::Normal),
+	}
+}
+
+/// Restrict `snapshot` to the on-chain snapshot of `validators` and `nominators`, which is all that
+/// the chain accepts a solution over.
+///
+/// Voters and targets that joined after the election window opened are dropped, and so are the
+/// votes for targets that are not in the on-chain snapshot.
+fn restrict(snapshot: Snapshot, validators: &[AccountId], nominators: &[AccountId]) -> Snapshot {
+	let targets = validators.iter().cloned().collect::<BTreeSet<_>>();
+	let voters = nominators.iter().collect::<BTreeSet<_>>();
+	let dropped = snapshot.voters.iter().filter(|(v, _, _)| !voters.contains(v)).count();
+	if dropped > 0 {
+		log::info!(
+			target: LOG_TARGET,
+			"{} voters joined after the election window opened and are left out.",
+			dropped
+		);
+	}
+	let voters = snapshot
+		.voters
+		.into_iter()
+		.filter(|(v, _, _)| voters.contains(v))
+		.map(|(v, w, votes)| (v, w, votes.into_iter().filter(|t| targets.contains(t)).collect()))
+		.collect();
+	Snapshot { targets: validators.to_vec(), voters, ..snapshot }
+}
+
+/// Mine a solution on `snapshot`, where `validators` and `nominators` are the on-chain snapshot
+/// that the indices of the solution refer to.
+///
+/// The solution is reduced and converted to the accuracy of the chain, and its score is computed
+/// from the converted assignments, as the chain would.
+pub(crate) fn mine(
+	snapshot: &Snapshot,
+	validators: &[AccountId],
+	nominators: &[AccountId],
+	iterations: usize,
+) -> error::Result<Solution> {
+	let count = snapshot.desired_targets as usize;
+	let outcome = election::elect(snapshot, count, Algorithm::SeqPhragmen, iterations, true)
+		.map_err(Error::Election)?;
+
+	let weights =
+		snapshot.voters.iter().map(|(v, w, _)| (v.clone(), *w)).collect::<BTreeMap<_, _>>();
+	let stake_of =
+		|who: &AccountId| -> VoteWeight { weights.get(who).cloned().unwrap_or_default() };
+	let nominator_indices = nominators
+		.iter()
+		.enumerate()
+		.map(|(i, n)| (n.clone(), i as NominatorIndex))
+		.collect::<BTreeMap<_, _>>();
+	let validator_indices = validators
+		.iter()
+		.enumerate()
+		.map(|(i, v)| (v.clone(), i as ValidatorIndex))
+		.collect::<BTreeMap<_, _>>();
+	let nominator_index =
+		|who: &AccountId| -> Option<NominatorIndex> { nominator_indices.get(who).cloned() };
+	let validator_index =
+		|who: &AccountId| -> Option<ValidatorIndex> { validator_indices.get(who).cloned() };
+
+	let election_error = |e: sp_npos_elections::Error| Error::Election(format!("{:?}", e));
+	let ratio =
+		assignment_staked_to_ratio_normalized::<AccountId, OffchainAccuracy>(outcome.assignments)
+			.map_err(election_error)?;
+	let staked =
+		assignment_ratio_to_staked_normalized(ratio.clone(), stake_of).map_err(election_error)?;
+	let score =
+		to_support_map::<AccountId>(&outcome.winners, &staked).map_err(election_error)?.evaluate();
+	let compact = CompactAssignments::from_assignment(ratio, nominator_index, validator_index)
+		.map_err(election_error)?;
+	let winners = outcome
+		.winners
+		.iter()
+		.map(|w| {
+			validator_index(w)
+				.ok_or_else(|| Error::Missing(format!("winner {} in the on-chain snapshot", w)))
+		})
+		.collect::<error::Result<Vec<_>>>()?;
+	let size = ElectionSize {
+		validators: validators.len() as ValidatorIndex,
+		nominators: nominators.len() as NominatorIndex,
+	};
+
+	Ok(Solution { winners, compact, score, size })
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, conf: MineConfig) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	if let Some(Phase::Emergency) = current_phase(client, at).await {
+		log::warn!(
+			target: LOG_TARGET,
+			"🚨 the election is in the emergency phase, see the `emergency` sub-command."
+		);
+	}
+	let snapshot = Snapshot::fetch(client, at).await?;
+	let (snapshot, validators, nominators) = match snapshot_indices(client, at).await {
+		Some((validators, nominators)) => {
+			(restrict(snapshot, &validators, &nominators), validators, nominators)
+		}
+		None => {
+			log::warn!(
+				target: LOG_TARGET,
+				"election window is closed, the solution refers to a scraped snapshot and will be \
+				rejected by the chain."
+			);
+			let nominators = snapshot.voters.iter().map(|(v, _, _)| v.clone()).collect();
+			let validators = snapshot.targets.clone();
+			(snapshot, validators, nominators)
+		}
+	};
+	let iterations = match conf.iterations {
+		Some(iterations) => iterations,
+		None => storage::get_const::<u32>(client, "Staking", "MaxIterations", at)
+			.await
+			.unwrap_or_default() as usize,
+	};
+
+	let solution = mine(&snapshot, &validators, &nominators, iterations)?;
+	let (spec_name, _) = network::get_spec(client, at).await?;
+	let weight = solution.weight(&spec_name);
+	let limit = weight_limit(client, at).await?;
+	println!("⛏  mined a solution with score {:?}", solution.score);
+	println!(
+		"⚖️  weight = {} / limit = {} ({:.2}%)",
+		weight,
+		limit,
+		weight as f64 * 100f64 / limit.max(1) as f64
+	);
+	let length = solution.encoded_length();
+	let max_length = length_limit(client, at).await;
+	println!(
+		"📏 length = {} bytes / limit = {} bytes ({:.2}%)",
+		length,
+		max_length,
+		length as f64 * 100f64 / max_length.max(1) as f64
+	);
+	println!("🚦 priority = {}", solution.priority(conf.unsigned_priority));
+	if length as f64 / max_length.max(1) as f64 > weight as f64 / limit.max(1) as f64 {
+		log::warn!(target: LOG_TARGET, "length, rather than weight, is the binding constraint.");
+	}
+	if length > max_length as usize {
+		log::warn!(target: LOG_TARGET, "solution exceeds the length limit and must be trimmed.");
+	}
+
+	if weight > limit {
+		if conf.force_weight {
+			log::warn!(target: LOG_TARGET, "solution exceeds the unsigned weight limit, forced.");
+		} else {
+			return Err(Error::Invalid(format!(
+				"solution weight {} exceeds the unsigned weight limit {} and would be rejected. \
+				 Use --force-weight to produce it anyway.",
+				weight, limit
+			)));
+		}
+	}
+
+	let (staking_index, submit_index) =
+		error::call_index(client, "Staking", "submit_election_solution_unsigned", at).await?;
+	let era = staking::get_current_era(client, at).await;
+	let mut call = vec![staking_index, submit_index];
+	call.extend(solution.winners.encode());
+	call.extend(solution.compact.encode());
+	call.extend(solution.score.encode());
+	call.extend(era.encode());
+	call.extend(solution.size.encode());
+	println!("📦 call: 0x{}", hex::encode(call));
+	Ok(())
+}
@@ -1,12 +1,80 @@
+/// Accuracy sub-command.
+pub mod accuracy;
+/// Anomaly-scan sub-command.
+pub mod anomaly_scan;
+/// Backtest sub-command.
+pub mod backtest;
+/// Bench sub-command.
+pub mod bench;
+/// Bounds sub-command.
+pub mod bounds;
+/// Churn sub-command.
+pub mod churn;
+/// Clusters sub-command.
+pub mod clusters;
+/// Commission-history sub-command.
+pub mod commission_history;
+/// Controllers sub-command.
+pub mod controllers;
+/// Convert sub-command.
+pub mod convert;
+/// Cross-check sub-command.
+pub mod cross_check;
 /// Current sub-command.
 pub mod current;
 /// Dangling nominators sub-command.
 pub mod dangling_nominators;
 /// Council sub-command.
 pub mod elections_phragmen;
+/// Emergency sub-command.
+pub mod emergency;
+/// Export-rewards sub-command.
+pub mod export_rewards;
+/// Generate sub-command.
+pub mod generate;
+/// Inflation sub-command.
+pub mod inflation;
+/// Ledger sub-command.
+pub mod ledger;
+/// Ledger-audit sub-command.
+pub mod ledger_audit;
+/// Mine sub-command.
+pub mod mine;
+/// Multi-block sub-command.
+pub mod multi_block;
 /// Nominator-check sub-command.
 pub mod nominator_check;
+/// Overlap sub-command.
+pub mod overlap;
+/// Payees sub-command.
+pub mod payees;
+/// Payout-check sub-command.
+pub mod payout_check;
+/// Repl sub-command.
+pub mod repl;
+/// Reward-pot sub-command.
+pub mod reward_pot;
+/// Selftest sub-command.
+pub mod selftest;
+/// Session-info sub-command.
+pub mod session_info;
+/// Simulate-slash sub-command.
+pub mod simulate_slash;
+/// Snapshot sub-command.
+pub mod snapshot;
+/// Stake-flow sub-command.
+pub mod stake_flow;
 /// Staking sub-command.
 pub mod staking;
+/// Stress sub-command.
+pub mod stress;
+/// Suggest sub-command.
+pub mod suggest;
+/// Targets sub-command.
+pub mod targets;
+/// Trace sub-command.
+pub mod trace;
+/// Unbonding sub-command.
+pub mod unbonding;
 /// Validator-check sub-command.
 pub mod validator_check;
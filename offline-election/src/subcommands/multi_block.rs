@@ -0,0 +1,204 @@
+//! Support for the paged snapshots and solutions of `pallet-election-provider-multi-block`.
+//!
+//! The voter snapshot of the multi-block election is split in pages, each of which is solved and
+//! verified in its own block. A solution is thus a set of pages, each containing the assignments
+//! of the voters of the same page of the snapshot, and its score is the score of all the pages
+//! combined.
+//!
+//! The mined solution is for analysis only: it lists the assignments of each page by account, not
+//! as the compact solution of the pallet, and cannot be submitted as is.
+
+use crate::{
+	error::{self, Error},
+	election::{self, Algorithm},
+	primitives::{AccountId, Hash},
+	snapshot::{Snapshot, Voter},
+	storage, Client, MultiBlockConfig, Opt, LOG_TARGET,
+};
+use codec::{Decode, Encode};
+use frame_support::Twox64Concat;
+use sp_npos_elections::*;
+use std::collections::BTreeMap;
+
+/// A snapshot of the multi-block election, as stored on chain.
+#[derive(Debug, Clone)]
+pub(crate) struct PagedSnapshot {
+	/// The round of the election.
+	pub round: u32,
+	/// The number of targets to elect.
+	pub desired_targets: u32,
+	/// All the targets.
+	pub targets: Vec<AccountId>,
+	/// The voters, page by page, from the first page to the last.
+	pub pages: Vec<Vec<Voter>>,
+}
+
+/// Read all the pages of a paged snapshot item, from the first page to the first missing one.
+async fn read_pages<T: Decode>(
+	pallet: &[u8],
+	item: &[u8],
+	round: u32,
+	client: &Client,
+	at: Hash,
+) -> Vec<Vec<T>> {
+	let mut pages = vec![];
+	loop {
+		let page = pages.len() as u32;
+		match storage::read::<Vec<T>>(
+			storage::double_map_key::<Twox64Concat, Twox64Concat>(
+				pallet,
+				item,
+				&round.encode(),
+				&page.encode(),
+			),
+			client,
+			at,
+		)
+		.await
+		{
+			Some(p) => pages.push(p),
+			None => break pages,
+		}
+	}
+}
+
+/// Read the target snapshot of `round`. It has a single page, but not necessarily the first one,
+/// so the keys of the round are scanned rather than guessed.
+async fn read_target_page(pallet: &[u8], round: u32, client: &Client, at: Hash) -> Vec<AccountId> {
+	let prefix = storage::map_key::<Twox64Concat>(pallet, b"PagedTargetSnapshot", &round.encode());
+	let keys = storage::get_keys_paged(prefix, 2, None, client, at).await;
+	if keys.len() > 1 {
+		log::warn!(target: LOG_TARGET, "more than one target page in round {}.", round);
+	}
+	match keys.into_iter().next() {
+		Some(key) => storage::read::<Vec<AccountId>>(key, client, at).await.unwrap_or_default(),
+		None => vec![],
+	}
+}
+
+impl PagedSnapshot {
+	/// Scrape the paged snapshot of the current round of `pallet`.
+	///
+	/// Returns `None` if there is no snapshot, i.e. the election has not started yet.
+	pub(crate) async fn fetch(pallet: &str, client: &Client, at: Hash) -> Option<Self> {
+		let pallet = pallet.as_bytes();
+		let round = storage::read::<u32>(storage::value_key(pallet, b"Round"), client, at)
+			.await
+			.unwrap_or_default();
+		let desired_targets = storage::read::<u32>(
+			storage::map_key::<Twox64Concat>(pallet, b"DesiredTargets", &round.encode()),
+			client,
+			at,
+		)
+		.await?;
+
+		let pages = read_pages::<Voter>(pallet, b"PagedVoterSnapshot", round, client, at).await;
+		let targets = read_target_page(pallet, round, client, at).await;
+		log::info!(
+			target: LOG_TARGET,
+			"paged snapshot of round {}: {} voter pages, {} voters, {} targets",
+			round,
+			pages.len(),
+			pages.iter().map(|p| p.len()).sum::<usize>(),
+			targets.len(),
+		);
+
+		Some(Self { round, desired_targets, targets, pages })
+	}
+
+	/// Flatten all the pages into a single snapshot.
+	pub(crate) fn flatten(&self) -> Snapshot {
+		Snapshot {
+			at: None,
+			desired_targets: self.desired_targets,
+			targets: self.targets.clone(),
+			voters: self.pages.iter().flatten().cloned().collect(),
+		}
+	}
+}
+
+/// A page of a solution.
+#[derive(serde::Serialize, Debug, Clone)]
+pub(crate) struct SolutionPage {
+	/// The index of the page of the snapshot that this page solves.
+	pub page: u32,
+	/// The assignments of the voters of the page, as `(voter, [(target, stake)])`.
+	pub assignments: Vec<(AccountId, Vec<(AccountId, u64)>)>,
+}
+
+/// A paged solution, by account. This is not the encoded submission of the pallet.
+#[derive(serde::Serialize, Debug, Clone)]
+pub(crate) struct PagedSolution {
+	pub round: u32,
+	pub winners: Vec<AccountId>,
+	pub score: ElectionScore,
+	pub pages: Vec<SolutionPage>,
+}
+
+/// Mine a paged solution, by solving all pages at once and splitting the assignments back in
+/// the pages of their voters.
+pub(crate) fn mine(snapshot: &PagedSnapshot, iterations: usize) -> error::Result<PagedSolution> {
+	let flat = snapshot.flatten();
+	let outcome = election::elect(
+		&flat,
+		snapshot.desired_targets as usize,
+		Algorithm::SeqPhragmen,
+		iterations,
+		true,
+	)
+	.map_err(Error::Election)?;
+
+	let page_of = snapshot
+		.pages
+		.iter()
+		.enumerate()
+		.flat_map(|(p, voters)| voters.iter().map(move |(v, _, _)| (v.clone(), p)))
+		.collect::<BTreeMap<_, _>>();
+	let mut pages = (0..snapshot.pages.len())
+		.map(|page| SolutionPage { page: page as u32, assignments: vec![] })
+		.collect::<Vec<_>>();
+	for StakedAssignment { who, distribution } in outcome.assignments {
+		let page = *page_of.get(&who).expect("all voters are in a page");
+		let distribution = distribution.into_iter().map(|(t, s)| (t, s as u64)).collect();
+		pages[page].assignments.push((who, distribution));
+	}
+
+	Ok(PagedSolution {
+		round: snapshot.round,
+		winners: outcome.winners,
+		score: outcome.score,
+		pages,
+	})
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, conf: MultiBlockConfig) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let snapshot = match PagedSnapshot::fetch(&conf.pallet, client, at).await {
+		Some(snapshot) => snapshot,
+		None => {
+			log::warn!(target: LOG_TARGET, "no paged snapshot in {}, try later.", conf.pallet);
+			return Ok(());
+		}
+	};
+
+	if let Some(ref path) = conf.save_snapshot {
+		let mut flat = snapshot.flatten();
+		flat.at = Some(at);
+		flat.save(path)?;
+	}
+
+	let solution = mine(&snapshot, conf.iterations)?;
+	println!("⛏  mined a paged solution for round {}", solution.round);
+	for page in solution.pages.iter() {
+		println!("\tpage {}: {} assignments", page.page, page.assignments.len());
+	}
+	println!("🏆 combined score {:?}", solution.score);
+
+	if let Some(ref path) = conf.output {
+		let file = std::fs::File::create(path).map_err(|e| Error::file(path, e))?;
+		serde_json::to_writer_pretty(file, &solution).map_err(|e| Error::file(path, e))?;
+		println!("📦 paged solution written to {:?}, for analysis only.", path);
+	}
+	Ok(())
+}
@@ -1,11 +1,99 @@
 use crate::{
-	primitives::{AccountId, Balance},
+	error,
+	primitives::{AccountId, Balance, Hash},
 	Client, Currency, Opt, LOG_TARGET,
 };
 use sub_storage::*;
 
+/// A proxy definition, as stored in the proxy pallet.
+///
+/// The proxy type is kept as a raw index, since it is defined by each runtime.
+#[derive(codec::Decode, Clone, Debug)]
+pub(crate) struct ProxyDefinition {
+	pub delegate: AccountId,
+	pub proxy_type: u8,
+	pub delay: crate::primitives::BlockNumber,
+}
+
+impl ProxyDefinition {
+	/// Name of the proxy type, if it is one of the types shared between polkadot and kusama.
+	pub(crate) fn type_name(&self) -> String {
+		match self.proxy_type {
+			0 => "Any".into(),
+			1 => "NonTransfer".into(),
+			2 => "Governance".into(),
+			3 => "Staking".into(),
+			x => format!("Other({})", x),
+		}
+	}
+
+	/// If this proxy can act on the staking of the delegator.
+	pub(crate) fn can_stake(&self) -> bool {
+		matches!(self.proxy_type, 0 | 1 | 3)
+	}
+}
+
+/// Get all the proxies of `who` that can act on its staking.
+pub(crate) async fn staking_proxies_of(
+	who: &AccountId,
+	client: &Client,
+	at: Hash,
+) -> Vec<ProxyDefinition> {
+	read::<(Vec<ProxyDefinition>, Balance)>(
+		map_key::<frame_support::Twox64Concat>(b"Proxy", b"Proxies", who.as_ref()),
+		&client,
+		at,
+	)
+	.await
+	.map(|(proxies, _deposit)| proxies)
+	.unwrap_or_default()
+	.into_iter()
+	.filter(|p| p.can_stake())
+	.collect::<Vec<_>>()
+}
+
+/// Print the staking proxies of `who`.
+pub(crate) async fn print_proxies(who: &AccountId, client: &Client, at: Hash) {
+	let proxies = staking_proxies_of(who, client, at).await;
+	if proxies.is_empty() {
+		println!("🔐 No staking proxies.");
+	} else {
+		println!("🔐 Staking proxies:");
+		for p in proxies {
+			println!("\t{:?} / type: {} / delay: {}", p.delegate, p.type_name(), p.delay);
+		}
+	}
+}
+
+/// Print the rewards of `who` as a nominator in each of the last `eras` eras, from the exposures,
+/// era points and commissions of its validators.
+async fn print_reward_history(who: &AccountId, eras: u32, client: &Client, at: Hash) {
+	use crate::subcommands::{backtest, commission_history, session_info};
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let depth = commission_history::history_depth(client, at).await;
+	let first_era = active_era.saturating_sub(eras.min(depth));
+
+	println!("💸 Rewards of the last {} eras:", active_era - first_era);
+	let mut total: Balance = 0;
+	let mut idle = 0;
+	// the rewards of the active era are not known yet.
+	for era in first_era..active_era {
+		let rewards = backtest::nominator_rewards(who, era, client, at).await;
+		let reward = rewards.iter().map(|(_, r)| r).sum::<Balance>();
+		if reward == 0 {
+			idle += 1;
+		}
+		println!("\tera {}: {:?} from {} validators", era, Currency::from(reward), rewards.len());
+		total += reward;
+	}
+	println!("\ttotal: {:?}", Currency::from(total));
+	if idle > 0 {
+		log::warn!(target: LOG_TARGET, "{} of the eras earned no rewards.", idle);
+	}
+}
+
 /// Main run function of the sub-command.
-pub async fn run(client: &Client, opt: Opt, who: AccountId) {
+pub async fn run(client: &Client, opt: Opt, who: AccountId, eras: u32) -> error::Result<()> {
 	let at = opt.at.unwrap();
 	let maybe_nomination = read::<pallet_staking::Nominations<AccountId>>(
 		map_key::<frame_support::Twox64Concat>(b"Staking", b"Nominators", who.as_ref()),
@@ -16,7 +104,7 @@ pub async fn run(client: &Client, opt: Opt, who: AccountId) {
 
 	if maybe_nomination.is_none() {
 		log::warn!("{:?} is not a nominator.", who);
-		return;
+		return Ok(());
 	}
 
 	let ctrl = read::<AccountId>(
@@ -61,6 +149,11 @@ pub async fn run(client: &Client, opt: Opt, who: AccountId) {
 		}
 	}
 
+	print_proxies(&who, client, at).await;
+	println!(
+		"🎯 Reward destination: {:?}",
+		crate::subcommands::staking::payee_of(&who, client, at).await
+	);
 	println!("📅 Submitted in era {}", nomination.submitted_in);
 	println!("📣 Votes:");
 	let mut active_bonded: Balance = 0;
@@ -79,9 +172,26 @@ pub async fn run(client: &Client, opt: Opt, who: AccountId) {
 		}
 	}
 
+	let shared =
+		crate::subcommands::suggest::shared_operators(&nomination.targets, client, at).await;
+	for (operator, validators) in shared.iter() {
+		log::warn!(
+			target: LOG_TARGET,
+			"{} of the targets are operated by {:?}: {:?}",
+			validators.len(),
+			operator,
+			validators
+		);
+	}
+
 	println!(
 		"💰 total bonded = {:?} // Active = {:?}",
 		Currency::from(total_bonded),
 		Currency::from(active_bonded)
 	);
+
+	if eras > 0 {
+		print_reward_history(&who, eras, client, at).await;
+	}
+	Ok(())
 }
@@ -0,0 +1,111 @@
+//! Report the pairs of validators that share many of their nominators.
+//!
+//! A nominator backing two validators that are slashed together is slashed twice. The overlap of
+//! a pair is the stake that the shared nominators put behind the less backed validator of the
+//! two, relative to the total backing of that validator.
+
+use crate::{
+	error, network,
+	primitives::{AccountId, Balance},
+	subcommands::{clusters, session_info, staking},
+	Client, Currency, Opt,
+};
+use sp_runtime::traits::Convert;
+use std::collections::BTreeMap;
+
+/// The backing of each validator, from nominator to stake. Self-stake is excluded.
+type Backings = BTreeMap<AccountId, BTreeMap<AccountId, Balance>>;
+
+/// The overlap of a pair of validators.
+#[derive(Debug, Clone)]
+struct Overlap {
+	pair: (AccountId, AccountId),
+	shared_nominators: usize,
+	shared_stake: Balance,
+	share: f64,
+}
+
+/// Compute the overlap of all the pairs of validators that share at least one nominator.
+fn overlaps(backings: &Backings) -> Vec<Overlap> {
+	let validators = backings.iter().collect::<Vec<_>>();
+	let mut overlaps = vec![];
+	for (i, (a, backing_a)) in validators.iter().enumerate() {
+		for (b, backing_b) in validators.iter().skip(i + 1) {
+			let (total_a, total_b) =
+				(backing_a.values().sum::<Balance>(), backing_b.values().sum::<Balance>());
+			let (smaller, other, total) = if total_a <= total_b {
+				(backing_a, backing_b, total_a)
+			} else {
+				(backing_b, backing_a, total_b)
+			};
+
+			let shared = smaller.iter().filter(|(n, _)| other.contains_key(*n)).collect::<Vec<_>>();
+			if shared.is_empty() {
+				continue;
+			}
+			let shared_stake = shared.iter().map(|(_, s)| **s).sum::<Balance>();
+			overlaps.push(Overlap {
+				pair: ((*a).clone(), (*b).clone()),
+				shared_nominators: shared.len(),
+				shared_stake,
+				share: shared_stake as f64 / total.max(1) as f64,
+			});
+		}
+	}
+	overlaps.sort_by(|x, y| y.share.partial_cmp(&x.share).unwrap_or(std::cmp::Ordering::Equal));
+	overlaps
+}
+
+/// Main run function of the sub-command.
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	predicted: bool,
+	threshold: f64,
+	top: usize,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let mut backings = Backings::new();
+	if predicted {
+		let (_, supports) = staking::predict(client, at).await?;
+		for (v, support) in supports.into_iter() {
+			let backing = backings.entry(v.clone()).or_default();
+			for (n, weight) in support.voters.into_iter().filter(|(n, _)| n != &v) {
+				let stake =
+					<network::CurrencyToVoteHandler as Convert<u128, u128>>::convert(weight);
+				backing.insert(n, stake);
+			}
+		}
+		println!("🔗 nominator overlap of the predicted set");
+	} else {
+		let era = session_info::get_active_era(client, at).await.index;
+		for (v, exposure) in clusters::exposures_at(era, client, at).await {
+			let backing = backings.entry(v).or_default();
+			for other in exposure.others {
+				backing.insert(other.who, other.value);
+			}
+		}
+		println!("🔗 nominator overlap of the active set of era {}", era);
+	}
+
+	let overlaps = overlaps(&backings);
+	let correlated = overlaps.iter().filter(|o| o.share >= threshold).count();
+	println!(
+		"\t{} pairs share nominators, {} of which share at least {:.0}% of their backing.",
+		overlaps.len(),
+		correlated,
+		threshold * 100.0
+	);
+	for o in overlaps.iter().take(top) {
+		println!(
+			"\t{} {:?} & {:?}: {} nominators / {:?} ({:.2}%)",
+			if o.share >= threshold { "⚠️ " } else { "  " },
+			o.pair.0,
+			o.pair.1,
+			o.shared_nominators,
+			Currency::from(o.shared_stake),
+			o.share * 100.0,
+		);
+	}
+	Ok(())
+}
@@ -0,0 +1,77 @@
+//! Report the distribution of reward destinations across all stakers.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance},
+	storage, Client, Currency, Opt, LOG_TARGET,
+};
+use pallet_staking::{RewardDestination, StakingLedger};
+use std::collections::BTreeMap;
+
+/// The kind of a reward destination, without the account of `RewardDestination::Account`.
+pub(crate) fn kind_of(payee: &RewardDestination<AccountId>) -> &'static str {
+	match payee {
+		RewardDestination::Staked => "Staked",
+		RewardDestination::Stash => "Stash",
+		RewardDestination::Controller => "Controller",
+		RewardDestination::Account(_) => "Account",
+		RewardDestination::None => "None",
+	}
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let payees = storage::enumerate_map::<AccountId, RewardDestination<AccountId>>(
+		b"Staking", b"Payee", client, at,
+	)
+	.await
+	.expect("Staking::payee should be enumerable.");
+	let bonded = storage::enumerate_map::<AccountId, AccountId>(b"Staking", b"Bonded", client, at)
+		.await
+		.expect("Staking::bonded should be enumerable.")
+		.into_iter()
+		.collect::<BTreeMap<AccountId, AccountId>>();
+	let ledgers = storage::enumerate_map::<AccountId, StakingLedger<AccountId, Balance>>(
+		b"Staking", b"Ledger", client, at,
+	)
+	.await
+	.expect("Staking::ledger should be enumerable.")
+	.into_iter()
+	.collect::<BTreeMap<AccountId, StakingLedger<AccountId, Balance>>>();
+
+	log::info!(target: LOG_TARGET, "aggregating {} payees.", payees.len());
+
+	// kind -> (count, total active stake)
+	let mut distribution: BTreeMap<&'static str, (usize, Balance)> = BTreeMap::new();
+	for (stash, payee) in payees.iter() {
+		let active = bonded
+			.get(stash)
+			.and_then(|ctrl| ledgers.get(ctrl))
+			.map(|l| l.active)
+			.unwrap_or_default();
+		let entry = distribution.entry(kind_of(payee)).or_default();
+		entry.0 += 1;
+		entry.1 += active;
+	}
+
+	let total_count = payees.len().max(1);
+	let total_stake = distribution.values().map(|(_, s)| s).sum::<Balance>().max(1);
+	println!("🎯 Reward destinations of {} stakers:", payees.len());
+	for (kind, (count, stake)) in distribution.iter() {
+		println!(
+			"\t{:<12} {:>7} stakers ({:>6.2}%) / active stake {:?} ({:>6.2}%)",
+			kind,
+			count,
+			*count as f64 * 100f64 / total_count as f64,
+			Currency::from(*stake),
+			*stake as f64 * 100f64 / total_stake as f64,
+		);
+	}
+	let compounding = distribution.get("Staked").map(|(c, _)| *c).unwrap_or_default();
+	println!(
+		"🔁 {:.2}% of the stakers are compounding their rewards.",
+		compounding as f64 * 100f64 / total_count as f64
+	);
+	Ok(())
+}
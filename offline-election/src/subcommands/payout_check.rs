@@ -0,0 +1,195 @@
+//! List the eras in which the payout of a validator has not been claimed yet.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance, Hash},
+	storage,
+	subcommands::{commission_history, mine, reward_pot, session_info, staking},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use codec::Encode;
+use frame_support::weights::Weight;
+use pallet_staking::{EraIndex, EraRewardPoints, WeightInfo};
+use sp_runtime::Perbill;
+
+/// Get the reward points of an era.
+pub(crate) async fn reward_points_of(
+	era: EraIndex,
+	client: &Client,
+	at: Hash,
+) -> EraRewardPoints<AccountId> {
+	storage::read::<EraRewardPoints<AccountId>>(
+		storage::map_key::<frame_support::Twox64Concat>(
+			b"Staking",
+			b"ErasRewardPoints",
+			era.encode().as_ref(),
+		),
+		client,
+		at,
+	)
+	.await
+	.unwrap_or_default()
+}
+
+/// An estimated payout of a validator in an era.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Payout {
+	/// The total payout of the validator and its nominators.
+	pub total: Balance,
+	/// The share of the validator itself, commission included.
+	pub validator: Balance,
+}
+
+/// Estimate the payout of `stash` in `era`, as done by `payout_stakers`.
+pub(crate) async fn estimate_payout(
+	stash: &AccountId,
+	era: EraIndex,
+	client: &Client,
+	at: Hash,
+) -> Payout {
+	let era_reward = reward_pot::era_reward_of(era, client, at).await.unwrap_or_default();
+	let points = reward_points_of(era, client, at).await;
+	let own_points = points.individual.get(stash).cloned().unwrap_or_default();
+	let total = Perbill::from_rational_approximation(own_points, points.total.max(1)) * era_reward;
+
+	let commission =
+		commission_history::commission_at(stash, era, client, at).await.unwrap_or_default();
+	let exposure = staking::exposure_of(stash, era, client, at).await;
+	let commission_payout = commission * total;
+	let own_share = Perbill::from_rational_approximation(exposure.own, exposure.total.max(1))
+		* (total - commission_payout);
+	Payout { total, validator: commission_payout + own_share }
+}
+
+/// Build the hex-encoded `utility.batch` calls of `staking.payout_stakers` for all the given
+/// eras, each with the number of nominators of `who` in it.
+///
+/// Each batch takes at most `chunk_size` eras, and no more than the weight of a normal extrinsic
+/// as estimated by `payout_stakers_alive_staked`.
+pub(crate) async fn payout_calls(
+	who: &AccountId,
+	eras: &[(EraIndex, u32)],
+	chunk_size: usize,
+	client: &Client,
+	at: Hash,
+) -> error::Result<Vec<String>> {
+	let (staking_index, payout_index) =
+		error::call_index(client, "Staking", "payout_stakers", at).await?;
+	let (utility_index, batch_index) = error::call_index(client, "Utility", "batch", at).await?;
+	let max_weight = mine::max_extrinsic(client, at).await?;
+	let max_rewarded =
+		storage::get_const::<u32>(client, "Staking", "MaxNominatorRewardedPerValidator", at)
+			.await
+			.unwrap_or(64);
+
+	let mut chunks: Vec<(Vec<EraIndex>, Weight)> = vec![];
+	for (era, nominators) in eras.iter() {
+		let weight =
+			<() as WeightInfo>::payout_stakers_alive_staked((*nominators).min(max_rewarded));
+		if weight > max_weight {
+			log::warn!(
+				target: LOG_TARGET,
+				"the payout of era {} weighs {}, more than the maximum of {}.",
+				era,
+				weight,
+				max_weight
+			);
+		}
+		match chunks.last_mut() {
+			Some((chunk, total))
+				if chunk.len() < chunk_size.max(1)
+					&& total.saturating_add(weight) <= max_weight =>
+			{
+				chunk.push(*era);
+				*total += weight;
+			}
+			_ => chunks.push((vec![*era], weight)),
+		}
+	}
+
+	Ok(chunks
+		.into_iter()
+		.map(|(chunk, _)| {
+			let mut batch = vec![utility_index, batch_index];
+			batch.extend(codec::Compact(chunk.len() as u32).encode());
+			for era in chunk {
+				batch.extend(&[staking_index, payout_index]);
+				batch.extend(who.encode());
+				batch.extend(era.encode());
+			}
+			format!("0x{}", hex::encode(batch))
+		})
+		.collect::<Vec<_>>())
+}
+
+/// Main run function of the sub-command.
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	who: AccountId,
+	call: bool,
+	chunk_size: usize,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let ledger = match staking::controller_of(&who, client, at).await {
+		Some(ctrl) => staking::ledger_of(&ctrl, client, at)
+			.await
+			.ok_or_else(|| error::Error::Missing(format!("ledger of {:?}", ctrl)))?,
+		None => {
+			log::warn!("{:?} is not a bonded stash.", who);
+			return Ok(());
+		}
+	};
+
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let depth = commission_history::history_depth(client, at).await;
+	let first_era = active_era.saturating_sub(depth);
+	log::info!(target: LOG_TARGET, "checking eras {}..{}", first_era, active_era);
+
+	let mut unclaimed = vec![];
+	let mut unclaimed_total: Balance = 0;
+	let mut unclaimed_own: Balance = 0;
+	for era in first_era..active_era {
+		if ledger.claimed_rewards.contains(&era) {
+			continue;
+		}
+		let exposure = staking::exposure_of(&who, era, client, at).await;
+		if exposure.total == 0 {
+			continue;
+		}
+		let payout = estimate_payout(&who, era, client, at).await;
+		if payout.total == 0 {
+			continue;
+		}
+		unclaimed.push((era, exposure.others.len() as u32));
+		unclaimed_total += payout.total;
+		unclaimed_own += payout.validator;
+		println!(
+			"\t❌ era {}: ~{:?} in total / ~{:?} for the validator",
+			era,
+			Currency::from(payout.total),
+			Currency::from(payout.validator)
+		);
+		if era < first_era + 2 {
+			log::warn!("era {} is about to fall out of the history depth.", era);
+		}
+	}
+
+	if unclaimed.is_empty() {
+		println!("✅ All payouts since era {} are claimed.", first_era);
+	} else {
+		println!(
+			"💸 {} unclaimed eras / ~{:?} in total / ~{:?} for the validator",
+			unclaimed.len(),
+			Currency::from(unclaimed_total),
+			Currency::from(unclaimed_own)
+		);
+		if call {
+			println!("📝 Call data, to be signed and submitted by any account:");
+			for call in payout_calls(&who, &unclaimed, chunk_size, client, at).await? {
+				println!("{}", call);
+			}
+		}
+	}
+	Ok(())
+}
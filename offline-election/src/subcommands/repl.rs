@@ -0,0 +1,95 @@
+//! An interactive session on a single connection.
+//!
+//! The snapshot is scraped once at the start, and kept in memory. Each line is then parsed as a
+//! sub-command, with the same syntax as on the command line, and run at the same block. The
+//! sub-commands that need the snapshot are served from memory, the others read the chain as
+//! usual. Arguments are split on whitespace, and quoting is not supported.
+
+use crate::{error, snapshot::Snapshot, Client, Opt, SubCommands};
+use std::{
+	future::Future,
+	io::{BufRead, Write},
+	pin::Pin,
+};
+use structopt::StructOpt;
+
+/// A line of the session.
+#[derive(Debug, StructOpt)]
+#[structopt(name = ">", no_version, setting = structopt::clap::AppSettings::NoBinaryName)]
+struct Line {
+	#[structopt(subcommand)]
+	cmd: SubCommands,
+}
+
+/// Why `cmd` cannot run in the repl, if it cannot. The sub-commands that run without a node are
+/// handled before connecting, and are left to the command line.
+fn unsupported(cmd: &SubCommands) -> Option<&'static str> {
+	match cmd {
+		SubCommands::Repl {} => Some("already in a repl."),
+		SubCommands::Bench(_)
+		| SubCommands::Generate(_)
+		| SubCommands::Selftest {}
+		| SubCommands::Snapshot(_)
+		| SubCommands::Stress(_)
+		| SubCommands::Trace(_)
+		| SubCommands::Convert { .. }
+		| SubCommands::AnomalyScan { input: Some(_), .. } => {
+			Some("runs without a node, use it outside of the repl.")
+		}
+		SubCommands::Staking(conf) if conf.offline || conf.schema => {
+			Some("runs without a node, use it outside of the repl.")
+		}
+		_ => None,
+	}
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let snapshot = Snapshot::fetch(client, at).await?;
+	println!(
+		"🐚 snapshot at {:?} with {} voters and {} targets kept in memory. Type `help` for the \
+		 sub-commands, `exit` to quit.",
+		at,
+		snapshot.voters.len(),
+		snapshot.targets.len()
+	);
+
+	let stdin = std::io::stdin();
+	let mut lines = stdin.lock().lines();
+	loop {
+		print!("> ");
+		std::io::stdout().flush().expect("Failed to flush stdout.");
+		let line = match lines.next() {
+			Some(line) => line.expect("Failed to read stdin."),
+			None => break,
+		};
+		let words = line.split_whitespace().collect::<Vec<_>>();
+		match words.as_slice() {
+			[] => continue,
+			["exit"] | ["quit"] => break,
+			_ => {}
+		}
+
+		let cmd = match Line::from_iter_safe(words) {
+			Ok(Line { cmd }) => cmd,
+			Err(e) => {
+				// also the way `help` and `--help` are printed.
+				println!("{}", e.message);
+				continue;
+			}
+		};
+		if let Some(reason) = unsupported(&cmd) {
+			println!("{}", reason);
+			continue;
+		}
+
+		// `run` dispatches to this function, hence the boxing.
+		let command: Pin<Box<dyn Future<Output = error::Result<()>> + '_>> =
+			Box::pin(crate::run(client, Opt { cmd, ..opt.clone() }));
+		if let Err(e) = command.await {
+			println!("❌ {}", e);
+		}
+	}
+	Ok(())
+}
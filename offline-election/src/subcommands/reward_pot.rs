@@ -0,0 +1,74 @@
+//! Estimate the reward pot of the current era.
+
+use crate::{
+	error,
+	primitives::{Balance, Hash},
+	storage,
+	subcommands::{inflation, session_info},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use codec::Encode;
+use pallet_staking::EraIndex;
+
+/// Get the actual reward of the validators of a finished era, if any.
+pub(crate) async fn era_reward_of(era: EraIndex, client: &Client, at: Hash) -> Option<Balance> {
+	storage::read::<Balance>(
+		storage::map_key::<frame_support::Twox64Concat>(
+			b"Staking",
+			b"ErasValidatorReward",
+			era.encode().as_ref(),
+		),
+		client,
+		at,
+	)
+	.await
+}
+
+/// The estimated reward pot of an era.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RewardPot {
+	/// The amount given to the validators and their nominators.
+	pub validators: Balance,
+	/// The amount given to the treasury.
+	pub treasury: Balance,
+}
+
+/// Estimate the reward pot of the active era.
+pub(crate) async fn estimate(client: &Client, at: Hash) -> RewardPot {
+	let model = inflation::model(client, at).await;
+	RewardPot { validators: model.era_payout, treasury: model.era_remainder() }
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let pot = estimate(client, at).await;
+
+	println!("🎁 Estimated reward pot of era {}:", active_era);
+	println!("\tvalidators and nominators: {:?}", Currency::from(pot.validators));
+	println!("\ttreasury: {:?}", Currency::from(pot.treasury));
+
+	if let Some(previous) = active_era.checked_sub(1) {
+		match era_reward_of(previous, client, at).await {
+			Some(actual) => {
+				let error = (pot.validators as f64 - actual as f64) * 100f64 / actual.max(1) as f64;
+				println!(
+					"🔎 Actual reward of era {}: {:?} (estimate is off by {:.2}%)",
+					previous,
+					Currency::from(actual),
+					error
+				);
+				if error.abs() > 5f64 {
+					log::warn!(
+						target: LOG_TARGET,
+						"estimate is off by more than 5%. The previous era probably did not have \
+						the expected duration."
+					);
+				}
+			}
+			None => println!("🔎 No reward recorded for era {}.", previous),
+		}
+	}
+	Ok(())
+}
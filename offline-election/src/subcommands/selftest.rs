@@ -0,0 +1,77 @@
+//! Run the election on a set of known-good snapshots bundled in the binary.
+
+use crate::{
+	election::{self, Algorithm},
+	primitives::AccountId,
+	snapshot::Snapshot,
+};
+use sp_npos_elections::ElectionScore;
+
+/// The bundled fixtures.
+const FIXTURES: &[&str] = &[
+	include_str!("../../fixtures/selftest/single_votes.json"),
+	include_str!("../../fixtures/selftest/single_votes_phragmms.json"),
+	include_str!("../../fixtures/selftest/balanced_split.json"),
+];
+
+#[derive(serde::Deserialize)]
+struct Expected {
+	winners: Vec<AccountId>,
+	score: ElectionScore,
+}
+
+#[derive(serde::Deserialize)]
+struct Fixture {
+	name: String,
+	algorithm: String,
+	iterations: usize,
+	reduce: bool,
+	snapshot: Snapshot,
+	expected: Expected,
+}
+
+/// Run a single fixture, returning an error describing the mismatch, if any.
+fn check(fixture: &Fixture) -> Result<(), String> {
+	let algorithm = fixture.algorithm.parse::<Algorithm>()?;
+	let count = fixture.snapshot.desired_targets as usize;
+	let outcome =
+		election::elect(&fixture.snapshot, count, algorithm, fixture.iterations, fixture.reduce)?;
+	if outcome.winners != fixture.expected.winners {
+		return Err(format!(
+			"winners {:?}, expected {:?}",
+			outcome.winners, fixture.expected.winners
+		));
+	}
+	if outcome.score != fixture.expected.score {
+		return Err(format!("score {:?}, expected {:?}", outcome.score, fixture.expected.score));
+	}
+	Ok(())
+}
+
+/// Main run function of the sub-command.
+///
+/// Note that this sub-command does not need a connection to any node. Exits with a non-zero code
+/// if any of the fixtures fails.
+pub fn run() {
+	let mut failed = 0;
+	for raw in FIXTURES {
+		let fixture: Fixture = serde_json::from_str(raw).expect("Bundled fixtures are valid.");
+		match check(&fixture) {
+			Ok(_) => println!("✅ {}", fixture.name),
+			Err(why) => {
+				failed += 1;
+				println!("❌ {}: {}", fixture.name, why);
+			}
+		}
+	}
+
+	if failed > 0 {
+		println!(
+			"{}/{} fixtures failed. Do not trust the results of this build.",
+			failed,
+			FIXTURES.len()
+		);
+		std::process::exit(1);
+	}
+	println!("All {} fixtures passed.", FIXTURES.len());
+}
@@ -0,0 +1,102 @@
+//! Display the timing context of the staking system: eras, sessions and epochs.
+
+use crate::{error, primitives::Hash, storage, subcommands, Client, Opt};
+use codec::Encode;
+use pallet_staking::{ActiveEraInfo, EraIndex};
+
+async fn read_value<T: codec::Decode>(
+	module: &[u8],
+	item: &[u8],
+	client: &Client,
+	at: Hash,
+) -> Option<T> {
+	storage::read::<T>(storage::value_key(module, item), client, at).await
+}
+
+/// Get the active era info.
+pub(crate) async fn get_active_era(client: &Client, at: Hash) -> ActiveEraInfo {
+	read_value::<ActiveEraInfo>(b"Staking", b"ActiveEra", client, at)
+		.await
+		.expect("ActiveEra must exist")
+}
+
+/// Get the first session index of the given era.
+pub(crate) async fn era_start_session(era: EraIndex, client: &Client, at: Hash) -> Option<u32> {
+	storage::read::<u32>(
+		storage::map_key::<frame_support::Twox64Concat>(
+			b"Staking",
+			b"ErasStartSessionIndex",
+			era.encode().as_ref(),
+		),
+		client,
+		at,
+	)
+	.await
+}
+
+/// Get the expected block time of the chain, in milliseconds.
+pub(crate) async fn expected_block_time(client: &Client, at: Hash) -> u64 {
+	storage::get_const::<u64>(client, "Babe", "ExpectedBlockTime", at).await.unwrap_or(6000)
+}
+
+/// Get the approximate duration of an era, in milliseconds.
+pub(crate) async fn era_duration(client: &Client, at: Hash) -> u64 {
+	let sessions_per_era =
+		storage::get_const::<u32>(client, "Staking", "SessionsPerEra", at).await.unwrap_or(6);
+	let epoch_duration =
+		storage::get_const::<u64>(client, "Babe", "EpochDuration", at).await.unwrap_or(2400);
+	sessions_per_era as u64 * epoch_duration * expected_block_time(client, at).await
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let active_era = get_active_era(client, at).await;
+	let current_era = subcommands::staking::get_current_era(client, at).await;
+	let session = read_value::<u32>(b"Session", b"CurrentIndex", client, at)
+		.await
+		.expect("CurrentIndex must exist");
+	let sessions_per_era =
+		storage::get_const::<u32>(client, "Staking", "SessionsPerEra", at).await.unwrap_or(6);
+	let era_start = era_start_session(active_era.index, client, at).await.unwrap_or(session);
+	let sessions_elapsed = session.saturating_sub(era_start);
+
+	let epoch_duration =
+		storage::get_const::<u64>(client, "Babe", "EpochDuration", at).await.unwrap_or(2400);
+	let block_time = expected_block_time(client, at).await;
+	let epoch_index = read_value::<u64>(b"Babe", b"EpochIndex", client, at).await.unwrap_or(0);
+	let genesis_slot = read_value::<u64>(b"Babe", b"GenesisSlot", client, at).await.unwrap_or(0);
+	let current_slot = read_value::<u64>(b"Babe", b"CurrentSlot", client, at).await.unwrap_or(0);
+	let epoch_start = genesis_slot + epoch_index * epoch_duration;
+	let epoch_progress = current_slot.saturating_sub(epoch_start);
+
+	println!("📅 Active era: {}", active_era.index);
+	if let Some(start) = active_era.start {
+		println!("\tstarted at timestamp {}", start);
+	}
+	println!("📅 Current era: {}", current_era);
+	if current_era > active_era.index {
+		println!("\t(the election for the next era is already done)");
+	}
+	println!("🔁 Session index: {}", session);
+	println!("\tera progress: {}/{} sessions", sessions_elapsed + 1, sessions_per_era);
+	println!(
+		"⏱  Epoch {}: {}/{} slots ({:.2}%)",
+		epoch_index,
+		epoch_progress,
+		epoch_duration,
+		epoch_progress as f64 * 100f64 / epoch_duration as f64,
+	);
+
+	let remaining_sessions = sessions_per_era.saturating_sub(sessions_elapsed + 1) as u64;
+	let remaining_slots =
+		remaining_sessions * epoch_duration + epoch_duration.saturating_sub(epoch_progress);
+	let remaining_ms = remaining_slots * block_time;
+	println!(
+		"⏳ Next era in ~{} slots, approximately {}h {}m.",
+		remaining_slots,
+		remaining_ms / 1000 / 3600,
+		remaining_ms / 1000 % 3600 / 60,
+	);
+	Ok(())
+}
@@ -0,0 +1,89 @@
+//! Simulate slashing a validator of the active era.
+//!
+//! The slash of each staker is the fraction of what it exposed to the validator in the active era,
+//! as the staking pallet computes it. Earlier slashes in the same slashing span, which would
+//! reduce the amount actually slashed, are not accounted for.
+
+use crate::{
+	error,
+	election::{self, Algorithm},
+	primitives::{AccountId, Balance},
+	snapshot::Snapshot,
+	subcommands::{session_info, staking},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use sp_runtime::Perbill;
+
+/// Main run function of the sub-command.
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	who: AccountId,
+	fraction: Perbill,
+	iterations: usize,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let era = session_info::get_active_era(client, at).await.index;
+	let exposure = staking::exposure_of(&who, era, client, at).await;
+	if exposure.total == 0 {
+		log::warn!(target: LOG_TARGET, "{:?} is not exposed in the active era {}.", who, era);
+		return Ok(());
+	}
+
+	let mut others = exposure.others.clone();
+	others.sort_by_key(|o| std::cmp::Reverse(o.value));
+	let mut slashes = vec![(who.clone(), fraction * exposure.own)];
+	slashes.extend(others.iter().map(|o| (o.who.clone(), fraction * o.value)));
+
+	println!("🔪 slashing {:?} of {:?} in era {}", fraction, who, era);
+	println!(
+		"\tvalidator: {:?} of {:?}",
+		Currency::from(slashes_of(&slashes, &who)),
+		Currency::from(exposure.own)
+	);
+	for other in others.iter() {
+		println!(
+			"\tnominator {:?}: {:?} of {:?}",
+			other.who,
+			Currency::from(slashes_of(&slashes, &other.who)),
+			Currency::from(other.value)
+		);
+	}
+	let total = slashes.iter().map(|(_, s)| s).sum::<Balance>();
+	println!("\ttotal: {:?} from {} stakers", Currency::from(total), slashes.len());
+
+	// the slashed validator is chilled, and the nominations to it are ignored until renewed.
+	let snapshot = Snapshot::fetch(client, at).await?;
+	let count = snapshot.desired_targets as usize;
+	let mut slashed = snapshot.clone();
+	slashed.targets.retain(|t| t != &who);
+	slashed.voters = slashed
+		.voters
+		.into_iter()
+		.filter(|(v, _, _)| v != &who)
+		.map(|(v, w, t)| {
+			let slash = staking::to_vote_weight(slashes_of(&slashes, &v));
+			(v, w.saturating_sub(slash), t.into_iter().filter(|t| t != &who).collect::<Vec<_>>())
+		})
+		.filter(|(_, _, t)| !t.is_empty())
+		.collect();
+
+	let elect = |s: &Snapshot| {
+		election::elect(s, count, Algorithm::SeqPhragmen, iterations, false)
+			.expect("Phragmen failed to elect.")
+	};
+	let (before, after) = (elect(&snapshot), elect(&slashed));
+	println!("🗳  next election, {} seats", count);
+	println!("\tscore: {:?} -> {:?}", before.score, after.score);
+	for w in before.winners.iter().filter(|w| !after.winners.contains(w)) {
+		println!("\t- {:?}", w);
+	}
+	for w in after.winners.iter().filter(|w| !before.winners.contains(w)) {
+		println!("\t+ {:?}", w);
+	}
+	Ok(())
+}
+
+fn slashes_of(slashes: &[(AccountId, Balance)], who: &AccountId) -> Balance {
+	slashes.iter().filter(|(s, _)| s == who).map(|(_, s)| s).sum()
+}
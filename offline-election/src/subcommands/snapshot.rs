@@ -0,0 +1,464 @@
+//! Tools to work with snapshot files.
+
+use crate::{
+	election::{self, Algorithm},
+	error::{self, Error},
+	primitives::{blake2_256, AccountId},
+	snapshot::Snapshot,
+	SnapshotCommand, LOG_TARGET,
+};
+use codec::Encode;
+use sp_npos_elections::ElectionScore;
+use std::{
+	io::Write,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+
+/// A property of a snapshot that must be preserved while shrinking it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Predicate {
+	/// seq-phragmen and phragmms elect different sets of winners.
+	AlgorithmsDisagree,
+	/// The score of seq-phragmen is different from the given one, e.g. the one computed on chain.
+	ScoreMismatch(ElectionScore),
+	/// The election fails, either by returning an error or by panicking.
+	Fails,
+}
+
+impl FromStr for Predicate {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		const ERR: &str =
+			"Invalid predicate. Can be algorithms-disagree|fails|score-mismatch:<min>,<sum>,<sum_sq>.";
+		match s {
+			"algorithms-disagree" => Ok(Self::AlgorithmsDisagree),
+			"fails" => Ok(Self::Fails),
+			_ if s.starts_with("score-mismatch:") => {
+				let score = s["score-mismatch:".len()..]
+					.split(',')
+					.map(|x| x.trim().parse::<u128>().map_err(|_| ERR))
+					.collect::<Result<Vec<_>, _>>()?;
+				if score.len() != 3 {
+					return Err(ERR);
+				}
+				Ok(Self::ScoreMismatch([score[0], score[1], score[2]]))
+			}
+			_ => Err(ERR),
+		}
+	}
+}
+
+impl Predicate {
+	/// Check if the predicate holds for the given snapshot.
+	fn holds(&self, snapshot: &Snapshot, iterations: usize) -> bool {
+		let count = snapshot.desired_targets as usize;
+		match self {
+			Self::AlgorithmsDisagree => {
+				let winners_of = |algorithm| {
+					election::elect(snapshot, count, algorithm, iterations, false).ok().map(|o| {
+						let mut winners = o.winners;
+						winners.sort();
+						winners
+					})
+				};
+				winners_of(Algorithm::SeqPhragmen) != winners_of(Algorithm::Phragmms)
+			}
+			Self::ScoreMismatch(expected) => {
+				election::elect(snapshot, count, Algorithm::SeqPhragmen, iterations, false)
+					.map_or(false, |o| &o.score != expected)
+			}
+			Self::Fails => std::panic::catch_unwind(|| {
+				election::elect(snapshot, count, Algorithm::SeqPhragmen, iterations, false)
+			})
+			.map_or(true, |r| r.is_err()),
+		}
+	}
+}
+
+/// Greedily remove chunks of `items` while `keep` holds for what remains, halving the size of the
+/// chunks whenever no chunk can be removed.
+fn shrink_items<T: Clone>(mut items: Vec<T>, keep: impl Fn(&[T]) -> bool) -> Vec<T> {
+	let mut chunk = (items.len() / 2).max(1);
+	loop {
+		let mut progressed = false;
+		let mut i = 0;
+		while i < items.len() {
+			let end = (i + chunk).min(items.len());
+			let candidate = [&items[..i], &items[end..]].concat();
+			if keep(&candidate) {
+				items = candidate;
+				progressed = true;
+			} else {
+				i = end;
+			}
+		}
+
+		if !progressed {
+			if chunk == 1 {
+				break;
+			}
+			chunk /= 2;
+		}
+	}
+	items
+}
+
+/// Remove the given targets from a snapshot, along with their self-votes and any vote for them.
+fn with_targets(snapshot: &Snapshot, targets: &[AccountId]) -> Snapshot {
+	let voters = snapshot
+		.voters
+		.iter()
+		.filter(|(v, _, t)| !(t == &vec![v.clone()] && !targets.contains(v)))
+		.map(|(v, w, t)| {
+			(v.clone(), *w, t.iter().filter(|t| targets.contains(t)).cloned().collect::<Vec<_>>())
+		})
+		.filter(|(_, _, t)| !t.is_empty())
+		.collect::<Vec<_>>();
+	Snapshot { targets: targets.to_vec(), voters, ..snapshot.clone() }
+}
+
+/// Shrink the snapshot, by greedily removing voters and targets, while the predicate still holds.
+///
+/// The number of targets never goes below the desired targets of the snapshot.
+pub fn shrink(
+	snapshot: Snapshot,
+	predicate: &Predicate,
+	iterations: usize,
+) -> error::Result<Snapshot> {
+	if !predicate.holds(&snapshot, iterations) {
+		return Err(Error::Invalid(format!("{:?} does not hold for the snapshot.", predicate)));
+	}
+	let mut current = snapshot;
+	loop {
+		let size = (current.voters.len(), current.targets.len());
+
+		let voters = shrink_items(current.voters.clone(), |voters| {
+			let candidate = Snapshot { voters: voters.to_vec(), ..current.clone() };
+			predicate.holds(&candidate, iterations)
+		});
+		current.voters = voters;
+		log::info!(target: LOG_TARGET, "shrunk down to {} voters", current.voters.len());
+
+		let desired_targets = current.desired_targets as usize;
+		let targets = shrink_items(current.targets.clone(), |targets| {
+			targets.len() >= desired_targets
+				&& predicate.holds(&with_targets(&current, targets), iterations)
+		});
+		current = with_targets(&current, &targets);
+		log::info!(target: LOG_TARGET, "shrunk down to {} targets", current.targets.len());
+
+		if (current.voters.len(), current.targets.len()) == size {
+			break;
+		}
+	}
+	Ok(current)
+}
+
+/// Replace all account ids of the snapshot with deterministic pseudonyms.
+///
+/// The pseudonym of each account is the hash of the `salt` and the original account, thus the same
+/// account is always mapped to the same pseudonym and the nomination graph and all stakes are
+/// preserved. Without a secret salt, the original accounts can be recovered by hashing all known
+/// accounts of the chain.
+pub fn anonymize(snapshot: Snapshot, salt: &[u8]) -> Snapshot {
+	let pseudonym_of =
+		|who: AccountId| -> AccountId { AccountId::from(blake2_256(&(salt, who).encode())) };
+
+	let Snapshot { desired_targets, targets, voters, .. } = snapshot;
+	let targets = targets.into_iter().map(pseudonym_of).collect::<Vec<_>>();
+	let voters = voters
+		.into_iter()
+		.map(|(v, w, t)| (pseudonym_of(v), w, t.into_iter().map(pseudonym_of).collect::<Vec<_>>()))
+		.collect::<Vec<_>>();
+
+	// the block hash would reveal the origin of the snapshot, so it is stripped as well.
+	Snapshot { at: None, desired_targets, targets, voters }
+}
+
+fn run_anonymize(input: PathBuf, output: PathBuf, salt: String) -> error::Result<()> {
+	if salt.is_empty() {
+		log::warn!(
+			target: LOG_TARGET,
+			"no salt provided, the pseudonyms can be reversed by anyone who knows the accounts."
+		);
+	}
+	let snapshot = Snapshot::load(&input)?;
+	anonymize(snapshot, salt.as_bytes()).save(&output)?;
+	println!("🥸 anonymized snapshot written to {:?}", output);
+	Ok(())
+}
+
+fn run_shrink(
+	input: PathBuf,
+	output: PathBuf,
+	predicate: Predicate,
+	iterations: usize,
+) -> error::Result<()> {
+	let snapshot = Snapshot::load(&input)?;
+	let (voters, targets) = (snapshot.voters.len(), snapshot.targets.len());
+	if predicate == Predicate::Fails {
+		// we expect lots of panics, don't print them all.
+		std::panic::set_hook(Box::new(|_| {}));
+	}
+	let shrunk = shrink(snapshot, &predicate, iterations);
+	let _ = std::panic::take_hook();
+	let shrunk = shrunk?;
+	println!(
+		"🔬 shrunk from {} voters and {} targets to {} voters and {} targets, written to {:?}",
+		voters,
+		targets,
+		shrunk.voters.len(),
+		shrunk.targets.len(),
+		output
+	);
+	shrunk.save(&output)
+}
+
+/// The percentiles reported by the histogram.
+const PERCENTILES: [u8; 8] = [1, 5, 10, 25, 50, 75, 90, 99];
+
+/// A histogram of the stake of the nominators of a snapshot, in vote weight.
+#[derive(serde::Serialize, Debug)]
+pub struct Histogram {
+	/// Buckets as `(lower bound, count)`, where the lower bounds are consecutive powers of two.
+	/// Each bucket contains the stakes in `[lower, 2 * lower)`, except the first one which starts
+	/// at zero.
+	pub buckets: Vec<(u64, usize)>,
+	/// The stake at each of [`PERCENTILES`], as `(percentile, stake)`.
+	pub percentiles: Vec<(u8, u64)>,
+}
+
+/// Build the histogram of the stake of all nominators of the snapshot. Self-votes are ignored.
+pub fn histogram(snapshot: &Snapshot) -> Histogram {
+	let mut stakes = snapshot
+		.voters
+		.iter()
+		.filter(|(v, _, t)| t != &vec![v.clone()])
+		.map(|(_, w, _)| *w)
+		.collect::<Vec<_>>();
+	stakes.sort_unstable();
+
+	let bucket_of = |w: u64| -> u32 { 63u32.saturating_sub(w.leading_zeros()) };
+	let mut buckets = vec![];
+	if let (Some(min), Some(max)) = (stakes.first(), stakes.last()) {
+		for b in bucket_of(*min)..=bucket_of(*max) {
+			let lower = if b == 0 { 0 } else { 1u64 << b };
+			buckets.push((lower, stakes.iter().filter(|w| bucket_of(**w) == b).count()));
+		}
+	}
+	let percentiles = if stakes.is_empty() {
+		vec![]
+	} else {
+		PERCENTILES
+			.iter()
+			.map(|p| (*p, stakes[(stakes.len() - 1) * *p as usize / 100]))
+			.collect::<Vec<_>>()
+	};
+	Histogram { buckets, percentiles }
+}
+
+fn run_histogram(input: PathBuf, output: Option<PathBuf>) -> error::Result<()> {
+	let snapshot = Snapshot::load(&input)?;
+	let histogram = histogram(&snapshot);
+	let total = histogram.buckets.iter().map(|(_, c)| c).sum::<usize>().max(1);
+
+	println!("📊 stake of {} nominators, in vote weight:", total);
+	for (lower, count) in histogram.buckets.iter() {
+		println!(
+			"\t>= {:>20} | {:>7} | {}",
+			lower,
+			count,
+			"#".repeat((count * 60 + total - 1) / total)
+		);
+	}
+	for (p, stake) in histogram.percentiles.iter() {
+		println!("\tp{:<2} = {}", p, stake);
+	}
+
+	if let Some(output) = output {
+		let file = std::fs::File::create(&output).map_err(|e| Error::file(&output, e))?;
+		serde_json::to_writer_pretty(file, &histogram).map_err(|e| Error::file(&output, e))?;
+		println!("📊 histogram written to {:?}", output);
+	}
+	Ok(())
+}
+
+/// The nomination graph of a snapshot as a sparse matrix, with one row per voter and one column
+/// per target. Each entry is the full vote weight of the voter, as it is up to the election to
+/// split it among the targets.
+#[derive(Debug)]
+pub struct Adjacency {
+	/// The voters, in the order of the rows.
+	pub voters: Vec<(AccountId, u64)>,
+	/// The targets, in the order of the columns.
+	pub targets: Vec<AccountId>,
+	/// The non-zero entries, as zero-based `(row, column, weight)`.
+	pub entries: Vec<(usize, usize, u64)>,
+}
+
+/// Build the adjacency matrix of the snapshot. Votes for accounts that are not targets of the
+/// snapshot are ignored, as the election does.
+pub fn adjacency(snapshot: &Snapshot) -> Adjacency {
+	let column_of = snapshot
+		.targets
+		.iter()
+		.enumerate()
+		.map(|(i, t)| (t.clone(), i))
+		.collect::<std::collections::BTreeMap<_, _>>();
+	let mut entries = vec![];
+	for (row, (_, weight, targets)) in snapshot.voters.iter().enumerate() {
+		for target in targets.iter() {
+			if let Some(column) = column_of.get(target) {
+				entries.push((row, *column, *weight));
+			}
+		}
+	}
+	let voters = snapshot.voters.iter().map(|(v, w, _)| (v.clone(), *w)).collect::<Vec<_>>();
+	Adjacency { voters, targets: snapshot.targets.clone(), entries }
+}
+
+/// Write `adjacency` into the directory `output`, in all the formats of `snapshot matrix`.
+fn write_matrix(adjacency: &Adjacency, output: &Path) -> std::io::Result<()> {
+	std::fs::create_dir_all(output)?;
+	let create = |name: &str| -> std::io::Result<_> {
+		Ok(std::io::BufWriter::new(std::fs::File::create(output.join(name))?))
+	};
+
+	// matrix market is one-based.
+	let mut mtx = create("graph.mtx")?;
+	writeln!(mtx, "%%MatrixMarket matrix coordinate integer general")?;
+	writeln!(
+		mtx,
+		"{} {} {}",
+		adjacency.voters.len(),
+		adjacency.targets.len(),
+		adjacency.entries.len()
+	)?;
+	for (row, column, weight) in adjacency.entries.iter() {
+		writeln!(mtx, "{} {} {}", row + 1, column + 1, weight)?;
+	}
+
+	// zero-based triples, as `scipy.sparse.coo_matrix` expects them.
+	let mut triples = create("graph.csv")?;
+	writeln!(triples, "voter,target,weight")?;
+	for (row, column, weight) in adjacency.entries.iter() {
+		writeln!(triples, "{},{},{}", row, column, weight)?;
+	}
+
+	let mut voters = create("voters.csv")?;
+	writeln!(voters, "index,account,weight")?;
+	for (i, (who, weight)) in adjacency.voters.iter().enumerate() {
+		writeln!(voters, "{},{},{}", i, who, weight)?;
+	}
+
+	let mut targets = create("targets.csv")?;
+	writeln!(targets, "index,account")?;
+	for (i, who) in adjacency.targets.iter().enumerate() {
+		writeln!(targets, "{},{}", i, who)?;
+	}
+
+	Ok(())
+}
+
+fn run_matrix(input: PathBuf, output: PathBuf) -> error::Result<()> {
+	let snapshot = Snapshot::load(&input)?;
+	let adjacency = adjacency(&snapshot);
+	write_matrix(&adjacency, &output).map_err(|e| Error::file(&output, e))?;
+	println!(
+		"🕸  {}x{} matrix with {} edges written to {:?}",
+		adjacency.voters.len(),
+		adjacency.targets.len(),
+		adjacency.entries.len(),
+		output
+	);
+	Ok(())
+}
+
+/// The number of account ids per line of the generated fixture.
+const FIXTURE_IDS_PER_LINE: usize = 16;
+
+/// Turn the snapshot into the source of a regression test of `sp-npos-elections`, named `name`.
+///
+/// The test is meant to be pasted into `primitives/npos-elections/src/tests.rs`, and uses the
+/// builders of its `mock` module, where accounts are `u64`. Targets are numbered from 1 in their
+/// order in the snapshot, then the voters that are not targets, then the nominated accounts that
+/// are neither.
+pub fn fixture(snapshot: &Snapshot, name: &str) -> String {
+	let mut ids = std::collections::BTreeMap::<AccountId, u64>::new();
+	let accounts = snapshot
+		.targets
+		.iter()
+		.chain(snapshot.voters.iter().map(|(v, _, _)| v))
+		.chain(snapshot.voters.iter().flat_map(|(_, _, t)| t.iter()));
+	for account in accounts {
+		let next = ids.len() as u64 + 1;
+		ids.entry(account.clone()).or_insert(next);
+	}
+	let id = |a: &AccountId| ids[a].to_string();
+	let list = |accounts: &[AccountId]| accounts.iter().map(id).collect::<Vec<_>>().join(", ");
+
+	let mut source = String::new();
+	let mut line = |l: String| {
+		source.push_str(&l);
+		source.push('\n');
+	};
+	line(format!(
+		"/// Generated by `offline-election snapshot fixture` from a snapshot of {} voters and {} \
+		 targets{}.",
+		snapshot.voters.len(),
+		snapshot.targets.len(),
+		snapshot.at.map(|at| format!(" at block {:?}", at)).unwrap_or_default(),
+	));
+	line("#[test]".into());
+	line(format!("fn {}() {{", name));
+	line("\tlet candidates: Vec<AccountId> = vec![".into());
+	for chunk in snapshot.targets.chunks(FIXTURE_IDS_PER_LINE) {
+		line(format!("\t\t{},", list(chunk)));
+	}
+	line("\t];".into());
+	line("\tlet voters: Vec<(AccountId, Vec<AccountId>)> = vec![".into());
+	for (voter, _, targets) in snapshot.voters.iter() {
+		line(format!("\t\t({}, vec![{}]),", id(voter), list(targets)));
+	}
+	line("\t];".into());
+	line("\tlet stake_of = create_stake_of(&[".into());
+	for (voter, weight, _) in snapshot.voters.iter() {
+		line(format!("\t\t({}, {}),", id(voter), weight));
+	}
+	line("\t]);".into());
+	line(format!(
+		"\trun_and_compare::<Perbill, _>(candidates, voters, &stake_of, {});",
+		snapshot.desired_targets
+	));
+	line("}".into());
+	source
+}
+
+fn run_fixture(input: PathBuf, output: PathBuf, name: String) -> error::Result<()> {
+	let snapshot = Snapshot::load(&input)?;
+	std::fs::write(&output, fixture(&snapshot, &name)).map_err(|e| Error::file(&output, e))?;
+	println!(
+		"🧪 regression test `{}` of {} voters and {} targets written to {:?}",
+		name,
+		snapshot.voters.len(),
+		snapshot.targets.len(),
+		output
+	);
+	Ok(())
+}
+
+/// Main run function of the sub-command.
+///
+/// Note that this sub-command does not need a connection to any node.
+pub fn run(command: SnapshotCommand) -> error::Result<()> {
+	match command {
+		SnapshotCommand::Anonymize { input, output, salt } => run_anonymize(input, output, salt),
+		SnapshotCommand::Shrink { input, output, predicate, iterations } => {
+			run_shrink(input, output, predicate, iterations)
+		}
+		SnapshotCommand::Histogram { input, output } => run_histogram(input, output),
+		SnapshotCommand::Matrix { input, output } => run_matrix(input, output),
+		SnapshotCommand::Fixture { input, output, name } => run_fixture(input, output, name),
+	}
+}
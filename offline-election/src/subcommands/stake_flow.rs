@@ -0,0 +1,161 @@
+//! Export how the backing moved between the validators of two consecutive elections, as the
+//! links of a Sankey diagram.
+//!
+//! For each staker, the stake that backs the same validator in both eras stays. The rest of what
+//! it withdrew from its old validators is spread over its new validators, pro rata, and whatever
+//! is left either exited or entered staking.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance},
+	subcommands::{clusters, session_info},
+	Client, Currency, Opt,
+};
+use pallet_staking::EraIndex;
+use sp_runtime::helpers_128bit::multiply_by_rational;
+use std::{collections::BTreeMap, io::Write, path::PathBuf};
+
+/// The pseudo-node of stake that was not staked in the first era.
+const ENTERED: &str = "entered";
+/// The pseudo-node of stake that is not staked anymore in the second era.
+const EXITED: &str = "exited";
+
+/// The backing of each staker, from validator to amount.
+type Backings = BTreeMap<AccountId, BTreeMap<AccountId, Balance>>;
+
+/// A link of the Sankey diagram, from a node of the first era to a node of the second.
+#[derive(Debug, Clone)]
+struct Link {
+	source: String,
+	target: String,
+	value: Balance,
+}
+
+async fn backings_at(era: EraIndex, client: &Client, at: crate::Hash) -> Backings {
+	let mut backings = Backings::new();
+	for (v, exposure) in clusters::exposures_at(era, client, at).await {
+		*backings.entry(v.clone()).or_default().entry(v.clone()).or_default() += exposure.own;
+		for other in exposure.others {
+			*backings.entry(other.who).or_default().entry(v.clone()).or_default() += other.value;
+		}
+	}
+	backings
+}
+
+/// `a * b / c`, without overflowing.
+fn pro_rata(a: Balance, b: Balance, c: Balance) -> Balance {
+	multiply_by_rational(a, b, c.max(1)).unwrap_or(Balance::max_value())
+}
+
+/// Compute the flows from the backings of one era to the next, keyed by `(source, target)`.
+fn flows(before: &Backings, after: &Backings) -> BTreeMap<(String, String), Balance> {
+	let empty = BTreeMap::new();
+	let mut flows = BTreeMap::<(String, String), Balance>::new();
+	let stakers = before.keys().chain(after.keys()).collect::<std::collections::BTreeSet<_>>();
+	for who in stakers {
+		let before = before.get(who).unwrap_or(&empty);
+		let after = after.get(who).unwrap_or(&empty);
+
+		let mut outgoing = BTreeMap::<&AccountId, Balance>::new();
+		let mut incoming = BTreeMap::<&AccountId, Balance>::new();
+		for (v, amount) in before.iter() {
+			let stayed = (*amount).min(after.get(v).cloned().unwrap_or_default());
+			if stayed > 0 {
+				*flows.entry((v.to_string(), v.to_string())).or_default() += stayed;
+			}
+			outgoing.insert(v, amount - stayed);
+		}
+		for (v, amount) in after.iter() {
+			let stayed = (*amount).min(before.get(v).cloned().unwrap_or_default());
+			incoming.insert(v, amount - stayed);
+		}
+
+		let total_out = outgoing.values().sum::<Balance>();
+		let total_in = incoming.values().sum::<Balance>();
+		let moved = total_out.min(total_in);
+		for (from, out) in outgoing.iter().filter(|(_, o)| **o > 0) {
+			// the share of `out` that moved, the rest exited.
+			let out_moved = pro_rata(*out, moved, total_out);
+			for (to, in_) in incoming.iter().filter(|(_, i)| **i > 0) {
+				let value = pro_rata(out_moved, *in_, total_in);
+				if value > 0 {
+					*flows.entry((from.to_string(), to.to_string())).or_default() += value;
+				}
+			}
+			if out - out_moved > 0 {
+				*flows.entry((from.to_string(), EXITED.into())).or_default() += out - out_moved;
+			}
+		}
+		for (to, in_) in incoming.iter().filter(|(_, i)| **i > 0) {
+			let entered = in_ - pro_rata(*in_, moved, total_in);
+			if entered > 0 {
+				*flows.entry((ENTERED.into(), to.to_string())).or_default() += entered;
+			}
+		}
+	}
+	flows
+}
+
+/// Main run function of the sub-command.
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	era: Option<EraIndex>,
+	output: PathBuf,
+	csv: bool,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let era = era.unwrap_or(session_info::get_active_era(client, at).await.index);
+	let previous = era.checked_sub(1).expect("there is no election before era 0.");
+
+	let before = backings_at(previous, client, at).await;
+	let after = backings_at(era, client, at).await;
+	let flows = flows(&before, &after);
+
+	let total = |f: &dyn Fn(&str, &str) -> bool| {
+		Currency::from(flows.iter().filter(|((s, t), _)| f(s, t)).map(|(_, v)| v).sum::<Balance>())
+	};
+	println!("🌊 stake flow from era {} to era {}", previous, era);
+	println!("\tstayed: {:?}", total(&|s, t| s == t));
+	println!("\tmoved: {:?}", total(&|s, t| s != t && s != ENTERED && t != EXITED));
+	println!("\tentered: {:?}", total(&|s, _| s == ENTERED));
+	println!("\texited: {:?}", total(&|_, t| t == EXITED));
+
+	let links = flows
+		.into_iter()
+		.map(|((source, target), value)| Link {
+			source: format!("{}:{}", previous, source),
+			target: format!("{}:{}", era, target),
+			value,
+		})
+		.collect::<Vec<_>>();
+
+	let mut file = std::fs::File::create(&output).expect("Failed to create output file.");
+	if csv {
+		writeln!(file, "source,target,value").unwrap();
+		for link in links.iter() {
+			writeln!(file, "{},{},{}", link.source, link.target, link.value).unwrap();
+		}
+	} else {
+		// amounts are strings, json numbers can not hold them all.
+		let mut nodes =
+			links.iter().flat_map(|l| vec![l.source.clone(), l.target.clone()]).collect::<Vec<_>>();
+		nodes.sort();
+		nodes.dedup();
+		let links = links
+			.iter()
+			.map(|l| {
+				serde_json::json!({
+					"source": l.source,
+					"target": l.target,
+					"value": l.value.to_string(),
+				})
+			})
+			.collect::<Vec<_>>();
+		let nodes = nodes.into_iter().map(|id| serde_json::json!({ "id": id })).collect::<Vec<_>>();
+		serde_json::to_writer_pretty(file, &serde_json::json!({ "nodes": nodes, "links": links }))
+			.unwrap();
+	}
+	println!("📦 {} links written to {:?}", links.len(), output);
+	Ok(())
+}
@@ -1,23 +1,29 @@
 //! Helpers to read staking module.
 
 use crate::{
+	election::{self, Algorithm, Outcome},
+	error::{self, Error},
 	network,
 	primitives::{AccountId, Balance, Hash},
-	storage, Client, Currency, Opt, StakingConfig, LOG_TARGET,
+	snapshot::{Snapshot, Voter},
+	storage,
+	subcommands::validator_check,
+	Client, Currency, Opt, StakingConfig, LOG_TARGET,
 };
 use codec::Encode;
 use pallet_staking::{
-	slashing::SlashingSpans, EraIndex, Exposure, Nominations, StakingLedger, ValidatorPrefs,
+	slashing::SlashingSpans, EraIndex, Exposure, Nominations, RewardDestination, StakingLedger,
+	ValidatorPrefs,
 };
 use sp_npos_elections::*;
 use sp_runtime::traits::Convert;
-use std::{collections::BTreeMap, convert::TryInto};
+use std::{collections::BTreeMap, convert::TryInto, str::FromStr};
 
 const MODULE: &[u8] = b"Staking";
 
 // TODO: remove and use the new one once runtime 0.29 is there.
 #[derive(codec::Decode, Clone, Debug)]
-struct OldValidatorPrefs {
+pub(crate) struct OldValidatorPrefs {
 	#[codec(compact)]
 	pub commission: sp_runtime::Perbill,
 }
@@ -33,7 +39,7 @@ pub(crate) async fn get_current_era(client: &Client, at: Hash) -> EraIndex {
 		.expect("CurrentEra must exist")
 }
 
-async fn get_candidates(client: &Client, at: Hash) -> Vec<AccountId> {
+pub(crate) async fn get_candidates(client: &Client, at: Hash) -> Vec<AccountId> {
 	storage::enumerate_map::<AccountId, OldValidatorPrefs>(MODULE, b"Validators", client, at)
 		.await
 		.expect("Staking::validators should be enumerable.")
@@ -42,26 +48,52 @@ async fn get_candidates(client: &Client, at: Hash) -> Vec<AccountId> {
 		.collect::<Vec<AccountId>>()
 }
 
-async fn stake_of(stash: &AccountId, client: &Client, at: Hash) -> Balance {
-	let ctrl = storage::read::<AccountId>(
+/// Get the controller of a stash, if it is bonded.
+pub(crate) async fn controller_of(
+	stash: &AccountId,
+	client: &Client,
+	at: Hash,
+) -> Option<AccountId> {
+	storage::read::<AccountId>(
 		storage::map_key::<frame_support::Twox64Concat>(MODULE, b"Bonded", stash.as_ref()),
 		&client,
 		at,
 	)
 	.await
-	.expect("All stashes must have 'Bonded' storage.");
+}
 
+/// Get the ledger of a controller, if any.
+pub(crate) async fn ledger_of(
+	ctrl: &AccountId,
+	client: &Client,
+	at: Hash,
+) -> Option<StakingLedger<AccountId, Balance>> {
 	storage::read::<StakingLedger<AccountId, Balance>>(
 		storage::map_key::<frame_support::Blake2_128Concat>(MODULE, b"Ledger", ctrl.as_ref()),
 		&client,
 		at,
 	)
 	.await
-	.expect("All controllers must have a 'Ledger' storage")
-	.active
 }
 
-async fn get_voters(client: &Client, at: Hash) -> Vec<(AccountId, VoteWeight, Vec<AccountId>)> {
+pub(crate) async fn stake_of(stash: &AccountId, client: &Client, at: Hash) -> Balance {
+	let ctrl =
+		controller_of(stash, client, at).await.expect("All stashes must have 'Bonded' storage.");
+
+	ledger_of(&ctrl, client, at).await.expect("All controllers must have a 'Ledger' storage").active
+}
+
+/// Get all the nominators as voters, skipping the ones already in `done`, e.g. from an interrupted
+/// scrape.
+///
+/// If the scrape is interrupted by ctrl-c, the voters fetched so far are returned as an error. The
+/// flag is checked once per voter, so an interruption during the single bulk read of all the
+/// nominations only takes effect once it completes.
+pub(crate) async fn get_voters(
+	client: &Client,
+	at: Hash,
+	done: Vec<Voter>,
+) -> Result<Vec<Voter>, Vec<Voter>> {
 	let nominators: Vec<(AccountId, Nominations<AccountId>)> = storage::enumerate_map::<
 		AccountId,
 		Nominations<AccountId>,
@@ -69,8 +101,16 @@ async fn get_voters(client: &Client, at: Hash) -> Vec<(AccountId, VoteWeight, Ve
 	.await
 	.expect("Staking::nominators should be enumerable");
 
-	let mut result = vec![];
+	let skip = done.iter().map(|(v, _, _)| v.clone()).collect::<std::collections::BTreeSet<_>>();
+	let mut result = done;
 	for (idx, (who, n)) in nominators.into_iter().enumerate() {
+		if skip.contains(&who) {
+			continue;
+		}
+		if crate::interrupt::interrupted() {
+			return Err(result);
+		}
+
 		// retain only targets who have not been yet slashed recently. This is highly dependent
 		// on the staking implementation.
 		let submitted_in = n.submitted_in;
@@ -97,7 +137,37 @@ async fn get_voters(client: &Client, at: Hash) -> Vec<(AccountId, VoteWeight, Ve
 		result.push((who, to_vote_weight(stake), targets));
 	}
 
-	result
+	Ok(result)
+}
+
+/// Get the commission of a validator stash, if it is a validator candidate.
+pub(crate) async fn commission_of(
+	stash: &AccountId,
+	client: &Client,
+	at: Hash,
+) -> Option<sp_runtime::Perbill> {
+	storage::read::<OldValidatorPrefs>(
+		storage::map_key::<frame_support::Twox64Concat>(MODULE, b"Validators", stash.as_ref()),
+		&client,
+		at,
+	)
+	.await
+	.map(|p| p.commission)
+}
+
+/// Get the reward destination of a stash.
+pub(crate) async fn payee_of(
+	stash: &AccountId,
+	client: &Client,
+	at: Hash,
+) -> RewardDestination<AccountId> {
+	storage::read::<RewardDestination<AccountId>>(
+		storage::map_key::<frame_support::Twox64Concat>(MODULE, b"Payee", stash.as_ref()),
+		&client,
+		at,
+	)
+	.await
+	.unwrap_or_default()
 }
 
 /// Get the slashing span of a voter stash.
@@ -135,94 +205,1003 @@ pub async fn exposure_of(
 	.unwrap_or_default()
 }
 
-async fn get_validator_count(client: &Client, at: Hash) -> u32 {
+pub(crate) async fn get_validator_count(client: &Client, at: Hash) -> u32 {
 	storage::read::<u32>(storage::value_key(MODULE, b"ValidatorCount"), client, at)
 		.await
 		.unwrap_or(50)
 }
 
-fn to_vote_weight(balance: Balance) -> VoteWeight {
+pub(crate) fn to_vote_weight(balance: Balance) -> VoteWeight {
 	<network::CurrencyToVoteHandler as Convert<Balance, VoteWeight>>::convert(balance)
 }
 
-/// Main run function of the sub-command.
-pub async fn run(client: &Client, opt: Opt, conf: StakingConfig) {
-	let at = opt.at.unwrap();
-	let val_count = get_validator_count(&client, at).await as usize;
-	let verbosity = opt.verbosity;
-	let iterations = conf.iterations;
-	let count = conf.count.unwrap_or(val_count);
-	let reduce = conf.reduce;
+/// Get the self-vote of each of the given candidates.
+pub(crate) async fn get_self_votes(
+	candidates: &[AccountId],
+	client: &Client,
+	at: Hash,
+) -> Vec<Voter> {
+	let mut self_votes = vec![];
+	for c in candidates.iter() {
+		self_votes.push((
+			c.clone(),
+			to_vote_weight(stake_of(c, client, at).await),
+			vec![c.clone()],
+		));
+	}
+	self_votes
+}
 
-	if count != val_count {
-		log::warn!(
-			target: LOG_TARGET,
-			"`count` provided ({:?}) differs from validator count on-chain ({}).",
-			count,
-			val_count,
+/// Predict the outcome of the next staking election at `at`, with no overrides and no balancing.
+///
+/// Returns the elected stashes and their supports.
+pub(crate) async fn predict(
+	client: &Client,
+	at: Hash,
+) -> error::Result<(Vec<AccountId>, SupportMap<AccountId>)> {
+	let snapshot = Snapshot::fetch(client, at).await?;
+	let count = snapshot.desired_targets as usize;
+	let Outcome { winners, supports, .. } =
+		election::elect(&snapshot, count, Algorithm::SeqPhragmen, 0, false)
+			.map_err(Error::Election)?;
+	Ok((winners, supports))
+}
+
+/// The format of the json output of the election.
+#[derive(Debug, Clone, Copy)]
+pub enum OutputFormat {
+	/// The winners and their supports.
+	Default,
+	/// A node-link graph of validators and nominators, as consumed by d3 based frontends.
+	D3,
+}
+
+impl FromStr for OutputFormat {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"default" => Ok(Self::Default),
+			"d3" => Ok(Self::D3),
+			_ => Err("Invalid output format. Can be default|d3."),
+		}
+	}
+}
+
+/// A top-level section of the default json output.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OutputField {
+	/// The elected validators.
+	Winners,
+	/// The assignments of each voter, after reduce if it is applied.
+	Assignments,
+	/// The supports of each winner.
+	Supports,
+	/// The targets and voters that the election ran on.
+	Snapshot,
+	/// The score, counts and estimated weight of the solution.
+	Metrics,
+	/// The duration of each step of the election, in milliseconds.
+	Timing,
+}
+
+impl FromStr for OutputField {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"winners" => Ok(Self::Winners),
+			"assignments" => Ok(Self::Assignments),
+			"supports" => Ok(Self::Supports),
+			"snapshot" => Ok(Self::Snapshot),
+			"metrics" => Ok(Self::Metrics),
+			"timing" => Ok(Self::Timing),
+			_ => Err(
+				"Invalid output field. Can be winners|assignments|supports|snapshot|metrics|timing.",
+			),
+		}
+	}
+}
+
+/// The current version of the default json output. Bumped on any breaking change.
+///
+/// - `0`: `{ supports, winners }`, without a version field.
+/// - `1`: `{ schema_version, supports, winners }`.
+pub(crate) const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+/// Parse `--schema-version`, which can be any version up to [`OUTPUT_SCHEMA_VERSION`].
+pub(crate) fn parse_schema_version(s: &str) -> Result<u32, String> {
+	let version = s.parse::<u32>().map_err(|e| e.to_string())?;
+	if version > OUTPUT_SCHEMA_VERSION {
+		return Err(format!(
+			"unknown schema version {}, latest is {}",
+			version, OUTPUT_SCHEMA_VERSION
+		));
+	}
+	Ok(version)
+}
+
+/// The json schema of the default json output, at [`OUTPUT_SCHEMA_VERSION`].
+pub(crate) fn output_schema() -> serde_json::Value {
+	let account = serde_json::json!({ "type": "string", "description": "ss58 encoded account id" });
+	serde_json::json!({
+		"$schema": "http://json-schema.org/draft-07/schema#",
+		"title": "offline-election staking output",
+		"type": "object",
+		"description": "only `winners` and `supports` are present by default, see `--fields`",
+		"required": ["schema_version"],
+		"properties": {
+			"schema_version": { "type": "integer", "const": OUTPUT_SCHEMA_VERSION },
+			"assignments": {
+				"type": "array",
+				"description": "the assignments of each voter, as [voter, [[target, stake]]]"
+			},
+			"snapshot": {
+				"type": "object",
+				"required": ["targets", "voters"],
+				"properties": {
+					"targets": { "type": "array", "items": account },
+					"voters": { "type": "array", "description": "[voter, stake, [target]]" }
+				}
+			},
+			"metrics": { "type": "object" },
+			"timing": {
+				"type": "object",
+				"description": "map from step to its duration in milliseconds",
+				"additionalProperties": { "type": "integer" }
+			},
+			"winners": { "type": "array", "items": account },
+			"supports": {
+				"type": "object",
+				"description": "map from winner to its support",
+				"additionalProperties": {
+					"type": "object",
+					"required": ["total", "voters"],
+					"properties": {
+						"total": { "type": "integer" },
+						"voters": {
+							"type": "array",
+							"items": {
+								"type": "array",
+								"items": [account, { "type": "integer" }],
+								"minItems": 2,
+								"maxItems": 2
+							}
+						}
+					}
+				}
+			}
+		}
+	})
+}
+
+/// A change to the bonded stake of an account, parsed from `<account>:<amount>`.
+///
+/// The amount is in the smallest unit of the currency.
+#[derive(Debug, Clone)]
+pub struct StakeChange {
+	who: AccountId,
+	amount: Balance,
+}
+
+impl FromStr for StakeChange {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.rsplitn(2, ':');
+		let amount = parts.next().and_then(|a| a.parse::<Balance>().ok());
+		let who = parts.next().and_then(|w| AccountId::from_str(w).ok());
+		match (who, amount) {
+			(Some(who), Some(amount)) => Ok(Self { who, amount }),
+			_ => Err("Invalid stake change. Must be <account>:<amount>."),
+		}
+	}
+}
+
+/// How to pick the voters to keep when the snapshot is truncated with `--max`.
+#[derive(Debug, Clone, Copy)]
+pub enum MaxStrategy {
+	/// Keep the voters with the most stake.
+	TopStake,
+	/// Keep a random sample of the voters, from the given seed.
+	Random(u64),
+	/// Keep the voters in the order of the bags-list of the chain, as the chain itself does.
+	BagsOrder,
+}
+
+impl FromStr for MaxStrategy {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"top-stake" => Ok(Self::TopStake),
+			"bags-order" => Ok(Self::BagsOrder),
+			_ => s
+				.strip_prefix("random:")
+				.and_then(|seed| seed.parse::<u64>().ok())
+				.map(Self::Random)
+				.ok_or("Invalid max strategy. Can be top-stake|random:<seed>|bags-order."),
+		}
+	}
+}
+
+/// A set of election configurations, as the cartesian product of the given values, e.g.
+/// `algorithm=seq-phragmen,phragmms;iterations=0,10;reduce=off,on`.
+///
+/// A key that is not given takes the single value of `seq-phragmen`, `0` and `off` respectively.
+#[derive(Debug, Clone)]
+pub struct ConfigMatrix {
+	algorithms: Vec<Algorithm>,
+	iterations: Vec<usize>,
+	reduce: Vec<bool>,
+}
+
+impl ConfigMatrix {
+	/// All the configurations of the matrix, as `(algorithm, iterations, reduce)`.
+	fn configs(&self) -> Vec<(Algorithm, usize, bool)> {
+		let mut configs = vec![];
+		for algorithm in self.algorithms.iter() {
+			for iterations in self.iterations.iter() {
+				for reduce in self.reduce.iter() {
+					configs.push((*algorithm, *iterations, *reduce));
+				}
+			}
+		}
+		configs
+	}
+}
+
+impl FromStr for ConfigMatrix {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		const ERR: &str = "Invalid config matrix. Expected \
+			algorithm=<seq-phragmen|phragmms,..>;iterations=<n,..>;reduce=<on|off,..>.";
+		let mut matrix = Self {
+			algorithms: vec![Algorithm::SeqPhragmen],
+			iterations: vec![0],
+			reduce: vec![false],
+		};
+		for part in s.split(';').filter(|p| !p.is_empty()) {
+			let mut kv = part.splitn(2, '=');
+			let key = kv.next().ok_or(ERR)?.trim();
+			let values = kv.next().ok_or(ERR)?.split(',').map(str::trim);
+			match key {
+				"algorithm" => {
+					matrix.algorithms = values.map(Algorithm::from_str).collect::<Result<_, _>>()?
+				}
+				"iterations" => {
+					matrix.iterations = values
+						.map(|v| v.parse::<usize>().map_err(|_| ERR))
+						.collect::<Result<_, _>>()?
+				}
+				"reduce" => {
+					matrix.reduce = values
+						.map(|v| match v {
+							"on" => Ok(true),
+							"off" => Ok(false),
+							_ => Err(ERR),
+						})
+						.collect::<Result<_, _>>()?
+				}
+				_ => return Err(ERR),
+			}
+		}
+		if matrix.algorithms.is_empty() || matrix.iterations.is_empty() || matrix.reduce.is_empty()
+		{
+			return Err(ERR);
+		}
+		Ok(matrix)
+	}
+}
+
+/// Run all the configurations of `matrix` on the same snapshot, and print a table comparing their
+/// scores and elected sets. The sets are compared with the one of the first configuration.
+fn run_matrix(snapshot: &Snapshot, count: usize, matrix: &ConfigMatrix) {
+	println!(
+		"{:<14} {:>10} {:>6} {:>24} {:>26} {:>40} {:>8} {:>8} {:>10}",
+		"algorithm",
+		"iterations",
+		"reduce",
+		"minimal stake",
+		"sum stake",
+		"sum stake squared",
+		"edges",
+		"set",
+		"time (ms)"
+	);
+	let mut reference: Option<Vec<AccountId>> = None;
+	for (algorithm, iterations, reduce) in matrix.configs() {
+		let start = std::time::Instant::now();
+		let outcome = election::elect(snapshot, count, algorithm, iterations, reduce)
+			.expect("Phragmen failed to elect.");
+		let elapsed = start.elapsed().as_millis();
+
+		let edges = outcome.assignments.iter().map(|a| a.distribution.len()).sum::<usize>();
+		let set = match reference {
+			None => {
+				reference = Some(outcome.winners.clone());
+				"=".to_string()
+			}
+			Some(ref reference) => {
+				let missing = reference.iter().filter(|w| !outcome.winners.contains(w)).count();
+				if missing == 0 {
+					"=".to_string()
+				} else {
+					format!("±{}", missing)
+				}
+			}
+		};
+		println!(
+			"{:<14} {:>10} {:>6} {:>24} {:>26} {:>40} {:>8} {:>8} {:>10}",
+			format!("{:?}", algorithm),
+			iterations,
+			if reduce { "on" } else { "off" },
+			outcome.score[0],
+			outcome.score[1],
+			outcome.score[2],
+			edges,
+			set,
+			elapsed,
 		);
 	}
+}
 
-	// stash key of all wannabe candidates.
-	let mut candidates = get_candidates(client, at).await;
+/// A node of the bags-list, only the fields that we need.
+#[derive(codec::Decode)]
+struct BagsListNode {
+	_id: AccountId,
+	_prev: Option<AccountId>,
+	next: Option<AccountId>,
+}
 
-	// stash key of current voters, including maybe self vote.
-	let mut all_voters_and_stake = get_voters(&client, at).await;
+/// A bag of the bags-list.
+#[derive(codec::Decode)]
+struct BagsListBag {
+	head: Option<AccountId>,
+	_tail: Option<AccountId>,
+}
 
-	if let Some(path) = conf.manual_override {
-		#[derive(serde::Serialize, serde::Deserialize)]
-		struct Override {
-			voters: Vec<(AccountId, u64, Vec<AccountId>)>,
-			voters_remove: Vec<AccountId>,
-			candidates: Vec<AccountId>,
-			candidates_remove: Vec<AccountId>,
+/// Get all the voters in the order of the bags-list, from the highest bag to the lowest, and in
+/// insertion order within each bag.
+///
+/// Returns `None` if the chain has no bags-list.
+async fn bags_order(client: &Client, at: Hash) -> Option<Vec<AccountId>> {
+	let mut found = None;
+	for module in ["VoterList", "BagsList"].iter() {
+		if let Some(thresholds) =
+			storage::get_const::<Vec<u64>>(client, module, "BagThresholds", at).await
+		{
+			found = Some((module.as_bytes(), thresholds));
+			break;
 		}
+	}
+	let (module, thresholds) = found?;
 
-		let file = std::fs::read(path).unwrap();
-		let json_str = std::str::from_utf8(file.as_ref()).unwrap();
-		let manual: Override = serde_json::from_str(json_str).unwrap();
+	let mut ordered = vec![];
+	// the last bag is implicitly `u64::max_value()`.
+	for upper in thresholds.into_iter().chain(std::iter::once(u64::max_value())).rev() {
+		let bag = storage::read::<BagsListBag>(
+			storage::map_key::<frame_support::Twox64Concat>(module, b"ListBags", &upper.encode()),
+			client,
+			at,
+		)
+		.await;
+		let mut next = bag.and_then(|b| b.head);
+		while let Some(who) = next {
+			next = storage::read::<BagsListNode>(
+				storage::map_key::<frame_support::Twox64Concat>(module, b"ListNodes", who.as_ref()),
+				client,
+				at,
+			)
+			.await
+			.and_then(|n| n.next);
+			ordered.push(who);
+		}
+	}
+	Some(ordered)
+}
 
-		// add any additional candidates
-		manual.candidates.iter().for_each(|c| {
-			if candidates.contains(c) {
-				println!("manual override: {:?} is already a candidate.", c);
-			} else {
-				println!("manual override: {:?} is added as candidate.", c);
-				candidates.push(c.clone())
+/// Truncate the voters to `max`, picking the ones to keep with `strategy`.
+///
+/// Fails if `strategy` is [`MaxStrategy::BagsOrder`] and the chain has no bags-list.
+async fn truncate_voters(
+	voters: &mut Vec<Voter>,
+	max: usize,
+	strategy: MaxStrategy,
+	client: &Client,
+	at: Hash,
+) -> error::Result<()> {
+	if voters.len() <= max {
+		return Ok(());
+	}
+	let ordered = match strategy {
+		MaxStrategy::BagsOrder => Some(
+			bags_order(client, at)
+				.await
+				.ok_or_else(|| Error::Missing("the bags-list, needed by bags-order,".into()))?,
+		),
+		_ => None,
+	};
+	truncate_voters_in(voters, max, strategy, ordered);
+	Ok(())
+}
+
+/// Truncate the voters to `max`, picking the ones to keep with `strategy`, where `ordered` is the
+/// order of the bags-list, only needed by [`MaxStrategy::BagsOrder`].
+fn truncate_voters_in(
+	voters: &mut Vec<Voter>,
+	max: usize,
+	strategy: MaxStrategy,
+	ordered: Option<Vec<AccountId>>,
+) {
+	if voters.len() <= max {
+		return;
+	}
+	match strategy {
+		MaxStrategy::TopStake => voters.sort_by_key(|(_, w, _)| std::cmp::Reverse(*w)),
+		MaxStrategy::Random(seed) => {
+			use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+			voters.shuffle(&mut StdRng::seed_from_u64(seed));
+		}
+		MaxStrategy::BagsOrder => {
+			let ordered = ordered.expect("bags-order needs the order of the bags-list.");
+			let position =
+				ordered.into_iter().enumerate().map(|(i, v)| (v, i)).collect::<BTreeMap<_, _>>();
+			// voters missing from the list come last, in their original order.
+			voters.sort_by_key(|(v, _, _)| position.get(v).copied().unwrap_or(usize::max_value()));
+		}
+	}
+	log::warn!(
+		target: LOG_TARGET,
+		"truncating {} voters to {} with {:?}.",
+		voters.len(),
+		max,
+		strategy
+	);
+	voters.truncate(max);
+}
+
+/// Apply the given stake changes to all the votes of each account, self-votes included.
+fn apply_stake_changes(voters: &mut Vec<Voter>, unbond: &[StakeChange], rebond: &[StakeChange]) {
+	for (who, weight, _) in voters.iter_mut() {
+		for change in unbond.iter().filter(|c| &c.who == who) {
+			*weight = weight.saturating_sub(to_vote_weight(change.amount));
+		}
+		for change in rebond.iter().filter(|c| &c.who == who) {
+			*weight = weight.saturating_add(to_vote_weight(change.amount));
+		}
+	}
+}
+
+/// Estimated era reward of `who`, assuming all winners earn the same amount of points.
+async fn estimate_reward_of(
+	who: &AccountId,
+	outcome: &Outcome,
+	client: &Client,
+	at: Hash,
+) -> Balance {
+	let pot = crate::subcommands::reward_pot::estimate(client, at).await;
+	let per_validator = pot.validators / outcome.winners.len().max(1) as Balance;
+	let mut reward: Balance = 0;
+	for (v, support) in outcome.supports.iter() {
+		let commission = commission_of(v, client, at).await.unwrap_or_default();
+		let commission_payout = commission * per_validator;
+		if v == who {
+			reward += commission_payout;
+		}
+		if let Some((_, stake)) = support.voters.iter().find(|(n, _)| n == who) {
+			reward +=
+				sp_runtime::Perbill::from_rational_approximation(*stake, support.total.max(1))
+					* (per_validator - commission_payout);
+		}
+	}
+	reward
+}
+
+/// Simulate the given stake changes on the snapshot, and print their impact on the elected set
+/// and on the rewards of the changed accounts.
+async fn simulate_stake_changes(
+	snapshot: &Snapshot,
+	count: usize,
+	iterations: usize,
+	unbond: &[StakeChange],
+	rebond: &[StakeChange],
+	client: &Client,
+	at: Hash,
+) {
+	let mut simulated = snapshot.clone();
+	apply_stake_changes(&mut simulated.voters, unbond, rebond);
+	let elect = |s: &Snapshot| {
+		election::elect(s, count, Algorithm::SeqPhragmen, iterations, false)
+			.expect("Phragmen failed to elect.")
+	};
+	let (before, after) = (elect(snapshot), elect(&simulated));
+
+	println!("🔮 Simulated stake changes:");
+	for w in after.winners.iter().filter(|w| !before.winners.contains(w)) {
+		println!("\t➕ {:?} enters the elected set.", w);
+	}
+	for w in before.winners.iter().filter(|w| !after.winners.contains(w)) {
+		println!("\t➖ {:?} leaves the elected set.", w);
+	}
+	for who in unbond.iter().chain(rebond.iter()).map(|c| &c.who) {
+		println!(
+			"\t{:?}: estimated era reward {:?} => {:?}",
+			who,
+			Currency::from(estimate_reward_of(who, &before, client, at).await),
+			Currency::from(estimate_reward_of(who, &after, client, at).await),
+		);
+	}
+}
+
+/// Emulate the on-chain fallback election on the snapshot, and compare it with the solution that
+/// a miner would compute with `iterations` balancing rounds.
+///
+/// The fallback runs unbounded sequential phragmen with no balancing and no reduce, on the voters
+/// truncated to `MaxElectingVoters` in the order of the bags-list, if the chain has such a limit.
+async fn emulate_fallback(
+	snapshot: &Snapshot,
+	count: usize,
+	iterations: usize,
+	client: &Client,
+	at: Hash,
+) -> error::Result<()> {
+	let mut fallback = snapshot.clone();
+	if let Some(max) =
+		storage::get_const::<u32>(client, "ElectionProviderMultiPhase", "MaxElectingVoters", at)
+			.await
+	{
+		truncate_voters(&mut fallback.voters, max as usize, MaxStrategy::BagsOrder, client, at)
+			.await?;
+	}
+	let fallback = election::elect(&fallback, count, Algorithm::SeqPhragmen, 0, false)
+		.map_err(Error::Election)?;
+	let mined = election::elect(snapshot, count, Algorithm::SeqPhragmen, iterations, false)
+		.map_err(Error::Election)?;
+
+	let diff = |f: u128, m: u128| (f as f64 - m as f64) * 100f64 / (m as f64).max(1f64);
+	println!("🛟 Emulated on-chain fallback:");
+	println!(
+		"\tminimal backing {:?} vs mined {:?} ({:+.2}%)",
+		Currency::from(fallback.score[0]),
+		Currency::from(mined.score[0]),
+		diff(fallback.score[0], mined.score[0]),
+	);
+	println!(
+		"\ttotal backing {:?} vs mined {:?} ({:+.2}%)",
+		Currency::from(fallback.score[1]),
+		Currency::from(mined.score[1]),
+		diff(fallback.score[1], mined.score[1]),
+	);
+	println!(
+		"\tsum of squared backing {} vs mined {} ({:+.2}%)",
+		fallback.score[2],
+		mined.score[2],
+		diff(fallback.score[2], mined.score[2]),
+	);
+	println!(
+		"\t{} of {} winners differ from the mined solution.",
+		fallback.winners.iter().filter(|w| !mined.winners.contains(w)).count(),
+		mined.winners.len(),
+	);
+	Ok(())
+}
+
+/// The status of a referendum of the democracy pallet, only the fields that we need.
+#[derive(codec::Decode)]
+enum ReferendumInfo {
+	Ongoing { _end: crate::BlockNumber, proposal_hash: Hash },
+	Finished,
+}
+
+/// The preimage of a proposal, only the fields that we need.
+#[derive(codec::Decode)]
+enum PreimageStatus {
+	Missing,
+	Available { data: Vec<u8> },
+}
+
+/// Find the validator count that an ongoing referendum would set, along with the index of the
+/// referendum, given the `current` count.
+///
+/// Only referenda that directly dispatch one of the validator count calls of staking are found,
+/// not the ones that are nested in a batch.
+async fn pending_validator_count(current: u32, client: &Client, at: Hash) -> Option<(u32, u32)> {
+	use codec::{Compact, Decode};
+	let call_index = |call| storage::get_call_index(client, "Staking", call, at);
+	let (set, increase, scale) = (
+		call_index("set_validator_count").await?,
+		call_index("increase_validator_count").await,
+		call_index("scale_validator_count").await,
+	);
+
+	let referenda =
+		storage::get_pairs(storage::map_prefix_key(b"Democracy", b"ReferendumInfoOf"), client, at)
+			.await;
+	for (key, value) in referenda {
+		// the index is the last 4 bytes of the `Twox64Concat` key.
+		let index = u32::decode(&mut &key.0[key.0.len().saturating_sub(4)..]).unwrap_or_default();
+		let proposal_hash = match ReferendumInfo::decode(&mut value.0.as_slice()) {
+			Ok(ReferendumInfo::Ongoing { proposal_hash, .. }) => proposal_hash,
+			_ => continue,
+		};
+		let data = match storage::read::<PreimageStatus>(
+			storage::map_key::<frame_support::Identity>(
+				b"Democracy",
+				b"Preimages",
+				proposal_hash.as_ref(),
+			),
+			client,
+			at,
+		)
+		.await
+		{
+			Some(PreimageStatus::Available { data }) if data.len() > 2 => data,
+			_ => continue,
+		};
+
+		let called = Some((data[0], data[1]));
+		let mut args = &data[2..];
+		let new = if called == Some(set) {
+			Compact::<u32>::decode(&mut args).ok().map(|c| c.0)
+		} else if called == increase {
+			Compact::<u32>::decode(&mut args).ok().map(|c| current.saturating_add(c.0))
+		} else if called == scale {
+			sp_runtime::Percent::decode(&mut args).ok().map(|f| current.saturating_add(f * current))
+		} else {
+			None
+		};
+		if let Some(new) = new {
+			return Some((new, index));
+		}
+	}
+	None
+}
+
+/// Run the election with `count` and `new_count` seats, and report the marginal entrants, or the
+/// marginal leavers if the count decreases.
+async fn compare_counts(
+	snapshot: &Snapshot,
+	count: usize,
+	new_count: usize,
+	iterations: usize,
+	client: &Client,
+	at: Hash,
+) {
+	let elect = |count| {
+		election::elect(snapshot, count, Algorithm::SeqPhragmen, iterations, false)
+			.expect("Phragmen failed to elect.")
+	};
+	let (before, after) = (elect(count), elect(new_count));
+	println!("🔢 validator count {} -> {}", count, new_count);
+	println!(
+		"\tminimal backing {:?} -> {:?}",
+		Currency::from(before.score[0]),
+		Currency::from(after.score[0])
+	);
+
+	let (label, marginal, outcome) = if new_count >= count {
+		("➕ entrant", after.winners.iter().filter(|w| !before.winners.contains(w)), &after)
+	} else {
+		("➖ leaver", before.winners.iter().filter(|w| !after.winners.contains(w)), &before)
+	};
+	for w in marginal.collect::<Vec<_>>() {
+		println!(
+			"\t{} {:?} ({}) / backing {:?}",
+			label,
+			w,
+			storage::helpers::get_identity::<AccountId, Balance>(w.as_ref(), client, at).await,
+			Currency::from(outcome.supports.get(w).map(|s| s.total).unwrap_or_default()),
+		);
+	}
+}
+
+/// Print everything about `who` in `outcome`, the election of `snapshot`: as a candidate, whether
+/// it is elected and its distance from the cutoff, and as a voter, its assignments and its
+/// targets. The projected era reward needs the `chain` that the snapshot belongs to.
+async fn focus(
+	who: &AccountId,
+	snapshot: &Snapshot,
+	outcome: &Outcome,
+	chain: Option<(&Client, Hash)>,
+) {
+	let cutoff = outcome.supports.iter().map(|(_, s)| s.total).min().unwrap_or_default();
+	println!("🔎 {:?}", who);
+
+	if snapshot.targets.contains(who) {
+		match outcome.winners.iter().position(|w| w == who) {
+			Some(i) => {
+				let total = outcome.supports.get(who).map(|s| s.total).unwrap_or_default();
+				println!(
+					"\tcandidate: elected #{} with backing {:?}, {:?} above the cutoff",
+					i + 1,
+					Currency::from(total),
+					Currency::from(total - cutoff),
+				);
 			}
-		});
-		// remove any that are in removal list.
-		candidates.retain(|c| !manual.candidates_remove.contains(c));
+			None => println!(
+				"\tcandidate: not elected, needs about {:?} more backing",
+				validator_check::backing_needed(who, snapshot).map(Currency::from),
+			),
+		}
+	}
 
-		// add any new votes
-		manual.voters.iter().for_each(|v| {
-			if let Some(mut already_existing_voter) =
-				all_voters_and_stake.iter_mut().find(|vv| vv.0 == v.0)
-			{
-				println!("manual override: {:?} is already a voter. Overriding votes.", v.0);
-				already_existing_voter.1 = v.1.into();
-				already_existing_voter.2 = v.2.clone();
-			} else {
-				println!("manual override: {:?} is added as voters.", v.0);
-				all_voters_and_stake.push(v.clone())
+	if let Some((_, stake, targets)) = snapshot.voters.iter().find(|(v, _, _)| v == who) {
+		println!(
+			"\tvoter: stake {:?}, {} targets",
+			Currency::from(*stake as Balance),
+			targets.len()
+		);
+		let distribution = outcome
+			.assignments
+			.iter()
+			.find(|a| &a.who == who)
+			.map(|a| a.distribution.clone())
+			.unwrap_or_default();
+		for t in targets.iter() {
+			match (outcome.supports.get(t), distribution.iter().find(|(d, _)| d == t)) {
+				(Some(support), Some((_, amount))) => println!(
+					"\t\t✅ {:?}: assigned {:?}, backing {:?} ({:?} above the cutoff)",
+					t,
+					Currency::from(*amount),
+					Currency::from(support.total),
+					Currency::from(support.total - cutoff),
+				),
+				(Some(support), None) => println!(
+					"\t\t☑️  {:?}: elected, nothing assigned, backing {:?}",
+					t,
+					Currency::from(support.total),
+				),
+				(None, _) => println!("\t\t❌ {:?}: not elected", t),
 			}
-		});
+		}
+	}
 
-		// remove any of them
-		all_voters_and_stake.retain(|v| !manual.voters_remove.contains(&v.0));
+	if let Some((client, at)) = chain {
+		println!(
+			"\tprojected era reward: {:?}",
+			Currency::from(estimate_reward_of(who, outcome, client, at).await)
+		);
 	}
+}
 
-	// add self-vote
-	for c in candidates.iter() {
-		let self_vote =
-			(c.clone(), to_vote_weight(stake_of(&c, &client, at).await), vec![c.clone()]);
-		all_voters_and_stake.push(self_vote);
+/// The assignments of a voter, as `(voter, [(target, stake)])`.
+type Assignment64 = (AccountId, Vec<(AccountId, u64)>);
+
+/// The support of a winner, as written in the json outputs.
+///
+/// We can't really use u128 or arbitrary_precision of serde for now, so sadly all I can do is
+/// duplicate the types with u64. Not cool but okay for now.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Support64 {
+	total: u64,
+	voters: Vec<(AccountId, u64)>,
+}
+
+type SupportMap64 = BTreeMap<AccountId, Support64>;
+
+/// The supports of each winner, as written in the json outputs.
+fn to_supports_64(supports: &SupportMap<AccountId>) -> SupportMap64 {
+	supports
+		.iter()
+		.map(|(v, s)| {
+			let voters = s
+				.voters
+				.iter()
+				.map(|(w, stake)| (w.clone(), (*stake).try_into().unwrap()))
+				.collect();
+			(v.clone(), Support64 { total: s.total.try_into().unwrap(), voters })
+		})
+		.collect()
+}
+
+/// The node-link graph consumed by d3 based frontends.
+fn d3_graph(supports: &SupportMap64) -> serde_json::Value {
+	let mut nodes = vec![];
+	let mut links = vec![];
+	let mut nominators = BTreeMap::<AccountId, u64>::new();
+	for (v, support) in supports.iter() {
+		nodes.push(serde_json::json!({ "id": v, "group": "validator", "value": support.total }));
+		for (n, stake) in support.voters.iter().filter(|(n, _)| n != v) {
+			*nominators.entry(n.clone()).or_default() += stake;
+			links.push(serde_json::json!({ "source": n, "target": v, "value": stake }));
+		}
+	}
+	for (n, stake) in nominators {
+		nodes.push(serde_json::json!({ "id": n, "group": "nominator", "value": stake }));
+	}
+	serde_json::json!({ "nodes": nodes, "links": links })
+}
+
+/// The edges of a voter that were rerouted by reduce.
+#[derive(serde::Serialize)]
+struct ReducedEdges {
+	who: AccountId,
+	/// The total stake of the voter before reduce.
+	total_before: u64,
+	/// The total stake of the voter after reduce. Always equal to `total_before`.
+	total_after: u64,
+	/// The changed edges, as `(target, stake before, stake after)`. A missing edge has zero stake.
+	edges: Vec<(AccountId, u64, u64)>,
+}
+
+fn to_assignments_64(staked: &[StakedAssignment<AccountId>]) -> Vec<Assignment64> {
+	staked
+		.iter()
+		.map(|a| {
+			let distribution =
+				a.distribution.iter().map(|(t, s)| (t.clone(), (*s).try_into().unwrap())).collect();
+			(a.who.clone(), distribution)
+		})
+		.collect()
+}
+
+/// Write the assignments before and after reduce, and the per-voter diff of the rerouted edges,
+/// into `path`.
+fn write_reduce_diff(
+	pre: &[StakedAssignment<AccountId>],
+	post: &[StakedAssignment<AccountId>],
+	path: &std::path::Path,
+) {
+	let pre = to_assignments_64(pre);
+	let post = to_assignments_64(post);
+	let after = post.iter().cloned().collect::<BTreeMap<_, _>>();
+
+	let mut diff = vec![];
+	for (who, before) in pre.iter() {
+		let after = after.get(who).cloned().unwrap_or_default();
+		let mut edges = BTreeMap::<AccountId, (u64, u64)>::new();
+		before.iter().for_each(|(t, s)| edges.entry(t.clone()).or_default().0 = *s);
+		after.iter().for_each(|(t, s)| edges.entry(t.clone()).or_default().1 = *s);
+		let edges = edges
+			.into_iter()
+			.filter(|(_, (b, a))| b != a)
+			.map(|(t, (b, a))| (t, b, a))
+			.collect::<Vec<_>>();
+		if !edges.is_empty() {
+			diff.push(ReducedEdges {
+				who: who.clone(),
+				total_before: before.iter().map(|(_, s)| s).sum(),
+				total_after: after.iter().map(|(_, s)| s).sum(),
+				edges,
+			});
+		}
+	}
+
+	let removed = diff.iter().flat_map(|d| d.edges.iter()).filter(|(_, _, a)| *a == 0).count();
+	let broken = diff.iter().filter(|d| d.total_before != d.total_after).count();
+	println!(
+		"✂️  reduce rerouted the edges of {} voters, {} edges removed, {} voters with a changed total",
+		diff.len(),
+		removed,
+		broken,
+	);
+	if broken > 0 {
+		log::warn!(target: LOG_TARGET, "reduce changed the total stake of {} voters.", broken);
+	}
+
+	let output = serde_json::json!({
+		"pre_reduce": pre,
+		"post_reduce": post,
+		"diff": diff,
+	});
+	serde_json::to_writer_pretty(&std::fs::File::create(path).unwrap(), &output).unwrap();
+}
+
+/// Compare `Staking::MinimumActiveStake`, if the runtime has it, with the minimum active stake of
+/// the voters of `snapshot`, as computed by the chain when it takes the snapshot of the election.
+///
+/// The chain only updates it at each election, so any change since then shows as a discrepancy.
+async fn check_minimum_active_stake(snapshot: &Snapshot, client: &Client, at: Hash) {
+	let on_chain = match storage::read::<Balance>(
+		storage::value_key(MODULE, b"MinimumActiveStake"),
+		client,
+		at,
+	)
+	.await
+	{
+		Some(on_chain) => on_chain,
+		None => return,
+	};
+	let min_weight = snapshot.voters.iter().map(|(_, w, _)| *w).min().unwrap_or_default();
+	let computed =
+		<network::CurrencyToVoteHandler as Convert<u128, u128>>::convert(min_weight.into());
+	if computed == on_chain {
+		log::info!(target: LOG_TARGET, "MinimumActiveStake {:?} matches.", Currency::from(on_chain));
+	} else {
+		log::warn!(
+			target: LOG_TARGET,
+			"⚠️  MinimumActiveStake on chain is {:?}, computed {:?} from the snapshot. Either the \
+			stakes changed since the last election, or the computation differs.",
+			Currency::from(on_chain),
+			Currency::from(computed),
+		);
 	}
+}
 
+/// Run the whole election pipeline twice on the same snapshot, and return an error if the
+/// outcomes are not exactly equal.
+fn verify_determinism(
+	snapshot: &Snapshot,
+	count: usize,
+	iterations: usize,
+	reduce: bool,
+) -> error::Result<()> {
+	let run = || {
+		election::elect(snapshot, count, Algorithm::SeqPhragmen, iterations, reduce)
+			.map_err(Error::Election)
+	};
+	let (first, second) = (run()?, run()?);
+	if first != second {
+		return Err(Error::Election(format!(
+			"not deterministic: scores {:?} and {:?}, {} and {} winners.",
+			first.score,
+			second.score,
+			first.winners.len(),
+			second.winners.len(),
+		)));
+	}
+	log::info!(target: LOG_TARGET, "✅ election is deterministic.");
+	Ok(())
+}
+
+/// Run the staking election on `--input` alone, with no connection to any node.
+///
+/// Anything that needs the chain is an error, rather than a silent fetch, so that the outcome
+/// only depends on the snapshot file.
+pub async fn run_offline(conf: StakingConfig, verbosity: u64) -> error::Result<()> {
+	let path = conf.input.as_ref().ok_or(Error::Offline("--input"))?;
+	let needs_chain = [
+		(conf.manual_override.is_some(), "--manual-override"),
+		(!conf.simulate_unbond.is_empty(), "--simulate-unbond"),
+		(!conf.simulate_rebond.is_empty(), "--simulate-rebond"),
+		(conf.emulate_fallback, "--emulate-fallback"),
+		(conf.pending_count, "--pending-count"),
+		(conf.assume_count.is_some(), "--assume-count"),
+		(conf.max.is_some() && matches!(conf.max_strategy, MaxStrategy::BagsOrder), "bags-order"),
+	];
+	if let Some((_, what)) = needs_chain.iter().find(|(needed, _)| *needed) {
+		return Err(Error::Offline(what));
+	}
+
+	let mut snapshot = Snapshot::load(path)?;
+	override_inputs(&mut snapshot, &conf)?;
+	let count = conf.count.unwrap_or(snapshot.desired_targets as usize);
+	if let Some(ref path) = conf.save_snapshot {
+		snapshot.save(path)?;
+	}
+	if let Some(max) = conf.max {
+		// bags-order is rejected above.
+		truncate_voters_in(&mut snapshot.voters, max, conf.max_strategy, None);
+	}
+	if conf.verify_determinism {
+		verify_determinism(&snapshot, count, conf.iterations, conf.reduce)?;
+	}
+	if let Some(ref matrix) = conf.matrix {
+		run_matrix(&snapshot, count, matrix);
+		return Ok(());
+	}
+
+	report(&snapshot, count, &conf, verbosity, None).await
+}
+
+/// Run the election of `snapshot`, electing `count` targets, and report the outcome on the console
+/// and into the json outputs of `conf`.
+///
+/// `chain` is the node and the block that the snapshot belongs to, if any. Without it, the
+/// identities, the staking rate, the weight of the solution and the projected reward of `--who`
+/// are left out.
+async fn report(
+	snapshot: &Snapshot,
+	count: usize,
+	conf: &StakingConfig,
+	verbosity: u64,
+	chain: Option<(&Client, Hash)>,
+) -> error::Result<()> {
+	let candidates = &snapshot.targets;
+	let all_voters_and_stake = &snapshot.voters;
 	let slashable_balance_votes = |who: &AccountId| -> VoteWeight {
 		all_voters_and_stake.iter().find(|v| &v.0 == who).map(|v| v.1).unwrap_or_default()
 	};
 
+	let mut timing = BTreeMap::<&'static str, u128>::new();
+
 	// run phragmen
 	t_start!(phragmen_run);
 	let ElectionResult { winners, assignments } =
@@ -230,10 +1209,11 @@ pub async fn run(client: &Client, opt: Opt, conf: StakingConfig) {
 			count,
 			candidates.clone(),
 			all_voters_and_stake.clone(),
-			Some((iterations, 0)),
+			Some((conf.iterations, 0)),
 		)
 		.expect("Phragmen failed to elect.");
 	t_stop!(phragmen_run);
+	timing.insert("phragmen", phragmen_run.elapsed().as_millis());
 
 	let elected_stashes = winners.iter().map(|(s, _)| s.clone()).collect::<Vec<AccountId>>();
 
@@ -241,18 +1221,25 @@ pub async fn run(client: &Client, opt: Opt, conf: StakingConfig) {
 	let mut staked_assignments =
 		assignment_ratio_to_staked(assignments.clone(), slashable_balance_votes);
 	t_stop!(ratio_into_staked_run);
+	timing.insert("ratio_into_staked", ratio_into_staked_run.elapsed().as_millis());
 
 	t_start!(build_support_map_run);
 	let mut supports =
 		to_support_map::<AccountId>(&elected_stashes, staked_assignments.as_slice()).unwrap();
 	t_stop!(build_support_map_run);
+	timing.insert("build_support_map", build_support_map_run.elapsed().as_millis());
 
 	let initial_score = supports.clone().evaluate();
 
-	if reduce {
+	if conf.reduce {
+		let pre_reduce = conf.reduce_diff.as_ref().map(|_| staked_assignments.clone());
 		t_start!(reducing_solution);
 		sp_npos_elections::reduce(&mut staked_assignments);
 		t_stop!(reducing_solution);
+		timing.insert("reduce", reducing_solution.elapsed().as_millis());
+		if let (Some(pre_reduce), Some(path)) = (pre_reduce, conf.reduce_diff.as_ref()) {
+			write_reduce_diff(&pre_reduce, &staked_assignments, path);
+		}
 		// just to check that support has NOT changed
 		let support_after_reduce =
 			to_support_map::<AccountId>(&elected_stashes, staked_assignments.as_slice()).unwrap();
@@ -260,6 +1247,18 @@ pub async fn run(client: &Client, opt: Opt, conf: StakingConfig) {
 		supports = support_after_reduce;
 	}
 
+	// only report on the account in focus.
+	if let Some(ref who) = conf.who {
+		let outcome = Outcome {
+			winners: elected_stashes.clone(),
+			assignments: staked_assignments.clone(),
+			supports: supports.clone(),
+			score: initial_score,
+		};
+		focus(who, snapshot, &outcome, chain).await;
+		return Ok(());
+	}
+
 	let mut nominator_info: BTreeMap<AccountId, Vec<(AccountId, Balance)>> = BTreeMap::new();
 
 	log::info!(target: LOG_TARGET, "💸 Winner Validators:");
@@ -273,10 +1272,17 @@ pub async fn run(client: &Client, opt: Opt, conf: StakingConfig) {
 			println!("⁉️ Self stake for this validator has been removed, seemingly.")
 		}
 
+		let identity = match chain {
+			Some((client, at)) => format!(
+				"{} ",
+				storage::helpers::get_identity::<AccountId, Balance>(s.as_ref(), client, at).await
+			),
+			None => String::new(),
+		};
 		println!(
-			"#{} --> {} [{:?}] [total backing = {:?} ({} voters)] [own backing = {:?}]",
+			"#{} --> {}[{:?}] [total backing = {:?} ({} voters)] [own backing = {:?}]",
 			i + 1,
-			storage::helpers::get_identity::<AccountId, Balance>(s.as_ref(), &client, at).await,
+			identity,
 			s,
 			Currency::from(support.total),
 			if other_count > conf.max_payouts {
@@ -341,54 +1347,357 @@ pub async fn run(client: &Client, opt: Opt, conf: StakingConfig) {
 		"solution score {:?}",
 		initial_score.iter().map(|n| format!("{:?}", Currency::from(*n))).collect::<Vec<_>>(),
 	);
-	log::info!(
-		target: LOG_TARGET,
-		"Staking rate: {}%",
-		initial_score[1] as f64 * 100f64 / network::issuance::get() as f64,
-	);
+	if chain.is_some() {
+		log::info!(
+			target: LOG_TARGET,
+			"Staking rate: {}%",
+			initial_score[1] as f64 * 100f64 / network::issuance::get() as f64,
+		);
+	}
 	log::info!(
 		target: LOG_TARGET,
 		"Phragmen Assignment size {} bytes.",
 		codec::Encode::encode(&assignments).len(),
 	);
 
+	// the weights depend on the runtime, hence are only known with a chain.
+	let (weight, max_extrinsic) = match chain {
+		Some((client, at)) => {
+			let (spec_name, _) = network::get_spec(client, at).await?;
+			let weight = crate::subcommands::mine::solution_weight(
+				&spec_name,
+				candidates.len() as u32,
+				all_voters_and_stake.len() as u32,
+				staked_assignments.len() as u32,
+				elected_stashes.len() as u32,
+			);
+			let max_extrinsic = crate::subcommands::mine::max_extrinsic(client, at).await?;
+			log::info!(
+				target: LOG_TARGET,
+				"Estimated solution weight {} / max extrinsic weight {} ({:.2}%).",
+				weight,
+				max_extrinsic,
+				weight as f64 * 100f64 / max_extrinsic.max(1) as f64,
+			);
+			if weight > max_extrinsic {
+				log::warn!(
+					target: LOG_TARGET,
+					"⚠️  The solution is too heavy to be submitted as is and must be trimmed."
+				);
+			}
+			(Some(weight), Some(max_extrinsic))
+		}
+		None => (None, None),
+	};
+
+	// the supports alone, keyed by winner, which is what most dashboards need.
+	if let Some(ref path) = conf.supports_output {
+		let supports_json = to_supports_64(&supports);
+		serde_json::to_writer_pretty(&std::fs::File::create(path).unwrap(), &supports_json)
+			.unwrap();
+		println!("📦 supports of {} winners written to {:?}", supports_json.len(), path);
+	}
+
 	// potentially write to json file
-	if let Some(output_file) = conf.output {
+	if let Some(ref output_file) = conf.output {
 		use std::fs::File;
 
-		// We can't really use u128 or arbitrary_precision of serde for now, so sadly all I can do
-		// is duplicate the types with u64. Not cool but okay for now.
-		#[derive(serde::Serialize, serde::Deserialize)]
-		struct Support64 {
-			total: u64,
-			voters: Vec<(AccountId, u64)>,
-		}
-
-		type SupportMap64 = std::collections::BTreeMap<AccountId, Support64>;
-
-		impl From<sp_npos_elections::Support<AccountId>> for Support64 {
-			fn from(t: sp_npos_elections::Support<AccountId>) -> Self {
-				Self {
-					total: t.total.try_into().unwrap(),
-					voters: t
-						.voters
-						.into_iter()
-						.map(|(w, v)| (w, (v).try_into().unwrap()))
-						.collect::<Vec<_>>(),
+		let supports_64 = to_supports_64(&supports);
+
+		let fields = if conf.fields.is_empty() {
+			vec![OutputField::Supports, OutputField::Winners]
+		} else {
+			conf.fields.clone()
+		};
+		let mut sections = serde_json::Map::new();
+		if conf.schema_version > 0 {
+			sections.insert("schema_version".into(), OUTPUT_SCHEMA_VERSION.into());
+		}
+		for field in fields {
+			let (key, value) = match field {
+				OutputField::Winners => ("winners", serde_json::json!(elected_stashes)),
+				OutputField::Assignments => {
+					("assignments", serde_json::json!(to_assignments_64(&staked_assignments)))
 				}
-			}
+				OutputField::Supports => ("supports", serde_json::json!(supports_64)),
+				OutputField::Snapshot => (
+					"snapshot",
+					serde_json::json!({ "targets": candidates, "voters": all_voters_and_stake }),
+				),
+				OutputField::Metrics => (
+					"metrics",
+					serde_json::json!({
+						// the sum of squares overflows json numbers, hence strings.
+						"score": initial_score.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+						"candidates": candidates.len(),
+						"voters": all_voters_and_stake.len(),
+						"winners": elected_stashes.len(),
+						"assignments": staked_assignments.len(),
+						"oversubscribed": oversubscribed,
+						"weight": weight,
+						"max_extrinsic_weight": max_extrinsic,
+					}),
+				),
+				OutputField::Timing => ("timing", serde_json::json!(timing)),
+			};
+			sections.insert(key.into(), value);
 		}
 
-		let mut supports_64 = SupportMap64::new();
-		for (k, v) in supports.into_iter() {
-			supports_64.insert(k, v.into());
+		let output = match conf.format {
+			OutputFormat::Default => serde_json::Value::Object(sections),
+			OutputFormat::D3 => d3_graph(&supports_64),
+		};
+
+		serde_json::to_writer_pretty(&File::create(output_file).unwrap(), &output).unwrap();
+	}
+	Ok(())
+}
+
+/// Replace the voters and the targets of `snapshot` with the ones of `--voters-file` and
+/// `--targets-file`, if given.
+fn override_inputs(snapshot: &mut Snapshot, conf: &StakingConfig) -> error::Result<()> {
+	if let Some(ref path) = conf.voters_file {
+		let file = std::fs::read(path).map_err(|e| Error::file(path, e))?;
+		snapshot.voters = serde_json::from_slice(&file).map_err(|e| Error::file(path, e))?;
+		log::info!(target: LOG_TARGET, "{} voters read from {:?}", snapshot.voters.len(), path);
+	}
+	if let Some(ref path) = conf.targets_file {
+		snapshot.targets = validator_check::accounts_from_file(path)?;
+		log::info!(target: LOG_TARGET, "{} targets read from {:?}", snapshot.targets.len(), path);
+	}
+	Ok(())
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, conf: StakingConfig) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let mut snapshot = match conf.input {
+		Some(ref path) => Snapshot::load(path)?,
+		None => Snapshot::fetch(client, at).await?,
+	};
+	override_inputs(&mut snapshot, &conf)?;
+	let val_count = snapshot.desired_targets as usize;
+	let verbosity = opt.verbosity;
+	let iterations = conf.iterations;
+	let count = conf.count.unwrap_or(val_count);
+	let reduce = conf.reduce;
+
+	if count != val_count {
+		log::warn!(
+			target: LOG_TARGET,
+			"`count` provided ({:?}) differs from validator count on-chain ({}).",
+			count,
+			val_count,
+		);
+	}
+
+	if let Some(ref path) = conf.save_snapshot {
+		snapshot.save(path)?;
+	}
+
+	if conf.input.is_none() {
+		check_minimum_active_stake(&snapshot, client, at).await;
+	}
+
+	// stash key of all wannabe candidates.
+	let mut candidates = snapshot.targets;
+
+	// stash key of current voters, including self votes.
+	let mut all_voters_and_stake = snapshot.voters;
+
+	if let Some(path) = conf.manual_override {
+		#[derive(serde::Serialize, serde::Deserialize)]
+		struct Override {
+			voters: Vec<(AccountId, u64, Vec<AccountId>)>,
+			voters_remove: Vec<AccountId>,
+			candidates: Vec<AccountId>,
+			candidates_remove: Vec<AccountId>,
+		}
+
+		let file = std::fs::read(path).unwrap();
+		let json_str = std::str::from_utf8(file.as_ref()).unwrap();
+		let manual: Override = serde_json::from_str(json_str).unwrap();
+
+		// add any additional candidates, with their self-vote.
+		for c in manual.candidates.iter() {
+			if candidates.contains(c) {
+				println!("manual override: {:?} is already a candidate.", c);
+			} else {
+				println!("manual override: {:?} is added as candidate.", c);
+				candidates.push(c.clone());
+				all_voters_and_stake.extend(get_self_votes(&[c.clone()], client, at).await);
+			}
 		}
+		// remove any that are in removal list, with their self-vote.
+		candidates.retain(|c| !manual.candidates_remove.contains(c));
+		all_voters_and_stake
+			.retain(|(v, _, t)| !(manual.candidates_remove.contains(v) && t == &vec![v.clone()]));
 
-		let output = serde_json::json!({
-			"supports": supports_64,
-			"winners": elected_stashes,
+		// add any new votes
+		manual.voters.iter().for_each(|v| {
+			if let Some(mut already_existing_voter) =
+				all_voters_and_stake.iter_mut().find(|vv| vv.0 == v.0)
+			{
+				println!("manual override: {:?} is already a voter. Overriding votes.", v.0);
+				already_existing_voter.1 = v.1.into();
+				already_existing_voter.2 = v.2.clone();
+			} else {
+				println!("manual override: {:?} is added as voters.", v.0);
+				all_voters_and_stake.push(v.clone())
+			}
 		});
 
-		serde_json::to_writer_pretty(&File::create(output_file).unwrap(), &output).unwrap();
+		// remove any of them
+		all_voters_and_stake.retain(|v| !manual.voters_remove.contains(&v.0));
+	}
+
+	if let Some(max) = conf.max {
+		truncate_voters(&mut all_voters_and_stake, max, conf.max_strategy, client, at).await?;
+	}
+
+	if !conf.simulate_unbond.is_empty() || !conf.simulate_rebond.is_empty() {
+		let snapshot = Snapshot {
+			at: Some(at),
+			desired_targets: count as u32,
+			targets: candidates.clone(),
+			voters: all_voters_and_stake.clone(),
+		};
+		simulate_stake_changes(
+			&snapshot,
+			count,
+			iterations,
+			&conf.simulate_unbond,
+			&conf.simulate_rebond,
+			client,
+			at,
+		)
+		.await;
+		// the rest of the election runs with the simulated stakes.
+		apply_stake_changes(
+			&mut all_voters_and_stake,
+			&conf.simulate_unbond,
+			&conf.simulate_rebond,
+		);
+	}
+
+	if conf.emulate_fallback {
+		let snapshot = Snapshot {
+			at: Some(at),
+			desired_targets: count as u32,
+			targets: candidates.clone(),
+			voters: all_voters_and_stake.clone(),
+		};
+		emulate_fallback(&snapshot, count, iterations, client, at).await?;
+	}
+
+	if conf.verify_determinism {
+		let snapshot = Snapshot {
+			at: Some(at),
+			desired_targets: count as u32,
+			targets: candidates.clone(),
+			voters: all_voters_and_stake.clone(),
+		};
+		verify_determinism(&snapshot, count, iterations, reduce)?;
+	}
+
+	if let Some(ref matrix) = conf.matrix {
+		let snapshot = Snapshot {
+			at: Some(at),
+			desired_targets: count as u32,
+			targets: candidates.clone(),
+			voters: all_voters_and_stake.clone(),
+		};
+		run_matrix(&snapshot, count, matrix);
+		return Ok(());
+	}
+
+	if conf.pending_count || conf.assume_count.is_some() {
+		let new_count = match conf.assume_count {
+			Some(new_count) => new_count,
+			None => match pending_validator_count(val_count as u32, client, at).await {
+				Some((new_count, index)) => {
+					println!("🗳  referendum #{} sets the validator count to {}.", index, new_count);
+					new_count as usize
+				}
+				None => {
+					println!("🗳  no ongoing referendum changes the validator count.");
+					return Ok(());
+				}
+			},
+		};
+		let snapshot = Snapshot {
+			at: Some(at),
+			desired_targets: count as u32,
+			targets: candidates.clone(),
+			voters: all_voters_and_stake.clone(),
+		};
+		compare_counts(&snapshot, count, new_count, iterations, client, at).await;
+		return Ok(());
+	}
+
+	let snapshot = Snapshot {
+		at: Some(at),
+		desired_targets: count as u32,
+		targets: candidates,
+		voters: all_voters_and_stake,
+	};
+	report(&snapshot, count, &conf, verbosity, Some((client, at))).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+	#[test]
+	fn stake_change_from_str_works() {
+		let change = StakeChange::from_str(&format!("{}:1000", ALICE)).unwrap();
+		assert_eq!(change.who, AccountId::from_str(ALICE).unwrap());
+		assert_eq!(change.amount, 1000);
+
+		// the account, the amount, and nothing more.
+		assert!(StakeChange::from_str(ALICE).is_err());
+		assert!(StakeChange::from_str(&format!("{}:", ALICE)).is_err());
+		assert!(StakeChange::from_str(&format!("{}:-1", ALICE)).is_err());
+		assert!(StakeChange::from_str(&format!("{}:1:2", ALICE)).is_err());
+		assert!(StakeChange::from_str("alice:1000").is_err());
+	}
+
+	#[test]
+	fn max_strategy_from_str_works() {
+		assert!(matches!(MaxStrategy::from_str("top-stake"), Ok(MaxStrategy::TopStake)));
+		assert!(matches!(MaxStrategy::from_str("bags-order"), Ok(MaxStrategy::BagsOrder)));
+		assert!(matches!(MaxStrategy::from_str("random:42"), Ok(MaxStrategy::Random(42))));
+
+		// random needs a seed.
+		assert!(MaxStrategy::from_str("random").is_err());
+		assert!(MaxStrategy::from_str("random:").is_err());
+		assert!(MaxStrategy::from_str("random:-1").is_err());
+		assert!(MaxStrategy::from_str("top").is_err());
+	}
+
+	#[test]
+	fn config_matrix_from_str_works() {
+		let matrix =
+			ConfigMatrix::from_str("algorithm=seq-phragmen,phragmms;iterations=0,10;reduce=off,on")
+				.unwrap();
+		let configs = matrix.configs();
+		assert_eq!(configs.len(), 8);
+		assert_eq!(configs[0], (Algorithm::SeqPhragmen, 0, false));
+		assert_eq!(configs[7], (Algorithm::Phragmms, 10, true));
+
+		// missing keys take their default, in any order.
+		let matrix = ConfigMatrix::from_str("reduce=on;iterations=5").unwrap();
+		assert_eq!(matrix.configs(), vec![(Algorithm::SeqPhragmen, 5, true)]);
+		let matrix = ConfigMatrix::from_str("").unwrap();
+		assert_eq!(matrix.configs(), vec![(Algorithm::SeqPhragmen, 0, false)]);
+
+		assert!(ConfigMatrix::from_str("algorithm=phragmen").is_err());
+		assert!(ConfigMatrix::from_str("iterations=ten").is_err());
+		assert!(ConfigMatrix::from_str("iterations=").is_err());
+		assert!(ConfigMatrix::from_str("reduce=yes").is_err());
+		assert!(ConfigMatrix::from_str("reduce").is_err());
+		assert!(ConfigMatrix::from_str("balancing=10").is_err());
 	}
 }
@@ -0,0 +1,142 @@
+//! Stress test the election pipeline on synthetic snapshots of increasing size.
+
+use crate::{
+	error::{self, Error},
+	primitives::AccountId,
+	subcommands::{bench::elapsed_ms, generate},
+	GenerateConfig, StressConfig,
+};
+use sp_npos_elections::*;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	io::Write,
+	time::Instant,
+};
+
+/// Current resident memory of this process in bytes. Only available on linux.
+fn resident_memory() -> Option<u64> {
+	let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+	let pages = statm.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+	Some(pages * 4096)
+}
+
+fn display_mb(bytes: Option<i64>) -> String {
+	bytes.map_or("?".into(), |b| format!("{:+}MB", b / 1024 / 1024))
+}
+
+/// Time and memory usage of a single phase.
+struct Phase {
+	name: &'static str,
+	ms: f64,
+	/// The change of the resident memory over the phase. Memory freed by the allocator is not
+	/// always returned to the system, hence this is rather the growth of the process.
+	memory: Option<i64>,
+}
+
+/// Measure a phase.
+fn measure<R>(name: &'static str, phases: &mut Vec<Phase>, f: impl FnOnce() -> R) -> R {
+	let before = resident_memory();
+	let start = Instant::now();
+	let r = f();
+	let ms = elapsed_ms(start);
+	let memory = before.zip(resident_memory()).map(|(b, a)| a as i64 - b as i64);
+	phases.push(Phase { name, ms, memory });
+	r
+}
+
+/// Main run function of the sub-command.
+///
+/// Note that this sub-command does not need a connection to any node.
+pub fn run(conf: StressConfig) -> error::Result<()> {
+	if conf.factor <= 1 {
+		return Err(Error::Invalid("factor must be more than one.".into()));
+	}
+	if conf.distribution == generate::StakeDistribution::Shape {
+		return Err(Error::Invalid(
+			"the shape distribution needs a real snapshot to shape.".into(),
+		));
+	}
+	let mut csv = match conf.csv {
+		Some(ref p) => {
+			let mut f = std::fs::File::create(p).map_err(|e| Error::file(p, e))?;
+			writeln!(f, "voters,targets,phase,ms,resident_delta_bytes")
+				.map_err(|e| Error::file(p, e))?;
+			Some((p, f))
+		}
+		None => None,
+	};
+
+	let mut voters = conf.from;
+	while voters <= conf.to {
+		let mut phases = vec![];
+		let gen_conf = GenerateConfig {
+			output: Default::default(),
+			voters,
+			targets: conf.targets,
+			desired_targets: conf.desired_targets,
+			edges: 16,
+			distribution: conf.distribution,
+			shape_of: None,
+			min_stake: 1000,
+			max_stake: 1_000_000,
+			zipf_exponent: 1.0,
+			seed: conf.seed,
+		};
+		let snapshot = measure("generate", &mut phases, || generate::generate(&gen_conf))?;
+		let count = snapshot.desired_targets as usize;
+		let weights =
+			snapshot.voters.iter().map(|(v, w, _)| (v.clone(), *w)).collect::<BTreeMap<_, _>>();
+		let weight_of =
+			|who: &AccountId| -> VoteWeight { weights.get(who).cloned().unwrap_or_default() };
+
+		let (_, mut election_voters) = measure("seq-phragmen", &mut phases, || {
+			let (candidates, election_voters) =
+				setup_inputs(snapshot.targets.clone(), snapshot.voters.clone());
+			seq_phragmen_core::<AccountId>(count, candidates, election_voters)
+				.map_err(|e| Error::Election(format!("{:?}", e)))
+		})?;
+		measure("balancing", &mut phases, || {
+			balance::<AccountId>(&mut election_voters, conf.iterations, 0)
+		});
+
+		let assignments = election_voters
+			.into_iter()
+			.filter_map(|v| v.into_assignment::<pallet_staking::ChainAccuracy>())
+			.collect::<Vec<_>>();
+		// the candidates do not expose whether they are elected, but only winners are assigned to.
+		let winners = assignments
+			.iter()
+			.flat_map(|a| a.distribution.iter().map(|(t, _)| t.clone()))
+			.collect::<BTreeSet<_>>()
+			.into_iter()
+			.collect::<Vec<_>>();
+		let mut staked = measure("ratio-to-staked", &mut phases, || {
+			assignment_ratio_to_staked(assignments, weight_of)
+		});
+		let _ = measure("support-map", &mut phases, || {
+			to_support_map::<AccountId>(&winners, &staked)
+				.map_err(|e| Error::Election(format!("{:?}", e)))
+		})?;
+		measure("reduce", &mut phases, || reduce(&mut staked));
+
+		println!("🔥 {} voters / {} targets / electing {}", voters, conf.targets, count);
+		for p in phases.iter() {
+			println!("  {:<16} {:>12.2}ms  resident {}", p.name, p.ms, display_mb(p.memory));
+			if let Some((path, ref mut f)) = csv {
+				writeln!(
+					f,
+					"{},{},{},{:.2},{}",
+					voters,
+					conf.targets,
+					p.name,
+					p.ms,
+					p.memory.map_or(String::new(), |m| m.to_string())
+				)
+				.map_err(|e| Error::file(path, e))?;
+			}
+		}
+
+		voters *= conf.factor;
+	}
+	Ok(())
+}
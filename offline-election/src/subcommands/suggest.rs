@@ -0,0 +1,248 @@
+//! Suggest nomination targets, subject to declarative constraints.
+//!
+//! The constraints are read from a json file, for example:
+//!
+//! ```json
+//! {
+//!   "max_commission": 10.0,
+//!   "min_self_stake": 10000000000000,
+//!   "require_verified_identity": true,
+//!   "exclude_slashed_within": 28,
+//!   "max_per_parent": 1,
+//!   "max_risk": 30,
+//!   "max_points_variation": 25.0,
+//!   "preferred": ["5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT"]
+//! }
+//! ```
+//!
+//! All fields are optional.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance, Hash},
+	storage,
+	subcommands::{session_info, staking, validator_check},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use pallet_staking::{EraIndex, EraRewardPoints};
+use sp_npos_elections::SupportMap;
+use sp_runtime::Perbill;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// The constraints that the suggested targets must respect.
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Constraints {
+	/// Maximum commission, in percent.
+	pub max_commission: Option<f64>,
+	/// Minimum self stake, in the smallest unit of the currency.
+	pub min_self_stake: Option<Balance>,
+	/// Only accept validators with a `Reasonable` or `KnownGood` identity judgement.
+	pub require_verified_identity: bool,
+	/// Exclude the validators slashed within this number of eras.
+	pub exclude_slashed_within: Option<EraIndex>,
+	/// Maximum number of validators with the same identity parent. Defaults to 1, since
+	/// nominating many nodes of the same operator defeats the purpose of nominating many.
+	pub max_per_parent: Option<usize>,
+	/// Maximum heuristic slash-risk score, out of 100. See `validator-check`.
+	pub max_risk: Option<u32>,
+	/// Maximum variation of the era points, in percent of their mean. Erratic producers make for
+	/// an erratic and lower APY. See `validator-check --erratic`.
+	pub max_points_variation: Option<f64>,
+	/// Validators to always include first, if they respect the other constraints.
+	pub preferred: Vec<AccountId>,
+}
+
+impl Constraints {
+	/// Read the constraints from a json file.
+	pub(crate) fn load(path: &PathBuf) -> Self {
+		let file = std::fs::read(path).expect("Failed to read constraints file.");
+		serde_json::from_slice(&file).expect("Invalid constraints file.")
+	}
+}
+
+/// A candidate, with all the information needed to check the constraints.
+#[derive(Debug, Clone)]
+pub(crate) struct Candidate {
+	pub who: AccountId,
+	pub commission: Perbill,
+	pub self_stake: Balance,
+	/// The stash of the identity parent, or the candidate itself if it has none.
+	pub operator: AccountId,
+	/// The backing of the candidate in the predicted election, if it is predicted to be elected.
+	pub predicted_backing: Option<Balance>,
+}
+
+impl Candidate {
+	/// The expected reward per unit of stake of a new nominator. Zero if not predicted to be
+	/// elected.
+	pub(crate) fn score(&self) -> f64 {
+		self.predicted_backing.map_or(0f64, |b| {
+			(1f64 - self.commission.deconstruct() as f64 / 1_000_000_000f64) / b.max(1) as f64
+		})
+	}
+}
+
+/// The operator of a validator: the stash of its identity parent, or itself if it has none.
+pub(crate) async fn operator_of(who: &AccountId, client: &Client, at: Hash) -> AccountId {
+	storage::helpers::get_parent::<AccountId>(who.as_ref(), client, at)
+		.await
+		.unwrap_or_else(|| who.clone())
+}
+
+/// Group the given validators by their operator, only returning the operators of more than one.
+pub(crate) async fn shared_operators(
+	validators: &[AccountId],
+	client: &Client,
+	at: Hash,
+) -> BTreeMap<AccountId, Vec<AccountId>> {
+	let mut operators: BTreeMap<AccountId, Vec<AccountId>> = BTreeMap::new();
+	for v in validators {
+		operators.entry(operator_of(v, client, at).await).or_default().push(v.clone());
+	}
+	operators.into_iter().filter(|(_, vs)| vs.len() > 1).collect()
+}
+
+/// Fetch the information of a candidate.
+pub(crate) async fn candidate(
+	who: &AccountId,
+	supports: &SupportMap<AccountId>,
+	client: &Client,
+	at: Hash,
+) -> Candidate {
+	Candidate {
+		who: who.clone(),
+		commission: staking::commission_of(who, client, at).await.unwrap_or_default(),
+		self_stake: staking::stake_of(who, client, at).await,
+		operator: operator_of(who, client, at).await,
+		predicted_backing: supports.get(who).map(|s| s.total),
+	}
+}
+
+/// Check the constraints that apply to a single candidate, returning the reason of rejection, if
+/// any.
+async fn violation(
+	c: &Candidate,
+	constraints: &Constraints,
+	active_era: EraIndex,
+	history: &[EraRewardPoints<AccountId>],
+	client: &Client,
+	at: Hash,
+) -> Option<&'static str> {
+	if let Some(max) = constraints.max_commission {
+		if c.commission > Perbill::from_parts((max * 10_000_000f64) as u32) {
+			return Some("commission");
+		}
+	}
+	if constraints.min_self_stake.map_or(false, |min| c.self_stake < min) {
+		return Some("self stake");
+	}
+	if let Some(eras) = constraints.exclude_slashed_within {
+		if let Some(spans) = staking::slashing_span_of(&c.who, client, at).await {
+			if spans.last_nonzero_slash() + eras >= active_era {
+				return Some("slashed");
+			}
+		}
+	}
+	if constraints.require_verified_identity
+		&& !storage::helpers::is_verified::<AccountId, Balance>(c.who.as_ref(), client, at).await
+	{
+		return Some("identity");
+	}
+	if let Some(max) = constraints.max_risk {
+		if validator_check::risk_of(&c.who, history, client, at).await.score > max {
+			return Some("risk");
+		}
+	}
+	if let Some(max) = constraints.max_points_variation {
+		if validator_check::consistency_of(&c.who, history).variation * 100f64 > max {
+			return Some("erratic points");
+		}
+	}
+	None
+}
+
+/// Select up to `count` targets out of `candidates` that respect all the constraints.
+///
+/// Preferred candidates come first, then the rest by descending score.
+pub(crate) async fn select(
+	mut candidates: Vec<Candidate>,
+	constraints: &Constraints,
+	count: usize,
+	client: &Client,
+	at: Hash,
+) -> (Vec<Candidate>, BTreeMap<&'static str, usize>) {
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let history = if constraints.max_points_variation.is_some() || constraints.max_risk.is_some() {
+		validator_check::recent_points(client, at).await
+	} else {
+		vec![]
+	};
+	candidates.sort_by(|a, b| {
+		let preferred = |c: &Candidate| constraints.preferred.contains(&c.who);
+		preferred(b)
+			.cmp(&preferred(a))
+			.then(b.score().partial_cmp(&a.score()).unwrap_or(std::cmp::Ordering::Equal))
+	});
+
+	let mut selected: Vec<Candidate> = vec![];
+	let mut rejected: BTreeMap<&'static str, usize> = BTreeMap::new();
+	for c in candidates {
+		if selected.len() == count {
+			break;
+		}
+		if let Some(reason) = violation(&c, constraints, active_era, &history, client, at).await {
+			*rejected.entry(reason).or_default() += 1;
+			continue;
+		}
+		let same_operator = selected.iter().filter(|s| s.operator == c.operator).count();
+		if same_operator >= constraints.max_per_parent.unwrap_or(1) {
+			*rejected.entry("operator").or_default() += 1;
+			continue;
+		}
+		selected.push(c);
+	}
+	(selected, rejected)
+}
+
+/// Main run function of the sub-command.
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	count: usize,
+	constraints: Option<PathBuf>,
+) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let constraints = constraints.as_ref().map(Constraints::load).unwrap_or_default();
+	log::info!(target: LOG_TARGET, "constraints: {:?}", constraints);
+
+	let (_, supports) = staking::predict(client, at).await?;
+	let mut candidates = vec![];
+	for who in staking::get_candidates(client, at).await {
+		candidates.push(candidate(&who, &supports, client, at).await);
+	}
+
+	let (selected, rejected) = select(candidates, &constraints, count, client, at).await;
+	println!("🎯 Suggested targets:");
+	for (i, c) in selected.iter().enumerate() {
+		println!(
+			"#{} [{}] {:?} / commission = {:?} / self stake = {:?} / predicted backing = {:?}",
+			i + 1,
+			storage::helpers::get_identity::<AccountId, Balance>(c.who.as_ref(), client, at).await,
+			c.who,
+			c.commission,
+			Currency::from(c.self_stake),
+			c.predicted_backing.map(Currency::from),
+		);
+		if c.operator != c.who {
+			println!("\toperated by {:?}", c.operator);
+		}
+	}
+	if selected.len() < count {
+		log::warn!("only {} out of {} targets respect the constraints.", selected.len(), count);
+	}
+	for (reason, count) in rejected.iter() {
+		println!("🚫 {} candidates rejected because of {}.", count, reason);
+	}
+	Ok(())
+}
@@ -0,0 +1,151 @@
+//! List all the validator candidates, active and waiting.
+
+use crate::{
+	error,
+	election::{self, Algorithm, Outcome},
+	network,
+	primitives::{AccountId, Balance, Hash},
+	snapshot::Snapshot,
+	storage,
+	subcommands::staking,
+	Client, Currency, Opt, LOG_TARGET,
+};
+use sp_runtime::traits::Convert;
+use std::collections::BTreeMap;
+
+/// A validator candidate, as seen in the snapshot.
+pub(crate) struct Target {
+	pub who: AccountId,
+	pub commission: sp_runtime::Perbill,
+	pub self_stake: Balance,
+	pub backing: Balance,
+	pub share: Balance,
+	pub nominators: usize,
+	pub active: bool,
+	pub predicted: bool,
+}
+
+fn to_balance(weight: u128) -> Balance {
+	<network::CurrencyToVoteHandler as Convert<u128, u128>>::convert(weight)
+}
+
+/// Build the list of all validator candidates of the snapshot.
+///
+/// The backing of each candidate is the sum of the stake of all of its voters, self-vote included,
+/// as if each voter backed only this candidate. The share is the same sum, with the stake of each
+/// voter split evenly between all of its votes.
+pub(crate) async fn targets_of(
+	snapshot: &Snapshot,
+	active: &[AccountId],
+	predicted: &[AccountId],
+	client: &Client,
+	at: Hash,
+) -> Vec<Target> {
+	let mut backing: BTreeMap<&AccountId, (u128, u128, usize)> = BTreeMap::new();
+	let mut self_stake: BTreeMap<&AccountId, u128> = BTreeMap::new();
+	for (who, weight, votes) in snapshot.voters.iter() {
+		for t in votes.iter() {
+			let entry = backing.entry(t).or_default();
+			entry.0 += *weight as u128;
+			entry.1 += *weight as u128 / votes.len() as u128;
+			if t == who {
+				self_stake.insert(t, *weight as u128);
+			} else {
+				entry.2 += 1;
+			}
+		}
+	}
+
+	let mut targets = vec![];
+	for t in snapshot.targets.iter() {
+		let (total, share, nominators) = backing.get(t).copied().unwrap_or_default();
+		targets.push(Target {
+			who: t.clone(),
+			commission: staking::commission_of(t, client, at).await.unwrap_or_default(),
+			self_stake: to_balance(self_stake.get(t).copied().unwrap_or_default()),
+			backing: to_balance(total),
+			share: to_balance(share),
+			nominators,
+			active: active.contains(t),
+			predicted: predicted.contains(t),
+		});
+	}
+	targets.sort_by_key(|t| std::cmp::Reverse(t.backing));
+	targets
+}
+
+/// Print the waiting candidates, sorted by the additional backing that they need to enter the
+/// predicted set.
+///
+/// This is only an estimate: the cutoff is the lowest backing among the predicted winners, and the
+/// backing of each waiting candidate is its share, while the election may well distribute the
+/// stake of its voters differently.
+async fn print_waiting(targets: &[Target], cutoff: Balance, client: &Client, at: Hash) {
+	let mut waiting = targets
+		.iter()
+		.filter(|t| !t.predicted)
+		.map(|t| (t, cutoff.saturating_sub(t.share)))
+		.collect::<Vec<_>>();
+	waiting.sort_by_key(|(_, distance)| *distance);
+
+	println!("✂️  cutoff of the predicted set: {:?}", Currency::from(cutoff));
+	for (i, (t, distance)) in waiting.into_iter().enumerate() {
+		println!(
+			"#{} {} [{:?}] / share {:?} / needs {:?} more",
+			i + 1,
+			storage::helpers::get_identity::<AccountId, Balance>(t.who.as_ref(), client, at).await,
+			t.who,
+			Currency::from(t.share),
+			Currency::from(distance),
+		);
+	}
+}
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, waiting: bool) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let snapshot = Snapshot::fetch(client, at).await?;
+	let (era, exposures) = network::get_validators_and_expo_at(client, at).await;
+	let active = exposures.into_iter().map(|(v, _)| v).collect::<Vec<_>>();
+	let Outcome { winners: predicted, supports, .. } = election::elect(
+		&snapshot,
+		snapshot.desired_targets as usize,
+		Algorithm::SeqPhragmen,
+		0,
+		false,
+	)
+	.expect("Phragmen failed to elect.");
+
+	let targets = targets_of(&snapshot, &active, &predicted, client, at).await;
+	if waiting {
+		let cutoff = supports.values().map(|s| s.total).min().unwrap_or_default();
+		print_waiting(&targets, cutoff, client, at).await;
+		return Ok(());
+	}
+
+	log::info!(
+		target: LOG_TARGET,
+		"{} candidates, {} active in era {}, {} predicted.",
+		targets.len(),
+		active.len(),
+		era,
+		predicted.len(),
+	);
+
+	for (i, t) in targets.iter().enumerate() {
+		println!(
+			"#{} {} {} {} [{:?}] / commission {:?} / self {:?} / backing {:?} from {} nominators",
+			i + 1,
+			if t.active { "🟢" } else { "⚪" },
+			if t.predicted { "🔮" } else { "  " },
+			storage::helpers::get_identity::<AccountId, Balance>(t.who.as_ref(), client, at).await,
+			t.who,
+			t.commission,
+			Currency::from(t.self_stake),
+			Currency::from(t.backing),
+			t.nominators,
+		);
+	}
+	println!("🟢 active in era {} / ⚪ waiting / 🔮 predicted to be elected.", era);
+	Ok(())
+}
@@ -0,0 +1,260 @@
+//! Export the full trace of the sequential phragmen algorithm as json.
+//!
+//! The trace is produced by a standalone floating point implementation that mirrors the
+//! `sp-npos-elections` one step by step. It is meant for visualization and education; the
+//! numbers can differ from the on-chain, fixed point implementation in the last digits.
+
+use crate::{
+	error::{self, Error},
+	primitives::AccountId,
+	snapshot::Snapshot,
+	TraceConfig,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The score of a candidate in a round.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct CandidateScore {
+	pub candidate: AccountId,
+	pub approval_stake: f64,
+	pub score: f64,
+}
+
+/// A round of the sequential phragmen algorithm.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct Round {
+	pub round: usize,
+	pub scores: Vec<CandidateScore>,
+	pub elected: AccountId,
+}
+
+/// The weight of an edge from a voter to an elected target.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct Edge {
+	pub voter: AccountId,
+	pub target: AccountId,
+	pub weight: f64,
+}
+
+/// The edge weights after an iteration of balancing. Iteration 0 is the phragmen output.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct Iteration {
+	pub iteration: usize,
+	pub edges: Vec<Edge>,
+	pub backings: BTreeMap<AccountId, f64>,
+}
+
+/// The full trace of an election.
+#[derive(Serialize, Debug, Clone, Default)]
+pub(crate) struct Trace {
+	pub rounds: Vec<Round>,
+	pub balancing: Vec<Iteration>,
+}
+
+struct Voter {
+	who: AccountId,
+	budget: f64,
+	load: f64,
+	// (candidate index, load, weight)
+	edges: Vec<(usize, f64, f64)>,
+}
+
+struct Candidate {
+	who: AccountId,
+	approval_stake: f64,
+	backed_stake: f64,
+	elected: bool,
+}
+
+fn snapshot_edges(voters: &[Voter], candidates: &[Candidate]) -> Vec<Edge> {
+	voters
+		.iter()
+		.flat_map(|v| {
+			v.edges.iter().filter(|(c, _, _)| candidates[*c].elected).map(move |(c, _, w)| Edge {
+				voter: v.who.clone(),
+				target: candidates[*c].who.clone(),
+				weight: *w,
+			})
+		})
+		.collect()
+}
+
+fn snapshot_backings(candidates: &[Candidate]) -> BTreeMap<AccountId, f64> {
+	candidates.iter().filter(|c| c.elected).map(|c| (c.who.clone(), c.backed_stake)).collect()
+}
+
+/// Balance the stake of a single voter among its elected edges, as in `sp-npos-elections`.
+fn balance_voter(voter: &mut Voter, candidates: &mut [Candidate]) {
+	let mut elected_edges =
+		voter.edges.iter_mut().filter(|(c, _, _)| candidates[*c].elected).collect::<Vec<_>>();
+	if elected_edges.len() <= 1 {
+		return;
+	}
+
+	for (c, _, w) in elected_edges.iter_mut() {
+		candidates[*c].backed_stake -= *w;
+		*w = 0f64;
+	}
+	elected_edges.sort_by(|a, b| {
+		candidates[a.0].backed_stake.partial_cmp(&candidates[b.0].backed_stake).unwrap()
+	});
+
+	let mut cumulative = 0f64;
+	let mut last_index = elected_edges.len() - 1;
+	for (idx, (c, _, _)) in elected_edges.iter().enumerate() {
+		let backed = candidates[*c].backed_stake;
+		if backed * idx as f64 - cumulative > voter.budget {
+			last_index = idx - 1;
+			break;
+		}
+		cumulative += backed;
+	}
+
+	let last_stake = candidates[elected_edges[last_index].0].backed_stake;
+	let ways_to_split = (last_index + 1) as f64;
+	let excess = voter.budget + cumulative - last_stake * ways_to_split;
+	for (c, _, w) in elected_edges.into_iter().take(last_index + 1) {
+		*w = excess / ways_to_split + last_stake - candidates[*c].backed_stake;
+		candidates[*c].backed_stake += *w;
+	}
+}
+
+/// Run sequential phragmen on the snapshot, followed by `iterations` rounds of balancing, and
+/// record every step.
+pub(crate) fn trace(snapshot: &Snapshot, count: usize, iterations: usize) -> Trace {
+	let index_of = snapshot
+		.targets
+		.iter()
+		.enumerate()
+		.map(|(i, t)| (t.clone(), i))
+		.collect::<BTreeMap<_, _>>();
+	let mut candidates = snapshot
+		.targets
+		.iter()
+		.map(|t| Candidate {
+			who: t.clone(),
+			approval_stake: 0f64,
+			backed_stake: 0f64,
+			elected: false,
+		})
+		.collect::<Vec<_>>();
+	let mut voters = snapshot
+		.voters
+		.iter()
+		.map(|(who, weight, targets)| {
+			let edges = targets
+				.iter()
+				.filter_map(|t| index_of.get(t))
+				.map(|c| (*c, 0f64, 0f64))
+				.collect::<Vec<_>>();
+			edges.iter().for_each(|(c, _, _)| candidates[*c].approval_stake += *weight as f64);
+			Voter { who: who.clone(), budget: *weight as f64, load: 0f64, edges }
+		})
+		.collect::<Vec<_>>();
+
+	let mut trace = Trace::default();
+	for round in 0..count.min(candidates.len()) {
+		let mut scores = candidates
+			.iter()
+			.enumerate()
+			.filter(|(_, c)| !c.elected && c.approval_stake > 0f64)
+			.map(|(i, c)| (i, 1f64 / c.approval_stake))
+			.collect::<BTreeMap<usize, f64>>();
+		for v in voters.iter() {
+			for (c, _, _) in v.edges.iter() {
+				if let Some(score) = scores.get_mut(c) {
+					*score += v.budget * v.load / candidates[*c].approval_stake;
+				}
+			}
+		}
+
+		let (winner, winner_score) =
+			match scores.iter().min_by(|a, b| a.1.partial_cmp(b.1).unwrap()).map(|(c, s)| (*c, *s))
+			{
+				Some(w) => w,
+				None => break,
+			};
+		candidates[winner].elected = true;
+		for v in voters.iter_mut() {
+			let load = v.load;
+			for (c, edge_load, _) in v.edges.iter_mut() {
+				if *c == winner {
+					*edge_load = winner_score - load;
+					v.load = winner_score;
+				}
+			}
+		}
+
+		trace.rounds.push(Round {
+			round: round + 1,
+			scores: scores
+				.iter()
+				.map(|(c, s)| CandidateScore {
+					candidate: candidates[*c].who.clone(),
+					approval_stake: candidates[*c].approval_stake,
+					score: *s,
+				})
+				.collect(),
+			elected: candidates[winner].who.clone(),
+		});
+	}
+
+	// convert loads into edge weights.
+	for v in voters.iter_mut() {
+		let (budget, load) = (v.budget, v.load);
+		for (c, edge_load, weight) in v.edges.iter_mut() {
+			if candidates[*c].elected && load > 0f64 {
+				*weight = *edge_load / load * budget;
+				candidates[*c].backed_stake += *weight;
+			}
+		}
+	}
+	trace.balancing.push(Iteration {
+		iteration: 0,
+		edges: snapshot_edges(&voters, &candidates),
+		backings: snapshot_backings(&candidates),
+	});
+
+	for iteration in 1..=iterations {
+		for v in voters.iter_mut() {
+			balance_voter(v, &mut candidates);
+		}
+		trace.balancing.push(Iteration {
+			iteration,
+			edges: snapshot_edges(&voters, &candidates),
+			backings: snapshot_backings(&candidates),
+		});
+	}
+
+	trace
+}
+
+/// Main run function of the sub-command.
+///
+/// Note that this sub-command does not need a connection to any node.
+pub fn run(conf: TraceConfig) -> error::Result<()> {
+	let snapshot = Snapshot::load(&conf.input)?;
+	let count = conf.count.unwrap_or(snapshot.desired_targets as usize);
+	let trace = trace(&snapshot, count, conf.iterations);
+
+	for round in trace.rounds.iter() {
+		log::debug!(
+			target: crate::LOG_TARGET,
+			"round {}: elected {:?} out of {} scored candidates",
+			round.round,
+			round.elected,
+			round.scores.len()
+		);
+	}
+	let file = std::fs::File::create(&conf.output).map_err(|e| Error::file(&conf.output, e))?;
+	serde_json::to_writer_pretty(std::io::BufWriter::new(file), &trace)
+		.map_err(|e| Error::file(&conf.output, e))?;
+	println!(
+		"📝 Trace of {} rounds and {} balancing iterations written to {:?}",
+		trace.rounds.len(),
+		conf.iterations,
+		conf.output
+	);
+	Ok(())
+}
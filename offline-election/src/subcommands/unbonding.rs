@@ -0,0 +1,85 @@
+//! Report the schedule of stake becoming withdrawable over the bonding duration.
+
+use crate::{
+	error,
+	primitives::{AccountId, Balance},
+	storage,
+	subcommands::{ledger, session_info},
+	Client, Currency, Opt, LOG_TARGET,
+};
+use pallet_staking::{EraIndex, Nominations, StakingLedger};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Main run function of the sub-command.
+pub async fn run(client: &Client, opt: Opt, validator: Option<AccountId>) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let active_era = session_info::get_active_era(client, at).await.index;
+	let bonding_duration = storage::get_const::<EraIndex>(client, "Staking", "BondingDuration", at)
+		.await
+		.unwrap_or(28);
+	let era_duration = session_info::era_duration(client, at).await;
+
+	let ledgers = storage::enumerate_map::<AccountId, StakingLedger<AccountId, Balance>>(
+		b"Staking", b"Ledger", client, at,
+	)
+	.await
+	.expect("Staking::ledger should be enumerable.");
+
+	// if a validator is given, only retain the ledgers of the stashes backing it.
+	let maybe_stashes = if let Some(validator) = validator.as_ref() {
+		let nominators = storage::enumerate_map::<AccountId, Nominations<AccountId>>(
+			b"Staking",
+			b"Nominators",
+			client,
+			at,
+		)
+		.await
+		.expect("Staking::nominators should be enumerable");
+		let mut stashes = nominators
+			.into_iter()
+			.filter(|(_, n)| n.targets.contains(validator))
+			.map(|(who, _)| who)
+			.collect::<BTreeSet<AccountId>>();
+		stashes.insert(validator.clone());
+		log::info!(target: LOG_TARGET, "{} stashes backing {:?}", stashes.len(), validator);
+		Some(stashes)
+	} else {
+		None
+	};
+
+	let mut schedule: BTreeMap<EraIndex, (Balance, usize)> = BTreeMap::new();
+	let mut total_active: Balance = 0;
+	for (_, l) in ledgers.iter() {
+		if maybe_stashes.as_ref().map_or(false, |s| !s.contains(&l.stash)) {
+			continue;
+		}
+		total_active += l.active;
+		for chunk in l.unlocking.iter() {
+			let entry = schedule.entry(chunk.era.max(active_era)).or_default();
+			entry.0 += chunk.value;
+			entry.1 += 1;
+		}
+	}
+
+	println!(
+		"🔓 Unbonding schedule from era {} over the next {} eras (active stake = {:?}):",
+		active_era,
+		bonding_duration,
+		Currency::from(total_active)
+	);
+	let mut cumulative: Balance = 0;
+	for era in active_era..=active_era + bonding_duration {
+		let (value, chunks) = schedule.get(&era).cloned().unwrap_or_default();
+		cumulative += value;
+		println!(
+			"\tera {} (~{}): {:?} in {} chunks / cumulative {:?} ({:.2}% of active)",
+			era,
+			ledger::human_duration((era - active_era) as u64 * era_duration),
+			Currency::from(value),
+			chunks,
+			Currency::from(cumulative),
+			cumulative as f64 * 100f64 / total_active.max(1) as f64,
+		);
+	}
+	Ok(())
+}
@@ -1,8 +1,343 @@
-use crate::{primitives::AccountId, subcommands, Client, Currency, Opt};
-use pallet_staking::Nominations;
+use crate::{
+	error,
+	election::{self, Algorithm},
+	network,
+	primitives::{AccountId, Balance, Hash},
+	snapshot::Snapshot,
+	storage, subcommands, Client, Currency, Opt, LOG_TARGET,
+};
+use pallet_staking::{EraRewardPoints, Nominations};
+use sp_npos_elections::VoteWeight;
+use sp_runtime::traits::Convert;
+use std::{path::PathBuf, str::FromStr};
+
+/// Read a list of accounts from a file, one per line. Both hex and ss58 encoding are acceptable.
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub(crate) fn accounts_from_file(path: &PathBuf) -> error::Result<Vec<AccountId>> {
+	let content = std::fs::read_to_string(path).map_err(|e| error::Error::file(path, e))?;
+	content
+		.lines()
+		.map(|l| l.trim())
+		.filter(|l| !l.is_empty() && !l.starts_with('#'))
+		.map(|l| {
+			AccountId::from_str(l)
+				.map_err(|_| error::Error::file(path, format!("invalid account {}", l)))
+		})
+		.collect::<error::Result<Vec<_>>>()
+}
+
+/// The number of past eras looked at to compute the risk score.
+const RISK_ERAS: u32 = 28;
+
+/// A validator elected in fewer than this many of the past `RISK_ERAS` eras is considered new.
+const NEW_STASH_ERAS: u32 = 7;
+
+/// The heuristic of the risk score, printed along with it.
+pub(crate) const RISK_HEURISTIC: &str = "risk score (0-100) = 40 if ever slashed + 20 if the \
+	commission spiked + up to 20 for eras with less than half the average era points + 10 if \
+	elected in fewer than 7 eras + 10 if the self stake is less than 0.5% of the backing. Looks at \
+	the last 28 eras.";
+
+/// A heuristic slash-risk score of a validator, with the reasons that contributed to it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Risk {
+	pub score: u32,
+	pub reasons: Vec<String>,
+}
+
+impl Risk {
+	fn add(&mut self, score: u32, reason: String) {
+		self.score += score;
+		self.reasons.push(reason);
+	}
+}
+
+/// Compute the risk score of a validator stash, where `history` is the [`recent_points`] of the
+/// chain, shared by all the validators that are checked. See [`RISK_HEURISTIC`].
+pub(crate) async fn risk_of(
+	who: &AccountId,
+	history: &[EraRewardPoints<AccountId>],
+	client: &Client,
+	at: Hash,
+) -> Risk {
+	let mut risk = Risk::default();
+	let active_era = subcommands::session_info::get_active_era(client, at).await.index;
+	let first_era = active_era.saturating_sub(history.len() as u32);
+
+	if let Some(spans) = subcommands::staking::slashing_span_of(who, client, at).await {
+		risk.add(40, format!("slashed, last in era {}", spans.last_nonzero_slash()));
+	}
+
+	let mut trajectory = vec![];
+	let mut elected = 0;
+	let mut low_points = 0;
+	for (era, points) in (first_era..active_era).zip(history.iter()) {
+		if let Some(c) = subcommands::commission_history::commission_at(who, era, client, at).await
+		{
+			trajectory.push((era, c));
+		}
+		if let Some(own) = points.individual.get(who) {
+			elected += 1;
+			let average = points.total / points.individual.len().max(1) as u32;
+			if *own < average / 2 {
+				low_points += 1;
+			}
+		}
+	}
+	// the active era is not over yet, hence only counts as an election.
+	if let Some(c) =
+		subcommands::commission_history::commission_at(who, active_era, client, at).await
+	{
+		trajectory.push((active_era, c));
+	}
+	let points = subcommands::payout_check::reward_points_of(active_era, client, at).await;
+	if points.individual.contains_key(who) {
+		elected += 1;
+	}
+
+	let spikes = subcommands::commission_history::spikes(&trajectory);
+	if !spikes.is_empty() {
+		risk.add(20, format!("commission spiked in eras {:?}", spikes));
+	}
+	if low_points > 0 {
+		risk.add(
+			20 * low_points / elected,
+			format!("less than half the average era points in {}/{} eras", low_points, elected),
+		);
+	}
+	if elected < NEW_STASH_ERAS {
+		risk.add(10, format!("elected in only {} of the last {} eras", elected, RISK_ERAS));
+	}
+
+	if subcommands::staking::controller_of(who, client, at).await.is_some() {
+		let self_stake = subcommands::staking::stake_of(who, client, at).await;
+		let exposure = subcommands::staking::exposure_of(who, active_era, client, at).await;
+		if self_stake * 200 < exposure.total {
+			risk.add(
+				10,
+				format!("self stake {:?} is less than 0.5% of backing", Currency::from(self_stake)),
+			);
+		}
+	}
+
+	risk
+}
+
+/// Print the risk score of a validator.
+pub(crate) fn print_risk(risk: &Risk, indent: &str) {
+	println!("{}🎲 Risk score = {}/100", indent, risk.score);
+	risk.reasons.iter().for_each(|r| println!("{}\t{}", indent, r));
+}
+
+/// The number of past, completed eras looked at to compute the consistency of era points.
+const CONSISTENCY_ERAS: u32 = 28;
+
+/// A validator whose era points vary by more than this fraction of their mean is erratic.
+pub(crate) const ERRATIC_VARIATION: f64 = 0.25;
+
+/// The consistency of the era points of a validator. The points of each era are taken relative to
+/// the average of that era, since the total varies with the length of the era.
+///
+/// Nominators are paid pro-rata to the points, so an erratic producer has an erratic APY, and a
+/// low mean drags it down.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Consistency {
+	/// The number of eras in which the validator earned points.
+	pub eras: u32,
+	/// The mean of the points of the validator, relative to the average of the era.
+	pub mean: f64,
+	/// The coefficient of variation of the same: the standard deviation over the mean.
+	pub variation: f64,
+}
+
+impl Consistency {
+	/// Whether the validator is an erratic producer, see [`ERRATIC_VARIATION`].
+	pub(crate) fn is_erratic(&self) -> bool {
+		self.variation > ERRATIC_VARIATION
+	}
+}
+
+/// The reward points of the last [`CONSISTENCY_ERAS`] completed eras, oldest first.
+pub(crate) async fn recent_points(client: &Client, at: Hash) -> Vec<EraRewardPoints<AccountId>> {
+	let active_era = subcommands::session_info::get_active_era(client, at).await.index;
+	let mut history = vec![];
+	for era in active_era.saturating_sub(CONSISTENCY_ERAS)..active_era {
+		history.push(subcommands::payout_check::reward_points_of(era, client, at).await);
+	}
+	history
+}
+
+/// The consistency of the era points of `who` in `history`. Only the eras in which the validator
+/// earned points are accounted for.
+pub(crate) fn consistency_of(
+	who: &AccountId,
+	history: &[EraRewardPoints<AccountId>],
+) -> Consistency {
+	let ratios = history
+		.iter()
+		.filter_map(|points| {
+			let own = points.individual.get(who)?;
+			let average = points.total as f64 / points.individual.len().max(1) as f64;
+			Some(*own as f64 / average.max(1f64))
+		})
+		.collect::<Vec<_>>();
+	if ratios.is_empty() {
+		return Consistency::default();
+	}
+
+	let n = ratios.len() as f64;
+	let mean = ratios.iter().sum::<f64>() / n;
+	let variance = ratios.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n;
+	Consistency {
+		eras: ratios.len() as u32,
+		mean,
+		variation: if mean > 0f64 { variance.sqrt() / mean } else { 0f64 },
+	}
+}
+
+/// Print the consistency of the era points of a validator.
+pub(crate) fn print_consistency(consistency: &Consistency, indent: &str) {
+	if consistency.eras == 0 {
+		println!("{}📈 No era points in the last {} eras.", indent, CONSISTENCY_ERAS);
+		return;
+	}
+	println!(
+		"{}📈 Era points = {:.2}x the era average over {} eras, varying by {:.2}%{}",
+		indent,
+		consistency.mean,
+		consistency.eras,
+		consistency.variation * 100f64,
+		if consistency.is_erratic() { " ⚠️  erratic" } else { "" },
+	);
+}
+
+/// Flag the erratic producers of the active set, most erratic first.
+pub async fn run_erratic(client: &Client, opt: Opt) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let (era, validators_and_expo) = crate::network::get_validators_and_expo_at(client, at).await;
+	let history = recent_points(client, at).await;
+	println!(
+		"📈 era points of the {} validators active in era {:?}, over the last {} eras.",
+		validators_and_expo.len(),
+		era,
+		history.len()
+	);
+
+	let mut consistencies = validators_and_expo
+		.iter()
+		.map(|(v, _)| (v, consistency_of(v, &history)))
+		.collect::<Vec<_>>();
+	consistencies.sort_by(|(_, a), (_, b)| {
+		b.variation.partial_cmp(&a.variation).unwrap_or(std::cmp::Ordering::Equal)
+	});
+	let erratic = consistencies.iter().filter(|(_, c)| c.is_erratic()).collect::<Vec<_>>();
+	for (v, c) in erratic.iter() {
+		println!(
+			"\t{:?}: {:.2}x the era average over {} eras, varying by {:.2}%",
+			v,
+			c.mean,
+			c.eras,
+			c.variation * 100f64
+		);
+	}
+	println!(
+		"📊 {} erratic producers, varying by more than {:.0}% of their mean.",
+		erratic.len(),
+		ERRATIC_VARIATION * 100f64
+	);
+	Ok(())
+}
+
+/// The number of elections to run while searching for the backing needed by a candidate.
+const BACKING_SEARCH_ROUNDS: usize = 24;
+
+/// Search for the additional stake that `who` needs to be elected in the predicted election, by
+/// injecting a synthetic nomination of increasing stake for it and re-running the election.
+///
+/// Returns `None` if `who` is not a candidate. This assumes that more backing never makes a
+/// candidate lose, which holds in practice but is not guaranteed by sequential phragmen.
+pub(crate) fn backing_needed(who: &AccountId, snapshot: &Snapshot) -> Option<Balance> {
+	if !snapshot.targets.contains(who) {
+		return None;
+	}
+	let count = snapshot.desired_targets as usize;
+	let synthetic = AccountId::from([0xff; 32]);
+	let is_elected_with = |weight: VoteWeight| -> bool {
+		let mut snapshot = snapshot.clone();
+		if weight > 0 {
+			snapshot.voters.push((synthetic.clone(), weight, vec![who.clone()]));
+		}
+		election::elect(&snapshot, count, Algorithm::SeqPhragmen, 0, false)
+			.expect("Phragmen failed to elect.")
+			.winners
+			.contains(who)
+	};
+
+	if is_elected_with(0) {
+		return Some(0);
+	}
+	// with the entire stake of the snapshot behind it, any candidate is elected.
+	let mut low: VoteWeight = 0;
+	let mut high: VoteWeight =
+		snapshot.voters.iter().fold(0 as VoteWeight, |acc, (_, w, _)| acc.saturating_add(*w));
+	for round in 0..BACKING_SEARCH_ROUNDS {
+		let mid = low + (high - low) / 2;
+		if is_elected_with(mid) {
+			high = mid;
+		} else {
+			low = mid;
+		}
+		log::debug!(target: LOG_TARGET, "[{}] backing needed in ({}, {}]", round, low, high);
+	}
+	Some(<network::CurrencyToVoteHandler as Convert<u128, u128>>::convert(high as u128))
+}
+
+/// The order in which the nominators of a validator are listed.
+#[derive(Debug, Clone, Copy)]
+pub enum NominatorOrder {
+	/// By stake, the largest first.
+	Stake,
+	/// By account.
+	Account,
+}
+
+impl FromStr for NominatorOrder {
+	type Err = &'static str;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"stake" => Ok(Self::Stake),
+			"account" => Ok(Self::Account),
+			_ => Err("Invalid nominator order. Can be stake|account."),
+		}
+	}
+}
+
+/// Print a table of `(nominator, stake, note)` in `order`, with its total.
+fn print_nominators(
+	title: &str,
+	mut rows: Vec<(AccountId, Balance, String)>,
+	order: NominatorOrder,
+) {
+	match order {
+		NominatorOrder::Stake => rows.sort_by_key(|(_, stake, _)| std::cmp::Reverse(*stake)),
+		NominatorOrder::Account => rows.sort_by(|(a, _, _), (b, _, _)| a.cmp(b)),
+	}
+	println!("{} ({} nominators):", title, rows.len());
+	for (who, stake, note) in rows.iter() {
+		println!("\t{:?} | {:>24} | {}", who, format!("{:?}", Currency::from(*stake)), note);
+	}
+	println!("\ttotal: {:?}", Currency::from(rows.iter().map(|(_, s, _)| s).sum::<Balance>()));
+}
 
 /// Main run function of the sub-command.
-pub async fn run(client: &Client, opt: Opt, who: AccountId) {
+pub async fn run(
+	client: &Client,
+	opt: Opt,
+	who: AccountId,
+	backing_needed: bool,
+	list_nominators: Option<NominatorOrder>,
+) -> error::Result<()> {
 	let at = opt.at.unwrap();
 
 	let nominators: Vec<(AccountId, Nominations<AccountId>)> =
@@ -31,7 +366,7 @@ pub async fn run(client: &Client, opt: Opt, who: AccountId) {
 	let era = subcommands::staking::get_current_era(client, at).await;
 	let exposure = subcommands::staking::exposure_of(&who, era, client, at).await;
 
-	for (n, submitted_in) in my_nominators {
+	for (n, submitted_in) in my_nominators.iter().cloned() {
 		let is_exposed = exposure.others.iter().find(|ie| ie.who == n).map(|ie| ie.value);
 		let is_dangling =
 			subcommands::dangling_nominators::is_dangling(&who, submitted_in, client, at).await;
@@ -52,12 +387,181 @@ pub async fn run(client: &Client, opt: Opt, who: AccountId) {
 	}
 
 	println!("🤑 Total stake = {:?}", Currency::from(exposure.total));
+
+	if let Some(order) = list_nominators {
+		// the intent: everyone that nominates `who`, with their entire active bond.
+		let mut intent = vec![];
+		for (n, _) in my_nominators.iter() {
+			let stake = subcommands::staking::stake_of(n, client, at).await;
+			intent.push((n.clone(), stake, String::new()));
+		}
+		print_nominators("🗳  Nominators by intent", intent, order);
+
+		// the exposure: only the part of the bond that the election assigned to `who`. Only the
+		// largest exposures are rewarded, the rest are clipped.
+		let max_rewarded =
+			storage::get_const::<u32>(client, "Staking", "MaxNominatorRewardedPerValidator", at)
+				.await
+				.unwrap_or(64) as usize;
+		let mut others = exposure.others.clone();
+		others.sort_by_key(|o| std::cmp::Reverse(o.value));
+		let exposed = others
+			.into_iter()
+			.enumerate()
+			.map(|(i, o)| {
+				let note = if i >= max_rewarded { "✂️  clipped".into() } else { String::new() };
+				(o.who, o.value, note)
+			})
+			.collect::<Vec<_>>();
+		print_nominators("🛡  Nominators by active exposure", exposed, order);
+	}
+
+	subcommands::nominator_check::print_proxies(&who, client, at).await;
+	println!("🎯 Reward destination: {:?}", subcommands::staking::payee_of(&who, client, at).await);
 	let maybe_slashing_spans = subcommands::staking::slashing_span_of(&who, client, at).await;
 	if let Some(spans) = maybe_slashing_spans {
 		println!("⚠️  Last non-zero slash happened at {}", spans.last_nonzero_slash());
+		match subcommands::dangling_nominators::unapplied_slashes(client, at).await?.get(&who) {
+			Some(era) => {
+				println!("⏳ Slash pending, to be applied at era {} unless cancelled.", era)
+			}
+			None => println!("⚔️  Slash applied (or cancelled by governance)."),
+		}
 		println!("💭g Raw Slashing spans = {:?}", spans);
 	} else {
 		println!("✅ This validator has no slashing spans.");
 	}
+	let history = recent_points(client, at).await;
+	print_risk(&risk_of(&who, &history, client, at).await, "");
+	print_consistency(&consistency_of(&who, &history), "");
+	println!("ℹ️  {}", RISK_HEURISTIC);
 	println!("💭 Raw Exposure = {:?}", exposure);
+
+	if backing_needed {
+		let snapshot = Snapshot::fetch(client, at).await?;
+		match self::backing_needed(&who, &snapshot) {
+			Some(0) => println!("🔮 Predicted to be elected as is."),
+			Some(needed) => println!(
+				"🔮 Needs about {:?} more backing to be elected in the predicted election.",
+				Currency::from(needed)
+			),
+			None => println!("🔮 Not a validator candidate, cannot be elected."),
+		}
+	}
+	Ok(())
+}
+
+/// Run the checkup of a batch of validators, read from `path`, and print a consolidated report.
+pub async fn run_batch(client: &Client, opt: Opt, path: PathBuf) -> error::Result<()> {
+	let at = opt.at.unwrap();
+	let stashes = accounts_from_file(&path)?;
+	log::info!(target: crate::LOG_TARGET, "checking {} validators from {:?}", stashes.len(), path);
+
+	let max_rewarded =
+		storage::get_const::<u32>(client, "Staking", "MaxNominatorRewardedPerValidator", at)
+			.await
+			.unwrap_or(64) as usize;
+	let (era, validators_and_expo) = crate::network::get_validators_and_expo_at(client, at).await;
+	let (predicted, supports) = subcommands::staking::predict(client, at).await?;
+	let pending_slashes = subcommands::dangling_nominators::unapplied_slashes(client, at).await?;
+	let history = recent_points(client, at).await;
+	println!("⏰ working on era {:?}, predicting the next election.", era);
+
+	let mut active_count = 0;
+	let mut predicted_count = 0;
+	let mut warned_count = 0;
+	for (i, who) in stashes.iter().enumerate() {
+		let mut warnings: Vec<String> = vec![];
+		let ident =
+			storage::helpers::get_identity::<AccountId, Balance>(who.as_ref(), client, at).await;
+		println!("#{} [{}] {:?}", i + 1, ident, who);
+
+		let maybe_commission = subcommands::staking::commission_of(who, client, at).await;
+		if let Some(commission) = maybe_commission {
+			let self_stake = subcommands::staking::stake_of(who, client, at).await;
+			println!(
+				"\t📣 Candidate / commission = {:?} / self stake = {:?}",
+				commission,
+				Currency::from(self_stake)
+			);
+		} else {
+			println!("\t❌ Not a validator candidate");
+			warnings.push("not in `Staking::Validators`, will not be elected.".into());
+		}
+
+		println!(
+			"\t🎯 Reward destination: {:?}",
+			subcommands::staking::payee_of(who, client, at).await
+		);
+		for p in subcommands::nominator_check::staking_proxies_of(who, client, at).await {
+			println!("\t🔐 Staking proxy {:?} / type: {}", p.delegate, p.type_name());
+		}
+
+		if let Some((_, expo)) = validators_and_expo.iter().find(|(v, _)| v == who) {
+			active_count += 1;
+			println!(
+				"\t✅ Active / total = {:?} / {} nominators",
+				Currency::from(expo.total),
+				expo.others.len()
+			);
+			if expo.others.len() > max_rewarded {
+				warnings.push(format!(
+					"oversubscribed: {} nominators, only the top {} are rewarded.",
+					expo.others.len(),
+					max_rewarded
+				));
+			}
+		} else {
+			println!("\t💤 Not active in era {}", era);
+		}
+
+		if predicted.contains(who) {
+			predicted_count += 1;
+			let support = supports.get(who).expect("Winners have supports; qed");
+			println!(
+				"\t🔮 Predicted to be elected / backing = {:?} / {} voters",
+				Currency::from(support.total),
+				support.voters.len()
+			);
+		} else {
+			println!("\t🔮 Predicted NOT to be elected");
+			if maybe_commission.is_some() {
+				warnings.push("candidate, but not predicted to be elected.".into());
+			}
+		}
+
+		if let Some(spans) = subcommands::staking::slashing_span_of(who, client, at).await {
+			warnings.push(format!(
+				"slashed in era {}, nominations submitted before are dangling.",
+				spans.last_nonzero_slash()
+			));
+			if let Some(era) = pending_slashes.get(who) {
+				warnings.push(format!("slash pending until era {}, may still be cancelled.", era));
+			}
+		}
+
+		print_risk(&risk_of(who, &history, client, at).await, "\t");
+		let consistency = consistency_of(who, &history);
+		print_consistency(&consistency, "\t");
+		if consistency.is_erratic() {
+			warnings.push("erratic era points, the rewards of its nominators vary.".into());
+		}
+
+		if !warnings.is_empty() {
+			warned_count += 1;
+		}
+		for w in warnings {
+			println!("\t⚠️  {}", w);
+		}
+	}
+
+	println!("ℹ️  {}", RISK_HEURISTIC);
+	println!(
+		"📊 {} validators checked / {} active / {} predicted to be elected / {} with warnings.",
+		stashes.len(),
+		active_count,
+		predicted_count,
+		warned_count
+	);
+	Ok(())
 }
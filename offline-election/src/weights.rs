@@ -0,0 +1,38 @@
+//! The benchmarked weights of the runtimes, where they differ from the defaults of the pallets.
+//!
+//! The defaults of `pallet_staking`, i.e. `<() as WeightInfo>`, are benchmarked on the substrate
+//! node and are only an approximation of what the polkadot and kusama runtimes charge. The
+//! formulas below are copied from `runtime/*/src/weights/pallet_staking.rs` of the tested
+//! runtimes, see `compat::TESTED_SPECS`.
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use pallet_staking::WeightInfo;
+
+/// The weight of `submit_solution_better` in the runtime named `spec_name`, e.g. `polkadot`.
+///
+/// - `v`: the number of validators in the snapshot.
+/// - `n`: the number of nominators in the snapshot, self-votes included.
+/// - `a`: the number of voters with at least one assignment in the solution.
+/// - `w`: the number of elected validators.
+///
+/// Runtimes with no known weights fall back to the defaults of `pallet_staking`.
+pub(crate) fn submit_solution_better(spec_name: &str, v: u32, n: u32, a: u32, w: u32) -> Weight {
+	match spec_name {
+		"polkadot" | "kusama" => {
+			let db = RocksDbWeight::get();
+			(0 as Weight)
+				.saturating_add((964_000 as Weight).saturating_mul(v as Weight))
+				.saturating_add((432_000 as Weight).saturating_mul(n as Weight))
+				.saturating_add((204_294_000 as Weight).saturating_mul(a as Weight))
+				.saturating_add((9_546_000 as Weight).saturating_mul(w as Weight))
+				.saturating_add(db.reads(6 as Weight))
+				.saturating_add(db.reads((4 as Weight).saturating_mul(a as Weight)))
+				.saturating_add(db.reads((1 as Weight).saturating_mul(w as Weight)))
+				.saturating_add(db.writes(2 as Weight))
+		}
+		_ => <() as WeightInfo>::submit_solution_better(v, n, a, w),
+	}
+}
@@ -55,3 +55,541 @@ fn nominator_check_works() {
 	])
 	.unwrap();
 }
+
+#[test]
+fn generate_and_bench_works() {
+	let path = std::env::temp_dir().join("offline-election-generate-test.json");
+	let path = path.to_str().unwrap();
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&["generate", "--output", path, "--voters", "200", "--targets", "30"]).unwrap();
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	let output = cmd
+		.args(&["bench", "--input", path, "--count", "10", "--runs", "2", "--iterations", "5"])
+		.unwrap();
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	// the targets are also voters, with their self-vote.
+	assert!(
+		stdout.contains("2 runs of electing 10 out of 30 targets with 230 voters"),
+		"{}",
+		stdout
+	);
+	for stage in ["seq-phragmen:", "balancing (5 iterations):", "reduce:"].iter() {
+		assert!(
+			stdout.lines().any(|l| l.trim_start().starts_with(stage) && l.contains("mean")),
+			"{} is not reported: {}",
+			stage,
+			stdout
+		);
+	}
+}
+
+/// Generate a snapshot with 50 voters into a temporary file named after `name`.
+fn generated_snapshot(name: &str) -> std::path::PathBuf {
+	let path = std::env::temp_dir().join(format!("offline-election-{}.json", name));
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&["generate", "--output", path.to_str().unwrap(), "--voters", "50"]).unwrap();
+	path
+}
+
+#[test]
+fn snapshot_anonymize_works() {
+	let input = generated_snapshot("anonymize-input");
+	let output = std::env::temp_dir().join("offline-election-anonymize-output.json");
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"snapshot",
+		"anonymize",
+		"--input",
+		input.to_str().unwrap(),
+		"--output",
+		output.to_str().unwrap(),
+		"--salt",
+		"secret",
+	])
+	.unwrap();
+
+	let original: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&input).unwrap()).unwrap();
+	let anonymized = std::fs::read_to_string(&output).unwrap();
+	let accounts = original["targets"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.chain(original["voters"].as_array().unwrap().iter().map(|v| &v[0]))
+		.map(|a| a.as_str().unwrap())
+		.collect::<Vec<_>>();
+	assert!(!accounts.is_empty());
+	for account in accounts {
+		assert!(!anonymized.contains(account), "{} is not anonymized", account);
+	}
+
+	// the nomination graph and the stakes are kept, thus the outcome has the same shape.
+	assert_eq!(elect_offline(&input), elect_offline(&output));
+}
+
+/// The number of winners and the minimal and total backing of the offline election of `input`.
+fn elect_offline(input: &std::path::Path) -> (usize, u64, u64) {
+	let output = input.with_extension("outcome.json");
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"staking",
+		"--offline",
+		"--input",
+		input.to_str().unwrap(),
+		"--count",
+		"10",
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+	let outcome: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	let backings = outcome["supports"]
+		.as_object()
+		.unwrap()
+		.values()
+		.map(|s| s["total"].as_u64().unwrap())
+		.collect::<Vec<_>>();
+	(
+		outcome["winners"].as_array().unwrap().len(),
+		backings.iter().cloned().min().unwrap(),
+		backings.iter().sum(),
+	)
+}
+
+#[test]
+fn snapshot_shrink_works() {
+	let input = generated_snapshot("shrink-input");
+	let output = std::env::temp_dir().join("offline-election-shrink-output.json");
+
+	// a score of all zeros never matches, so this always shrinks down to the bare minimum.
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"snapshot",
+		"shrink",
+		"--input",
+		input.to_str().unwrap(),
+		"--output",
+		output.to_str().unwrap(),
+		"--predicate",
+		"score-mismatch:0,0,0",
+	])
+	.unwrap();
+
+	let size = |path: &std::path::Path| {
+		let snapshot: serde_json::Value =
+			serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+		(
+			snapshot["voters"].as_array().unwrap().len(),
+			snapshot["targets"].as_array().unwrap().len(),
+		)
+	};
+	let (voters, targets) = size(&input);
+	let (shrunk_voters, shrunk_targets) = size(&output);
+	assert!(shrunk_voters < voters && shrunk_targets <= targets);
+
+	// shrinking asserts that the predicate holds for its input, so the shrunk snapshot must still
+	// reproduce it.
+	let again = std::env::temp_dir().join("offline-election-shrink-again.json");
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"snapshot",
+		"shrink",
+		"--input",
+		output.to_str().unwrap(),
+		"--output",
+		again.to_str().unwrap(),
+		"--predicate",
+		"score-mismatch:0,0,0",
+	])
+	.unwrap();
+	assert_eq!(size(&again), (shrunk_voters, shrunk_targets));
+}
+
+#[test]
+fn snapshot_histogram_works() {
+	let input = generated_snapshot("histogram-input");
+	let output = std::env::temp_dir().join("offline-election-histogram-output.json");
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"snapshot",
+		"histogram",
+		"--input",
+		input.to_str().unwrap(),
+		"--output",
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+
+	let histogram: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	let counted = histogram["buckets"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|b| b[1].as_u64().unwrap())
+		.sum::<u64>();
+	assert_eq!(counted, 50);
+}
+
+#[test]
+fn snapshot_matrix_works() {
+	let input = generated_snapshot("matrix-export-input");
+	let output = std::env::temp_dir().join("offline-election-matrix-export");
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"snapshot",
+		"matrix",
+		"--input",
+		input.to_str().unwrap(),
+		"--output",
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+
+	let mtx = std::fs::read_to_string(output.join("graph.mtx")).unwrap();
+	let size = mtx.lines().nth(1).unwrap().split(' ').collect::<Vec<_>>();
+	assert_eq!(mtx.lines().count(), 2 + size[2].parse::<usize>().unwrap());
+	// the voters include the self-votes of the targets.
+	let voters = std::fs::read_to_string(output.join("voters.csv")).unwrap();
+	assert_eq!(voters.lines().count(), 1 + size[0].parse::<usize>().unwrap());
+}
+
+#[test]
+fn anomaly_scan_works() {
+	let input = generated_snapshot("anomaly-scan-input");
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	let output = cmd.args(&["anomaly-scan", "--input", input.to_str().unwrap()]).unwrap();
+	// one line per class, and no examples since a generated snapshot has no anomalies.
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(stdout.lines().filter(|l| l.starts_with("✅")).count(), 5);
+}
+
+#[test]
+fn snapshot_fixture_works() {
+	let input = generated_snapshot("fixture-input");
+	let output = std::env::temp_dir().join("offline-election-fixture.rs");
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"snapshot",
+		"fixture",
+		"--input",
+		input.to_str().unwrap(),
+		"--output",
+		output.to_str().unwrap(),
+		"--name",
+		"kusama_regression",
+	])
+	.unwrap();
+
+	let source = std::fs::read_to_string(output).unwrap();
+	assert!(source.contains("fn kusama_regression() {"));
+	// each voter is listed once with its votes, and once with its stake.
+	let voters = source.lines().filter(|l| l.trim_start().starts_with('(')).count();
+	assert_eq!(voters % 2, 0);
+	assert!(voters >= 2 * 50);
+}
+
+#[test]
+fn selftest_works() {
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&["selftest"]).unwrap();
+}
+
+#[test]
+fn trace_works() {
+	let input = generated_snapshot("trace-input");
+	let output = std::env::temp_dir().join("offline-election-trace-output.json");
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"trace",
+		"--input",
+		input.to_str().unwrap(),
+		"--output",
+		output.to_str().unwrap(),
+		"--count",
+		"10",
+		"--iterations",
+		"2",
+	])
+	.unwrap();
+
+	let trace: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	let rounds = trace["rounds"].as_array().unwrap();
+	assert_eq!(rounds.len(), 10);
+	let mut elected = rounds.iter().map(|r| r["elected"].as_str().unwrap()).collect::<Vec<_>>();
+	elected.sort();
+	elected.dedup();
+	assert_eq!(elected.len(), 10);
+
+	// the phragmen output and each balancing iteration back exactly the winners of the rounds.
+	let balancing = trace["balancing"].as_array().unwrap();
+	assert_eq!(balancing.len(), 3);
+	for iteration in balancing.iter() {
+		let mut backed = iteration["backings"]
+			.as_object()
+			.unwrap()
+			.keys()
+			.map(|k| k.as_str())
+			.collect::<Vec<_>>();
+		backed.sort();
+		assert_eq!(backed, elected);
+	}
+}
+
+#[test]
+fn staking_schema_works() {
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	let output = cmd.args(&["staking", "--schema"]).unwrap();
+	let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert!(schema["required"].as_array().unwrap().contains(&"schema_version".into()));
+
+	// an unknown version is rejected when parsing the arguments, offline or not.
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	let output = cmd.args(&["staking", "--offline", "--schema-version", "2"]).output().unwrap();
+	assert!(!output.status.success());
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	assert!(stderr.contains("unknown schema version 2"), "{}", stderr);
+}
+
+#[test]
+fn staking_offline_works() {
+	let input = generated_snapshot("offline-input");
+	let output = std::env::temp_dir().join("offline-election-offline-output.json");
+
+	// no node is running at this uri, any connection attempt would fail.
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"--uri",
+		"ws://localhost:1",
+		"staking",
+		"--offline",
+		"--input",
+		input.to_str().unwrap(),
+		"--count",
+		"10",
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+
+	let outcome: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	assert_eq!(outcome["winners"].as_array().unwrap().len(), 10);
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"staking",
+		"--offline",
+		"--input",
+		input.to_str().unwrap(),
+		"--count",
+		"10",
+		"--format",
+		"d3",
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+	let graph: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	let nodes = graph["nodes"].as_array().unwrap();
+	assert_eq!(nodes.iter().filter(|n| n["group"] == "validator").count(), 10);
+	assert!(!graph["links"].as_array().unwrap().is_empty());
+
+	// the same report as online, only without what needs a chain.
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"staking",
+		"--offline",
+		"--input",
+		input.to_str().unwrap(),
+		"--count",
+		"10",
+		"--fields",
+		"winners,metrics",
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+	let outcome: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	assert_eq!(outcome["metrics"]["winners"], 10);
+	assert!(outcome["metrics"]["weight"].is_null());
+	assert!(outcome.get("supports").is_none());
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	let out =
+		cmd.args(&["-vv", "staking", "--offline", "--input", input.to_str().unwrap()]).unwrap();
+	assert!(String::from_utf8(out.stdout).unwrap().contains("Distributions:"));
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	let out = cmd
+		.args(&[
+			"staking",
+			"--offline",
+			"--input",
+			input.to_str().unwrap(),
+			"--who",
+			"5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT",
+		])
+		.unwrap();
+	assert!(String::from_utf8(out.stdout).unwrap().contains("🔎"));
+
+	// a chain is still needed for anything that reads it.
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&["staking", "--offline", "--input", input.to_str().unwrap(), "--emulate-fallback"])
+		.assert()
+		.failure();
+}
+
+#[test]
+fn staking_targets_file_works() {
+	let input = generated_snapshot("targets-file-input");
+	let targets = std::env::temp_dir().join("offline-election-targets-file.txt");
+	let output = std::env::temp_dir().join("offline-election-targets-file-output.json");
+
+	let snapshot: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&input).unwrap()).unwrap();
+	let curated = snapshot["targets"].as_array().unwrap().iter().take(12).collect::<Vec<_>>();
+	let lines = curated.iter().map(|t| t.as_str().unwrap()).collect::<Vec<_>>().join("\n");
+	std::fs::write(&targets, lines).unwrap();
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"staking",
+		"--offline",
+		"--input",
+		input.to_str().unwrap(),
+		"--targets-file",
+		targets.to_str().unwrap(),
+		"--count",
+		"10",
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+
+	let outcome: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	let winners = outcome["winners"].as_array().unwrap();
+	assert_eq!(winners.len(), 10);
+	assert!(winners.iter().all(|w| curated.contains(&w)));
+}
+
+#[test]
+fn staking_h160_accounts_work() {
+	let input = std::env::temp_dir().join("offline-election-h160-input.json");
+	let output = std::env::temp_dir().join("offline-election-h160-output.json");
+	let alice = "0x0101010101010101010101010101010101010101";
+	let bob = "0x0202020202020202020202020202020202020202";
+	let carol = "0x0303030303030303030303030303030303030303";
+	let snapshot = serde_json::json!({
+		"at": null,
+		"desired_targets": 1,
+		"targets": [alice, bob],
+		"voters": [[alice, 10, [alice]], [bob, 20, [bob]], [carol, 5, [bob]]],
+	});
+	std::fs::write(&input, snapshot.to_string()).unwrap();
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"staking",
+		"--offline",
+		"--input",
+		input.to_str().unwrap(),
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+
+	let outcome: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	assert_eq!(outcome["winners"], serde_json::json!([bob]));
+}
+
+#[test]
+fn staking_matrix_works() {
+	let input = generated_snapshot("matrix-input");
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	let output = cmd
+		.args(&[
+			"staking",
+			"--offline",
+			"--input",
+			input.to_str().unwrap(),
+			"--count",
+			"10",
+			"--matrix",
+			"algorithm=seq-phragmen,phragmms;iterations=0,2;reduce=off,on",
+		])
+		.unwrap();
+	// the header and one row per configuration.
+	assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 1 + 8);
+}
+
+#[test]
+fn plan_is_rejected_without_a_plan() {
+	// rejected before connecting, hence the unreachable uri.
+	for args in [["--plan", "selftest"], ["--plan", "ledger-audit"]].iter() {
+		let mut cmd = Command::cargo_bin("offline-election").unwrap();
+		let output = cmd.args(&["--uri", "ws://localhost:1"]).args(args).output().unwrap();
+		assert!(!output.status.success());
+		let stderr = String::from_utf8(output.stderr).unwrap();
+		assert!(stderr.contains("no plan is known"), "{}", stderr);
+	}
+}
+
+#[test]
+fn also_reports_a_summary() {
+	// `--network` is ignored rather than rejected, and each unreachable node is in the summary.
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	let output = cmd
+		.args(&["--uri", "ws://localhost:1", "--also", "ws://localhost:2", "-n", "polkadot"])
+		.arg("current")
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(stdout.contains("summary of 2 nodes"), "{}", stdout);
+	assert!(stdout.contains("ws://localhost:1: setup failed"), "{}", stdout);
+	assert!(stdout.contains("ws://localhost:2: setup failed"), "{}", stdout);
+}
+
+#[test]
+fn convert_legacy_output_works() {
+	let input = std::env::temp_dir().join("offline-election-convert-input.json");
+	let output = std::env::temp_dir().join("offline-election-convert-output.json");
+	let account = "5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT";
+	let legacy = serde_json::json!({
+		"supports": { account: { "total": 10, "voters": [[account, 10]] } },
+		"winners": [[account, 10]],
+	});
+	std::fs::write(&input, legacy.to_string()).unwrap();
+
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&[
+		"convert",
+		"--input",
+		input.to_str().unwrap(),
+		"--output",
+		output.to_str().unwrap(),
+	])
+	.unwrap();
+
+	let converted: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	assert_eq!(converted["schema_version"], 1);
+	assert_eq!(converted["winners"][0], account);
+}
+
+#[test]
+#[ignore = "requires a running node"]
+fn session_info_works() {
+	let mut cmd = Command::cargo_bin("offline-election").unwrap();
+	cmd.args(&["--uri", TEST_URI, "session-info"]).unwrap();
+}
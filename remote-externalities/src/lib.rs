@@ -183,6 +183,9 @@ impl<T: ?Sized + AsRef<[u8]>> HexDisplayExt for T {
 
 #[derive(Copy, Clone, Debug)]
 /// Basic configuration for the cache behavior.
+///
+/// While a cache is being created, the progress of the scrape is checkpointed next to it, and an
+/// interrupted scrape of the same block resumes from there.
 pub enum CacheMode {
 	/// Use the cache if it is there, else create it.
 	UseElseCreate,
@@ -319,30 +322,78 @@ impl Builder {
 		self.at.expect("At intialized after `built`; qed")
 	}
 
+	/// The directory in which the progress of an unfinished scrape is kept.
+	///
+	/// Each completed prefix is stored in its own file, named after the hex of the prefix, such that
+	/// an interrupted scrape can resume where it left off.
+	fn checkpoint_dir(&self) -> PathBuf {
+		let mut name = self.cache_path().into_os_string();
+		name.push(".partial");
+		name.into()
+	}
+
+	/// Whether the progress of the scrape is checkpointed. Only if a cache is going to be created.
+	fn checkpoints(&self) -> bool {
+		!matches!(self.cache_config, CacheMode::None)
+	}
+
+	/// The prefixes to scrape, one request each.
+	///
+	/// If no module is filtered, the entire chain is scraped in pages of all keys with the same
+	/// first byte.
+	fn prefixes(&self) -> Vec<(String, StorageKey)> {
+		if self.module_filter.len() > 0 {
+			self.module_filter
+				.iter()
+				.map(|f| (f.clone(), StorageKey(twox_128(f.as_bytes()).to_vec())))
+				.collect()
+		} else {
+			(0..=u8::max_value()).map(|b| (format!("page {:#04x}", b), StorageKey(vec![b]))).collect()
+		}
+	}
+
+	/// Scrape the pairs under `prefix`, or load them from the checkpoint of a previous scrape.
+	async fn scrape_prefix(&self, prefix: StorageKey, at: Hash) -> Vec<KeyPair> {
+		if !self.checkpoints() {
+			return self.rpc_get_pairs(prefix, at).await;
+		}
+
+		let name = prefix.0.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+		let path = self.checkpoint_dir().join(format!("{}.bin", name));
+		if let Some(kv) = fs::read(&path).ok().and_then(|b| bincode::deserialize(&b[..]).ok()) {
+			debug!(target: LOG_TARGET, "resuming from checkpoint {:?}", path);
+			return kv;
+		}
+
+		let kv = self.rpc_get_pairs(prefix, at).await;
+		fs::create_dir_all(self.checkpoint_dir()).unwrap();
+		// write then rename, such that an interrupted write is not taken as a completed prefix.
+		let tmp = path.with_extension("tmp");
+		fs::write(&tmp, bincode::serialize(&kv).unwrap()).unwrap();
+		fs::rename(tmp, path).unwrap();
+		kv
+	}
+
 	/// Build `Self` from a network node denoted by `uri`.
 	async fn scrape_remote(&self) -> Vec<KeyPair> {
 		let at = self.final_at();
 		info!(target: LOG_TARGET, "scraping keypairs from remote node {} @ {:?}", self.uri, at);
-
-		let mut keys_and_values = if self.module_filter.len() > 0 {
-			let mut filtered_kv = vec![];
-			for f in self.module_filter.iter() {
-				let hashed_prefix = StorageKey(twox_128(f.as_bytes()).to_vec());
-				let module_kv = self.rpc_get_pairs(hashed_prefix.clone(), at).await;
-				info!(
-					target: LOG_TARGET,
-					"downloaded data for module {} (count: {} / prefix: {:?}).",
-					f,
-					module_kv.len(),
-					hashed_prefix,
-				);
-				filtered_kv.extend(module_kv);
-			}
-			filtered_kv
-		} else {
+		if self.module_filter.is_empty() {
 			info!(target: LOG_TARGET, "downloading data for all modules.");
-			self.rpc_get_pairs(StorageKey(vec![]), at).await.into_iter().collect::<Vec<_>>()
-		};
+		}
+
+		let mut keys_and_values = vec![];
+		for (name, prefix) in self.prefixes() {
+			let kv = self.scrape_prefix(prefix.clone(), at).await;
+			info!(
+				target: LOG_TARGET,
+				"downloaded data for {} (count: {} / prefix: {:?}).",
+				name,
+				kv.len(),
+				prefix,
+			);
+			keys_and_values.extend(kv);
+		}
 
 		// concat any custom key values.
 		keys_and_values.extend(self.inject.clone());
@@ -352,6 +403,8 @@ impl Builder {
 	async fn force_update(&self) -> Vec<KeyPair> {
 		let kp = self.scrape_remote().await;
 		self.save_cache(&kp);
+		// the scrape is complete, its progress is no longer needed.
+		let _ = fs::remove_dir_all(self.checkpoint_dir());
 		kp
 	}
 
@@ -1,59 +1,175 @@
-import {  readFileSync } from 'fs';
+import {  readFileSync, readdirSync } from 'fs';
+import { createInterface } from "readline";
 import { ApiPromise } from "@polkadot/api";
 import { ValidatorIndex, CompactAssignments, ElectionScore, EraIndex, ElectionSize } from "@polkadot/types/interfaces/staking"
 import { Keyring } from "@polkadot/keyring"
+import { KeyringPair } from "@polkadot/keyring/types"
+import { SubmittableExtrinsic } from "@polkadot/api/types"
+import { ISubmittableResult } from "@polkadot/types/types"
 import { join } from "path"
+import { decodeAddress } from "@polkadot/util-crypto"
+import { u8aConcat } from "@polkadot/util"
+import { toString as qrString } from "qrcode"
 
 const keyring = new Keyring({ type: 'sr25519', ss58Format: 0 });
 
+// the key type of account keys in a substrate keystore, hex encoded.
+const ACCOUNT_KEY_TYPE = Buffer.from("acco").toString("hex");
 
-async function main() {
-	const api = await ApiPromise.create()
+// the prefix of a transaction to sign in the qr format of parity signer: substrate, sr25519, sign
+// transaction.
+const QR_SIGN_TX = new Uint8Array([0x53, 0x01, 0x02]);
 
-	let solutionPath = join(".", process.argv[2]);
-	console.log(`++ reading solution binary from path ${solutionPath}`)
+// read the value of a `--name value` argument, if given.
+function arg(name: string): string | undefined {
+	let index = process.argv.indexOf(`--${name}`);
+	return index > 0 ? process.argv[index + 1] : undefined
+}
 
-	let buffer = readFileSync(solutionPath)
-	let bytes = new Uint8Array(buffer)
+// prompt for an answer on the terminal. A hidden answer, e.g. a password, is not echoed.
+function prompt(question: string, hidden: boolean): Promise<string> {
+	const rl = createInterface({ input: process.stdin, output: process.stdout });
+	if (hidden) {
+		// @ts-ignore
+		rl._writeToOutput = (s: string) => { if (s.startsWith(question)) process.stdout.write(s) };
+	}
+	return new Promise(resolve => rl.question(question, answer => {
+		rl.close();
+		if (hidden) process.stdout.write("\n");
+		resolve(answer.trim());
+	}))
+}
 
-	// @ts-ignore
-	let [winners_raw, compact_raw, score_raw, era_raw, size_raw]: any[] = api.createType('(Vec<ValidatorIndex>, CompactAssignments, ElectionScore, EraIndex, ElectionSize)', bytes);
+// load the signing key, from (in order of precedence):
+//
+// - `--keystore <dir>`: a substrate keystore directory. The account key with public key
+//   `--public <hex>` is used, or the only account key of the keystore.
+// - `--json <file>`: an encrypted json keyfile, as exported by polkadot-js. The password is
+//   prompted for.
+// - `--seed-file <file>`: a file containing a raw seed or uri. Defaults to `key_real`.
+async function loadKey(): Promise<KeyringPair> {
+	let keystore = arg("keystore");
+	let json = arg("json");
+	if (keystore) {
+		let publicKey = arg("public")?.replace(/^0x/, "");
+		let candidates = readdirSync(keystore)
+			.filter(f => f.startsWith(ACCOUNT_KEY_TYPE))
+			.filter(f => !publicKey || f.endsWith(publicKey));
+		if (candidates.length != 1) {
+			throw `expected exactly one account key in ${keystore}, found ${candidates.length}. Use --public.`
+		}
+		// the keystore stores the secret uri as a json string.
+		let uri: string = JSON.parse(readFileSync(join(keystore, candidates[0])).toString());
+		return keyring.addFromUri(uri)
+	} else if (json) {
+		let pair = keyring.addFromJson(JSON.parse(readFileSync(json).toString()));
+		pair.decodePkcs8(await prompt(`password of ${pair.address}: `, true));
+		return pair
+	} else {
+		return keyring.addFromUri(readFileSync(arg("seed-file") || "key_real").toString().trim())
+	}
+}
 
-	let winners: ValidatorIndex[] = winners_raw;
-	let compact: CompactAssignments = compact_raw;
-	let score: ElectionScore = score_raw;
-	let era: EraIndex = era_raw;
-	let size: ElectionSize = size_raw;
 
-	let call = api.tx.staking.submitElectionSolution(winners, compact, score, era, size)
+// sign `call` with an external signer, e.g. a Ledger or an air-gapped device: print the payload
+// to sign, as hex and as a qr code that parity signer can scan, and read back the signature.
+async function signExternally(api: ApiPromise, call: SubmittableExtrinsic<"promise">, address: string) {
+	const nonce = await api.rpc.system.accountNextIndex(address);
+	const header = await api.rpc.chain.getHeader();
+	const payload = api.createType('SignerPayload', {
+		address,
+		blockHash: header.hash,
+		blockNumber: header.number,
+		era: api.createType('ExtrinsicEra', { current: header.number, period: 64 }),
+		genesisHash: api.genesisHash,
+		method: call.method,
+		nonce,
+		runtimeVersion: api.runtimeVersion,
+		signedExtensions: api.registry.signedExtensions,
+		version: api.extrinsicVersion,
+	});
+
+	// payloads longer than 256 bytes are hashed by the signer before signing, as usual.
+	const data = payload.toRaw().data;
+	console.log(`++ payload to sign with ${address}:`)
+	console.log(data)
+	const qr = u8aConcat(QR_SIGN_TX, decodeAddress(address), data, api.genesisHash);
+	console.log(await qrString([{ data: Buffer.from(qr), mode: 'byte' }], { type: 'terminal', small: true }))
+	const signature = await prompt("++ signature (hex, including the crypto type prefix): ", false);
+	call.addSignature(address, signature, payload.toPayload());
+}
+
+async function main() {
+	const api = await ApiPromise.create()
+
+	// the input is either a solution file, as written by the miner, or any hex-encoded call, e.g.
+	// a payout batch.
+	let input = process.argv[2];
+	let call: SubmittableExtrinsic<"promise">;
+	if (input.startsWith("0x")) {
+		call = api.tx(api.createType('Call', input));
+	} else {
+		let solutionPath = join(".", input);
+		console.log(`++ reading solution binary from path ${solutionPath}`)
+
+		let buffer = readFileSync(solutionPath)
+		let bytes = new Uint8Array(buffer)
+
+		// @ts-ignore
+		let [winners_raw, compact_raw, score_raw, era_raw, size_raw]: any[] = api.createType('(Vec<ValidatorIndex>, CompactAssignments, ElectionScore, EraIndex, ElectionSize)', bytes);
+
+		let winners: ValidatorIndex[] = winners_raw;
+		let compact: CompactAssignments = compact_raw;
+		let score: ElectionScore = score_raw;
+		let era: EraIndex = era_raw;
+		let size: ElectionSize = size_raw;
+
+		call = api.tx.staking.submitElectionSolution(winners, compact, score, era, size)
+	}
 	let info = await api.rpc.payment.queryInfo(call.toJSON())
-	const origin = keyring.addFromUri(readFileSync("key_real").toString().trim());
 
-	console.log(`++ submitting call ${call.meta.name}, weight = ${info.weight.toHuman()}, partialFee = ${info.partialFee.toHuman()}`)
-	console.log(`++ free balance of sender account (${origin.address}) = ${(await api.query.system.account(origin.address)).data.free.toHuman()}`)
+	// with `--external-signer <address>`, no key is loaded and the payload is signed externally.
+	const external = arg("external-signer");
+	const origin = external ? undefined : await loadKey();
+	const address = external || origin!.address;
 
-	return 0;
+	console.log(`++ call ${call.meta.name}, weight = ${info.weight.toHuman()}, partialFee = ${info.partialFee.toHuman()}`)
+	console.log(`++ free balance of sender account (${address}) = ${(await api.query.system.account(address)).data.free.toHuman()}`)
+
+	// without `--submit`, this is a dry run that only reports the call.
+	if (!process.argv.includes("--submit")) {
+		console.log("++ dry run, pass --submit to sign and submit the call.")
+		return 0;
+	}
 	let exitCode = 0;
 
-	try {
-		let unsubscribe = await call.signAndSend(origin, ({ events = [], status, }) => {
-			console.log(`Current status is ${status.type}`);
+	const onStatus = ({ events = [], status }: ISubmittableResult) => {
+		console.log(`Current status is ${status.type}`);
 
-			if (status.isInBlock) {
-				  console.log(`++ Transaction included at blockHash ${status.asInBlock}`);
-			}
+		if (status.isInBlock) {
+			  console.log(`++ Transaction included at blockHash ${status.asInBlock}`);
+		}
 
-			if (status.isFinalized) {
-				console.log(`++ Transaction Finalized at blockHash ${status.asFinalized}`);
+		if (status.isFinalized) {
+			console.log(`++ Transaction Finalized at blockHash ${status.asFinalized}`);
 
-				events.forEach(({ phase, event: { data, method, section } }) => {
-					console.log(`\t' ${phase}: ${section}.${method}:: ${data}`);
-				});
-				unsubscribe();
-			}
+			events.forEach(({ phase, event: { data, method, section } }) => {
+				console.log(`\t' ${phase}: ${section}.${method}:: ${data}`);
+			});
+			unsubscribe();
+		}
+	};
 
-		})
+	let unsubscribe = () => {};
+	try {
+		if (origin) {
+			unsubscribe = await call.signAndSend(origin, onStatus)
+		} else {
+			await signExternally(api, call, address);
+			unsubscribe = await call.send(onStatus)
+		}
 	} catch(err) {
+		console.error("++ submission failed:", err)
 		exitCode = 1;
 	}
 
@@ -104,14 +104,15 @@ pub async fn submit_at(at: Hash) {
 
 			if !closed {
 				if clt::confirm("Submit the solution?", false, "n", true) {
-					let output = std::process::Command::new("node")
+					// any extra arguments, e.g. the source of the signing key, are for the script.
+					// The script inherits the terminal, since it might prompt for a password.
+					let status = std::process::Command::new("node")
 						.args(&["js/build/index.js", &solution_file])
-						.output()
+						.args(std::env::args().skip(1))
+						.status()
 						.unwrap();
 
-					log::info!("Exit code of js script = {}", output.status);
-					println!("STDOUT\n{}", String::from_utf8_lossy(&output.stdout));
-					println!("STDERR\n{}", String::from_utf8_lossy(&output.stderr));
+					log::info!("Exit code of js script = {}", status);
 				}
 			}
 		});
@@ -81,6 +81,53 @@ pub async fn get_identity<
 	}
 }
 
+/// Get the identity parent of an account, if it is a sub-identity.
+pub async fn get_parent<AccountId: Decode>(
+	who: &[u8],
+	client: &Client,
+	at: Hash,
+) -> Option<AccountId> {
+	use pallet_identity::Data;
+	crate::read::<(AccountId, Data)>(
+		crate::map_key::<Blake2_128Concat>(b"Identity", b"SuperOf", who.as_ref()),
+		client,
+		at,
+	)
+	.await
+	.map(|(parent, _)| parent)
+}
+
+/// Check if the identity of an account, or of its parent, has a `Reasonable` or `KnownGood`
+/// judgement.
+pub async fn is_verified<
+	AccountId: Decode + AsRef<[u8]>,
+	Balance: Encode + Decode + Copy + Clone + Debug + Eq + PartialEq,
+>(
+	who: &[u8],
+	client: &Client,
+	at: Hash,
+) -> bool {
+	use pallet_identity::{Judgement, Registration};
+	let maybe_parent = get_parent::<AccountId>(who, client, at).await;
+	let maybe_identity = crate::read::<Registration<Balance>>(
+		crate::map_key::<Twox64Concat>(
+			b"Identity",
+			b"IdentityOf",
+			maybe_parent.as_ref().map_or(who.as_ref(), |x| x.as_ref()),
+		),
+		client,
+		at,
+	)
+	.await;
+
+	maybe_identity.map_or(false, |identity| {
+		identity
+			.judgements
+			.iter()
+			.any(|(_, j)| matches!(j, Judgement::Reasonable | Judgement::KnownGood))
+	})
+}
+
 /// Get the account data at the given block.
 pub async fn get_account_data_at<Balance: Decode, Nonce: Decode>(
 	account: &[u8],
@@ -30,6 +30,8 @@ use jsonrpsee_types::jsonrpc::{Params, to_value as to_json_value};
 /// Helper's module.
 #[cfg(feature = "helpers")]
 pub mod helpers;
+/// Throttling of the rpc requests.
+pub mod throttle;
 
 /// re-export some stuff from sp-core.
 pub use sp_core::storage::{StorageData, StorageKey};
@@ -96,16 +98,19 @@ pub fn module_prefix_raw(module: &[u8], storage: &[u8]) -> Vec<u8> {
 	final_key
 }
 
-/// Read from a raw key regardless of the type. This can be used in combination with the key
-/// generation methods above and read any data from storage, regardless of its type.
-pub async fn read<T: Decode>(key: StorageKey, client: &Client, at: Hash) -> Option<T> {
+/// Read the raw, undecoded data stored under a key.
+pub async fn read_raw(key: StorageKey, client: &Client, at: Hash) -> Option<StorageData> {
 	let serialized_key = to_json_value(key).expect("StorageKey serialization infallible");
 	let at = to_json_value(at).expect("Block hash serialization infallible");
-	let raw: Option<StorageData> = client
-		.request("state_getStorage", Params::Array(vec![serialized_key, at]))
+	throttle::request(client, "state_getStorage", Params::Array(vec![serialized_key, at]))
 		.await
-		.expect("Storage request failed");
-	let encoded = raw.map(|d| d.0)?;
+		.expect("Storage request failed")
+}
+
+/// Read from a raw key regardless of the type. This can be used in combination with the key
+/// generation methods above and read any data from storage, regardless of its type.
+pub async fn read<T: Decode>(key: StorageKey, client: &Client, at: Hash) -> Option<T> {
+	let encoded = read_raw(key, client, at).await.map(|d| d.0)?;
 	<T as Decode>::decode(&mut encoded.as_slice()).ok()
 }
 
@@ -121,8 +126,7 @@ pub async fn get_pairs(
 ) -> Vec<(StorageKey, StorageData)> {
 	let serialized_prefix = to_json_value(prefix).expect("StorageKey serialization infallible");
 	let at = to_json_value(at).expect("Block hash serialization infallible");
-	client
-		.request("state_getPairs", Params::Array(vec![serialized_prefix, at]))
+	throttle::request(client, "state_getPairs", Params::Array(vec![serialized_prefix, at]))
 		.await
 		.expect("Storage state_getPairs failed")
 }
@@ -141,9 +145,44 @@ pub async fn get_pairs_http(
 	jsonrpsee_types::jsonrpc::from_value(json_value).unwrap()
 }
 
+/// Get up to `count` storage keys located under a certain prefix, after `start_key` if given.
+///
+/// Unlike [`get_pairs`], no values are fetched.
+pub async fn get_keys_paged(
+	prefix: StorageKey,
+	count: u32,
+	start_key: Option<StorageKey>,
+	client: &Client,
+	at: Hash,
+) -> Vec<StorageKey> {
+	let params = Params::Array(vec![
+		to_json_value(prefix).expect("StorageKey serialization infallible"),
+		to_json_value(count).expect("u32 serialization infallible"),
+		to_json_value(start_key).expect("StorageKey serialization infallible"),
+		to_json_value(at).expect("Block hash serialization infallible"),
+	]);
+	throttle::request(client, "state_getKeysPaged", params)
+		.await
+		.expect("Storage state_getKeysPaged failed")
+}
+
+/// Strip the hash of a key of a map that is hashed with `Twox64Concat` or `Blake2_128Concat`,
+/// returning the key itself, or `None` if neither hasher matches.
+pub fn unhash_concat(hashed: &[u8]) -> Option<&[u8]> {
+	use sp_core::hashing::{blake2_128, twox_64};
+	if hashed.len() >= 8 && twox_64(&hashed[8..])[..] == hashed[..8] {
+		Some(&hashed[8..])
+	} else if hashed.len() >= 16 && blake2_128(&hashed[16..])[..] == hashed[..16] {
+		Some(&hashed[16..])
+	} else {
+		None
+	}
+}
+
 /// Enumerate all keys and values in a storage map.
 ///
-/// It is basically a wrapper around `get_pairs` that also decodes types.
+/// It is basically a wrapper around `get_pairs` that also decodes types. The keys are recovered
+/// from maps hashed with a concat hasher, otherwise they are assumed to be the last 32 bytes.
 pub async fn enumerate_map<K, V>(
 	module: &[u8],
 	storage: &[u8],
@@ -159,9 +198,11 @@ where
 
 	raw.into_iter()
 		.map(|(k, v)| {
-			let mut full_key = k.0;
-			let full_len = full_key.len();
-			let key = full_key.drain(full_len - 32..).collect::<Vec<_>>();
+			let full_key = k.0;
+			let key = match unhash_concat(&full_key[32..]) {
+				Some(key) => key.to_vec(),
+				None => full_key[full_key.len() - 32..].to_vec(),
+			};
 			(key, v.0)
 		})
 		.map(|(raw_key, raw_value)| {
@@ -222,27 +263,116 @@ pub async fn get_const<T: Decode>(
 	None
 }
 
+/// Get the type of a storage item from the metadata, as written in the runtime. For maps, this is
+/// the type of the values.
+///
+/// Useful to detect which version of a storage item a runtime uses.
+pub async fn get_storage_type(
+	client: &Client,
+	module: &str,
+	item: &str,
+	at: Hash,
+) -> Option<String> {
+	use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryType};
+	let raw_metadata = get_metadata(client, at).await.0;
+	let prefixed_metadata = <RuntimeMetadataPrefixed as codec::Decode>::decode(&mut &*raw_metadata)
+		.expect("Runtime Metadata failed to decode");
+	let metadata = prefixed_metadata.1;
+
+	if let RuntimeMetadata::V12(inner) = metadata {
+		let decode_modules = unwrap_decoded(inner.modules);
+		for module_encoded in decode_modules.into_iter() {
+			let mod_name = unwrap_decoded(module_encoded.name);
+			if mod_name == module {
+				let storage = unwrap_decoded(module_encoded.storage?);
+				for entry in unwrap_decoded(storage.entries) {
+					if unwrap_decoded(entry.name) == item {
+						return Some(match entry.ty {
+							StorageEntryType::Plain(ty) => unwrap_decoded(ty),
+							StorageEntryType::Map { value, .. } => unwrap_decoded(value),
+							StorageEntryType::DoubleMap { value, .. } => unwrap_decoded(value),
+						});
+					}
+				}
+			}
+		}
+	} else {
+		panic!("Unsupported metadata version. Please make an issue.")
+	}
+
+	None
+}
+
+/// Get the index of a call, as `(module_index, call_index)`, from the metadata.
+///
+/// These are the first two bytes of an encoded call.
+pub async fn get_call_index(
+	client: &Client,
+	module: &str,
+	call: &str,
+	at: Hash,
+) -> Option<(u8, u8)> {
+	use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+	let raw_metadata = get_metadata(client, at).await.0;
+	let prefixed_metadata = <RuntimeMetadataPrefixed as codec::Decode>::decode(&mut &*raw_metadata)
+		.expect("Runtime Metadata failed to decode");
+	let metadata = prefixed_metadata.1;
+
+	if let RuntimeMetadata::V12(inner) = metadata {
+		let decode_modules = unwrap_decoded(inner.modules);
+		for module_encoded in decode_modules.into_iter() {
+			let mod_name = unwrap_decoded(module_encoded.name);
+			if mod_name == module {
+				let calls = unwrap_decoded(module_encoded.calls?);
+				return calls
+					.into_iter()
+					.position(|c| unwrap_decoded(c.name) == call)
+					.map(|call_index| (module_encoded.index, call_index as u8));
+			}
+		}
+	} else {
+		panic!("Unsupported metadata version. Please make an issue.")
+	}
+
+	None
+}
+
 /// Get the latest finalized head of the chain.
 ///
 /// This is technically not a storage operation but RPC, but we will keep it here since it is very
 /// useful in lots of places.
 pub async fn get_head(client: &Client) -> Hash {
-	let data: Option<StorageData> = client
-		.request("chain_getFinalizedHead", Params::None)
-		.await
-		.expect("get chain finalized head request failed");
+	let data: Option<StorageData> =
+		throttle::request(client, "chain_getFinalizedHead", Params::None)
+			.await
+			.expect("get chain finalized head request failed");
 	let now_raw = data.expect("Should always get the head hash").0;
 	<Hash as Decode>::decode(&mut &*now_raw).expect("Block hash should decode")
 }
 
+/// Get the latest best (possibly not finalized) head of the chain.
+pub async fn get_best_head(client: &Client) -> Hash {
+	let data: Option<Hash> = throttle::request(client, "chain_getBlockHash", Params::None)
+		.await
+		.expect("get chain best head request failed");
+	data.expect("Should always get the best head hash")
+}
+
+/// Get the hash of the block with the given number on the canonical chain of the node, if any.
+pub async fn get_block_hash(client: &Client, number: u32) -> Option<Hash> {
+	let number = to_json_value(number).expect("Block number serialization infallible");
+	throttle::request(client, "chain_getBlockHash", Params::Array(vec![number]))
+		.await
+		.expect("get chain block hash request failed")
+}
+
 /// Get the latest finalized head of the chain.
 ///
 /// This is technically not a storage operation but RPC, but we will keep it here since it is very
 /// useful in lots of places.
 pub async fn get_header<H: serde::de::DeserializeOwned>(client: &Client, at: Hash) -> Option<H> {
 	let at = to_json_value(at).expect("Block hash serialization infallible");
-	client
-		.request("chain_getHeader", Params::Array(vec![at]))
+	throttle::request(client, "chain_getHeader", Params::Array(vec![at]))
 		.await
 		.expect("get chain header request failed")
 }
@@ -250,8 +380,7 @@ pub async fn get_header<H: serde::de::DeserializeOwned>(client: &Client, at: Has
 /// Get the block at the the given hash.
 pub async fn get_block<B: serde::de::DeserializeOwned>(client: &Client, at: Hash) -> Option<B> {
 	let at = to_json_value(at).expect("Block hash serialization infallible");
-	client
-		.request("chain_getBlock", Params::Array(vec![at]))
+	throttle::request(client, "chain_getBlock", Params::Array(vec![at]))
 		.await
 		.expect("get chain block request failed")
 }
@@ -261,10 +390,10 @@ pub async fn get_block<B: serde::de::DeserializeOwned>(client: &Client, at: Hash
 /// Cannot fail. Runtime must always have some bytes as metadata.
 pub async fn get_metadata(client: &Client, at: Hash) -> sp_core::Bytes {
 	let at = to_json_value(at).expect("Block hash serialization infallible");
-	let data: Option<sp_core::Bytes> = client
-		.request("state_getMetadata", Params::Array(vec![at]))
-		.await
-		.expect("Failed to decode block");
+	let data: Option<sp_core::Bytes> =
+		throttle::request(client, "state_getMetadata", Params::Array(vec![at]))
+			.await
+			.expect("Failed to decode block");
 	data.expect("Metadata must exist")
 }
 
@@ -273,10 +402,10 @@ pub async fn get_metadata(client: &Client, at: Hash) -> sp_core::Bytes {
 /// Cannot fail. Runtime must always have some version.
 pub async fn get_runtime_version(client: &Client, at: Hash) -> sp_version::RuntimeVersion {
 	let at = to_json_value(at).expect("Block hash serialization infallible");
-	let data: Option<sp_version::RuntimeVersion> = client
-		.request("state_getRuntimeVersion", Params::Array(vec![at]))
-		.await
-		.expect("Failed to fetch version");
+	let data: Option<sp_version::RuntimeVersion> =
+		throttle::request(client, "state_getRuntimeVersion", Params::Array(vec![at]))
+			.await
+			.expect("Failed to fetch version");
 	data.expect("Version must exist")
 }
 
@@ -284,7 +413,7 @@ pub async fn get_runtime_version(client: &Client, at: Hash) -> sp_version::Runti
 pub async fn get_storage_size(key: StorageKey, client: &Client, at: Hash) -> Option<u64> {
 	let at = to_json_value(at).expect("Block hash serialization infallible");
 	let key = to_json_value(key).expect("extrinsic serialization infallible");
-	client.request("state_getStorageSize", Params::Array(vec![key, at])).await.unwrap()
+	throttle::request(client, "state_getStorageSize", Params::Array(vec![key, at])).await.unwrap()
 }
 
 #[cfg(test)]
@@ -391,6 +520,15 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn unhash_concat_works() {
+		use frame_support::{Blake2_128Concat, Identity, Twox64Concat};
+		let h160 = [7u8; 20];
+		assert_eq!(unhash_concat(&Twox64Concat::hash(&h160)), Some(&h160[..]));
+		assert_eq!(unhash_concat(&Blake2_128Concat::hash(&h160)), Some(&h160[..]));
+		assert_eq!(unhash_concat(&Identity::hash(&h160)), None);
+	}
+
 	#[test]
 	fn get_const_works() {
 		let client = block_on(test_client());
@@ -0,0 +1,92 @@
+//! Throttling of the outgoing rpc requests.
+//!
+//! Public endpoints are often rate limited, and ban clients that exceed their limit. All requests
+//! of this crate go through [`request`], which spaces them to at most [`set_max_rps`] requests per
+//! second, and retries with a jittered exponential backoff if the endpoint pushes back.
+
+use crate::Client;
+use jsonrpsee_types::{error::Error, jsonrpc::Params};
+use std::{
+	sync::atomic::{AtomicU32, AtomicU64, Ordering},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const LOG_TARGET: &'static str = "sub-storage";
+
+/// Maximum number of requests per second. Zero means unlimited.
+static MAX_RPS: AtomicU32 = AtomicU32::new(0);
+
+/// The earliest time at which the next request can be sent, in microseconds since the epoch.
+static NEXT_SLOT: AtomicU64 = AtomicU64::new(0);
+
+/// Number of times a request is retried before its error is returned.
+const MAX_RETRIES: u32 = 5;
+
+/// The base delay of the backoff, doubled on each retry.
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+
+fn since_epoch() -> Duration {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Limit the outgoing requests to `rps` per second. Zero means unlimited.
+pub fn set_max_rps(rps: u32) {
+	MAX_RPS.store(rps, Ordering::Relaxed);
+}
+
+/// Wait until the next request can be sent, and reserve its slot.
+async fn wait_for_slot() {
+	let rps = MAX_RPS.load(Ordering::Relaxed);
+	if rps == 0 {
+		return;
+	}
+
+	let interval = 1_000_000 / rps as u64;
+	let now = since_epoch().as_micros() as u64;
+	let previous = NEXT_SLOT
+		.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |next| Some(next.max(now) + interval))
+		.expect("closure always returns `Some`");
+	let slot = previous.max(now);
+	if slot > now {
+		async_std::task::sleep(Duration::from_micros(slot - now)).await;
+	}
+}
+
+/// Whether `error` is the endpoint pushing back, rather than the request being wrong.
+///
+/// A dropped connection is not retried: the client does not reconnect, so all later attempts would
+/// fail the same way.
+fn is_push_back(error: &Error) -> bool {
+	let error = error.to_string().to_lowercase();
+	["429", "too many requests", "rate limit", "timeout", "timed out"]
+		.iter()
+		.any(|e| error.contains(e))
+}
+
+/// A random jitter of up to half of `delay`. The source of randomness need not be good.
+fn jitter(delay: Duration) -> Duration {
+	let nanos = since_epoch().subsec_nanos();
+	delay / 2 * (nanos % 1000) / 1000
+}
+
+/// Send a request, throttled and retried.
+pub async fn request<T: serde::de::DeserializeOwned>(
+	client: &Client,
+	method: &str,
+	params: Params,
+) -> Result<T, Error> {
+	let mut retries = 0;
+	loop {
+		wait_for_slot().await;
+		match client.request(method, params.clone()).await {
+			Err(e) if retries < MAX_RETRIES && is_push_back(&e) => {
+				let delay = BACKOFF_BASE * 2u32.pow(retries);
+				let delay = delay + jitter(delay);
+				log::warn!(target: LOG_TARGET, "{} failed ({}), retrying in {:?}.", method, e, delay);
+				async_std::task::sleep(delay).await;
+				retries += 1;
+			}
+			outcome => return outcome,
+		}
+	}
+}
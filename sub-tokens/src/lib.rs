@@ -119,6 +119,16 @@ pub mod dynamic {
 		DECIMAL_POINTS.with(|v| *v.borrow_mut() = decimal);
 	}
 
+	/// Get the name of the currency token.
+	pub fn name() -> &'static str {
+		TOKEN_NAME.with(|v| *v.borrow())
+	}
+
+	/// Get the decimal points of the currency.
+	pub fn decimal_points() -> u128 {
+		DECIMAL_POINTS.with(|v| *v.borrow())
+	}
+
 	/// Wrapper to pretty-print currency token.
 	pub struct DynamicToken(u128);
 